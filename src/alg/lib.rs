@@ -9,36 +9,118 @@
 
 //			Add	Sub	Mul	Div	Neg	Inv
 //	Dual2		x	x	x	x	x	x
+//	DualN		x	x	x	x	x	x
+//	HyperDual	x	x	x	x	x	x
+//	Var		x	x	x	x	x	x
 //	Complex		x	x	x	x	x	x
+//	Transform2	-	-	x	-	-	x
 //	Quaternion	x	x	x	x	x	x
 //	Matrix4		x	x	x	x	x	x
+//	Matrix2		x	x	x	x	x	x
+//	Matrix3		x	x	x	x	x	x
+//	Matrix3x4	x	x	x	x	x	-
+//	Matrix4x3	x	x	x	x	x	-
+//	Vector3		x	x	x	-	x	-
+//	Vector4		x	x	x	-	x	-
 //	Vector		x	x	x	x	x	x
+//	Matrix		x	x	x	x	x	x
+//	CooMatrix	-	-	-	-	-	-
+//	CsrMatrix	x	x	-	-	x	-
+//	CscMatrix	x	x	-	-	x	-
 //	f32		std	std	std	std	std	x
 //	f64		std	std	std	std	std	x
 
-//			Det	NormSq
+//			Det	NormSq	PInv
+//	Dual2		x	x	-
+//	DualN		-	-	-
+//	HyperDual	-	-	-
+//	Var		-	x	-
+//	Complex		x	x	-
+//	Transform2	-	-	-
+//	Quaternion	-	x	-
+//	Matrix4		x	-	x
+//	Matrix2		x	-	-
+//	Matrix3		x	-	-
+//	Matrix3x4	-	-	-
+//	Matrix4x3	-	-	-
+//	Vector3		-	x	-
+//	Vector4		-	x	-
+//	Vector		-	x	-
+//	Matrix		x	x	x
+//	CooMatrix	-	-	-
+//	CsrMatrix	-	x	-
+//	CscMatrix	-	x	-
+//	f32		-	-	x
+//	f64		-	-	x
+
+//			Eq	Zero	One	Clone
+//	Dual2		x	x	x	-
+//	DualN		x	x	x	-
+//	HyperDual	x	x	x	-
+//	Var		-	-	-	x
+//	Complex		x	x	x	x
+//	Transform2	x	-	x	-
+//	Quaternion	x	x	x	-
+//	Matrix4		x	x	x	x
+//	Matrix2		x	x	x	-
+//	Matrix3		x	x	x	-
+//	Matrix3x4	x	x	-	-
+//	Matrix4x3	x	x	-	-
+//	Vector3		x	x	-	-
+//	Vector4		x	x	-	-
+//	Vector		x	-	-	x
+//	Matrix		x	-	-	x
+//	CooMatrix	x	-	-	x
+//	CsrMatrix	x	-	-	x
+//	CscMatrix	x	-	-	x
+
+//			TryInv	TryDiv
 //	Dual2		x	x
+//	DualN		x	x
+//	HyperDual	x	x
+//	Var		x	x
 //	Complex		x	x
-//	Quaternion	-	x
-//	Matrix4		x	-
-//	Vector		-	x
-
-//			Eq	Zero	One
-//	Dual2		x	x	x
-//	Complex		x	x	x
-//	Quaternion	x	x	x
-//	Matrix4		x	x	x
-//	Vector		x	-	-
+//	Transform2	x	-
+//	Quaternion	x	x
+//	Matrix4		x	x
+//	Matrix2		x	x
+//	Matrix3		x	x
+//	Matrix3x4	-	x
+//	Matrix4x3	-	x
+//	Vector		x	x
+//	Matrix		x	x
+//	f32		x	x
+//	f64		x	x
 
 //			Eps	Scale
 //	Dual2		x	x
+//	DualN		x	x
+//	HyperDual	x	x
+//	Var		-	-
 //	Complex		x	x
+//	Transform2	x	-
 //	Quaternion	x	x
 //	Matrix4		x	x
+//	Matrix2		x	x
+//	Matrix3		x	x
+//	Matrix3x4	x	-
+//	Matrix4x3	x	-
+//	Vector3		x	-
+//	Vector4		x	-
 //	Vector		x	-
+//	Matrix		x	-
+//	CooMatrix	-	-
+//	CsrMatrix	x	-
+//	CscMatrix	x	-
 //	f32		x	-
 //	f64		x	-
 
+//			LinearOperator
+//	Matrix		x
+//	CooMatrix	-
+//	CsrMatrix	x
+//	CscMatrix	x
+
 /// Computes the square of the norm/length.
 #[inline(always)]
 pub fn norm_sq<T: NormSq<U>, U>(a: T) -> U {a.norm_sq()} 
@@ -71,6 +153,87 @@ pub trait Inv<Result> {
 	fn inv(&self) -> Result;
 }
 
+/// Computes the Moore-Penrose pseudo-inverse.
+#[inline(always)]
+pub fn pinv<T: PInv<U>, U>(a: T, eps: f64) -> U {a.pinv(eps)}
+
+/// Implemented on structures that have a pseudo-inverse.
+/// The pseudo-inverse exists even when the structure is singular
+/// and equals the inverse when it is invertible.
+pub trait PInv<Result> {
+	/// Computes the Moore-Penrose pseudo-inverse.
+	/// Singular values not larger than the epsilon
+	/// times the largest singular value are treated as zero.
+	/// A scalar is its own singular value, so only zero is treated as zero.
+	fn pinv(&self, eps: f64) -> Result;
+}
+
+/// Creates an inverted version of the structure if it is invertible.
+#[inline(always)]
+pub fn try_inv<T: TryInv<U>, U>(a: T, eps: f64) -> Option<U> {a.try_inv(eps)}
+
+/// Implemented on structures that can be inverted when they are not singular.
+/// A matrix is singular when its smallest pivot relative to the largest
+/// is closer to zero than the epsilon.
+/// Other structures are singular when their squared norm
+/// or real part is closer to zero than the epsilon.
+pub trait TryInv<Result> {
+	/// Creates an inverted version of the structure if it is invertible.
+	fn try_inv(&self, eps: f64) -> Option<Result>;
+}
+
+/// Divides by a structure if it is invertible.
+#[inline(always)]
+pub fn try_div<T: TryDiv<Rhs, U>, Rhs, U>(a: T, b: Rhs, eps: f64) -> Option<U> {
+	a.try_div(&b, eps)
+}
+
+/// Implemented on structures that can be divided by another
+/// when it is not singular.
+pub trait TryDiv<Rhs, Result> {
+	/// Divides by a structure if it is invertible.
+	fn try_div(&self, rhs: &Rhs, eps: f64) -> Option<Result>;
+}
+
+/// Returns true if a value is closer to zero than the epsilon.
+#[inline(always)]
+fn is_singular<T: Eps + std::num::Zero>(a: &T, eps: f64) -> bool {
+	a.close_eps(&std::num::zero(), eps)
+}
+
+/// Computes the smallest absolute pivot of Gaussian elimination
+/// with partial pivoting relative to the largest.
+/// The ratio does not depend on the scale and is zero for singular matrices.
+fn pivot_ratio<T: std::num::Float>(a: ~[T], n: uint) -> T {
+	let zero: T = std::num::zero();
+	if n == 0 { return std::num::one(); }
+
+	let mut a = a;
+	let mut min = zero.clone();
+	let mut max = zero.clone();
+	for k in range(0, n) {
+		let mut p = k;
+		for i in range(k + 1, n) {
+			if a[i * n + k].abs() > a[p * n + k].abs() { p = i; }
+		}
+		for j in range(0, n) { a.swap(k * n + j, p * n + j); }
+
+		let pivot = a[k * n + k].clone();
+		if k == 0 || pivot.abs() < min { min = pivot.abs(); }
+		if pivot.abs() > max { max = pivot.abs(); }
+		if pivot == zero { continue; }
+		for i in range(k + 1, n) {
+			let f = a[i * n + k] / pivot;
+			for j in range(k + 1, n) {
+				let v = a[i * n + j] - f * a[k * n + j];
+				a[i * n + j] = v;
+			}
+		}
+	}
+
+	if max == zero { zero } else { min / max }
+}
+
 /// Checks for equality with a custom approximate epsilon.
 #[inline(always)]
 pub fn close_eps<T: Eps>(a: &T, other: &T, eps: f64) -> bool {
@@ -96,6 +259,27 @@ pub trait Scale<T> {
 	fn scale(factor: T) -> Self;
 }
 
+/// Multiplies a vector by a linear operator.
+#[inline(always)]
+pub fn apply<T, U: LinearOperator<T>>(a: &U, v: &Vector<T>) -> Vector<T> {a.apply(v)}
+
+/// Implemented on matrix-like structures that can multiply a vector.
+/// Iterative solvers only need this product.
+pub trait LinearOperator<T> {
+	/// Multiplies a vector by the operator.
+	fn apply(&self, v: &Vector<T>) -> Vector<T>;
+}
+
+/// Applies an approximate inverse to a vector.
+#[inline(always)]
+pub fn precondition<T, U: Preconditioner<T>>(p: &U, v: &Vector<T>) -> Vector<T> {p.precondition(v)}
+
+/// Implemented on approximate inverses that speed up iterative solvers.
+pub trait Preconditioner<T> {
+	/// Applies the approximate inverse to a vector.
+	fn precondition(&self, v: &Vector<T>) -> Vector<T>;
+}
+
 /// A Dual type is commonly used for automatic differentiation.
 #[deriving(Eq, Zero)]
 pub struct Dual2<T> {
@@ -122,6 +306,60 @@ Dual2<T> {
 	}
 }
 
+impl<T>
+Dual2<T> {
+	/// Sets the real part.
+	pub fn set_real(&mut self, x0: T) {
+		self.x0 = x0;
+	}
+
+	/// Sets the dual part.
+	pub fn set_dual(&mut self, x1: T) {
+		self.x1 = x1;
+	}
+
+	/// Creates a dual number from a tuple of the real and dual part.
+	pub fn from_tuple((x0, x1): (T, T)) -> Dual2<T> {
+		Dual2::new(x0, x1)
+	}
+
+	/// Destructures into a tuple of the real and dual part.
+	pub fn into_tuple(self) -> (T, T) {
+		let Dual2 { x0, x1 } = self;
+		(x0, x1)
+	}
+}
+
+impl<
+	T: Clone
+>
+Dual2<T> {
+	/// Returns the real part.
+	pub fn real(&self) -> T {
+		self.x0.clone()
+	}
+
+	/// Returns the dual part.
+	pub fn dual(&self) -> T {
+		self.x1.clone()
+	}
+
+	/// Creates a dual number from an array of the real and dual part.
+	pub fn from_array(a: [T, ..2]) -> Dual2<T> {
+		Dual2::new(a[0].clone(), a[1].clone())
+	}
+
+	/// Returns a tuple of the real and dual part.
+	pub fn to_tuple(&self) -> (T, T) {
+		(self.x0.clone(), self.x1.clone())
+	}
+
+	/// Returns an array of the real and dual part.
+	pub fn to_array(&self) -> [T, ..2] {
+		[self.x0.clone(), self.x1.clone()]
+	}
+}
+
 impl<
 	T: std::num::Zero
 >
@@ -254,102 +492,345 @@ Eps for Dual2<T> {
 	}
 }
 
-/// A Complex number is commonly used for rotations in 2D.
-#[deriving(Eq, Zero)]
-pub struct Complex<T> {
-	/// The real dimension of the complex number.
+impl<
+	T: std::num::Float
+>
+Dual2<T> {
+	/// Computes the square root.
+	/// The dual part is propagated by the chain rule.
+	pub fn sqrt(&self) -> Dual2<T> {
+		let s = self.x0.sqrt();
+		let two: T = std::num::one::<T>() + std::num::one();
+		let x1 = self.x1 / (two * s);
+		Dual2 {
+			x0: s,
+			x1: x1
+		}
+	}
+
+	/// Computes the natural exponential.
+	/// The dual part is propagated by the chain rule.
+	pub fn exp(&self) -> Dual2<T> {
+		let e = self.x0.exp();
+		let x1 = self.x1 * e;
+		Dual2 {
+			x0: e,
+			x1: x1
+		}
+	}
+
+	/// Computes the natural logarithm.
+	/// The dual part is propagated by the chain rule.
+	pub fn ln(&self) -> Dual2<T> {
+		Dual2 {
+			x0: self.x0.ln(),
+			x1: self.x1 / self.x0
+		}
+	}
+
+	/// Computes the sine.
+	/// The dual part is propagated by the chain rule.
+	pub fn sin(&self) -> Dual2<T> {
+		Dual2 {
+			x0: self.x0.sin(),
+			x1: self.x1 * self.x0.cos()
+		}
+	}
+
+	/// Computes the cosine.
+	/// The dual part is propagated by the chain rule.
+	pub fn cos(&self) -> Dual2<T> {
+		Dual2 {
+			x0: self.x0.cos(),
+			x1: -self.x1 * self.x0.sin()
+		}
+	}
+
+	/// Computes the tangent.
+	/// The dual part is propagated by the chain rule.
+	pub fn tan(&self) -> Dual2<T> {
+		let t = self.x0.tan();
+		let x1 = self.x1 * (std::num::one::<T>() + t * t);
+		Dual2 {
+			x0: t,
+			x1: x1
+		}
+	}
+
+	/// Computes the four quadrant arctangent of `self` (y) and `other` (x).
+	/// The dual part is propagated by the chain rule.
+	pub fn atan2(&self, other: &Dual2<T>) -> Dual2<T> {
+		let len2 = self.x0 * self.x0 + other.x0 * other.x0;
+		Dual2 {
+			x0: self.x0.atan2(&other.x0),
+			x1: (other.x0 * self.x1 - self.x0 * other.x1) / len2
+		}
+	}
+
+	/// Raises the number to a real power.
+	/// The dual part is propagated by the chain rule.
+	pub fn powf(&self, n: &T) -> Dual2<T> {
+		let n1 = *n - std::num::one();
+		Dual2 {
+			x0: self.x0.powf(n),
+			x1: self.x1 * *n * self.x0.powf(&n1)
+		}
+	}
+
+	/// Computes the absolute value.
+	/// The dual part is multiplied by the sign of the real part.
+	pub fn abs(&self) -> Dual2<T> {
+		Dual2 {
+			x0: self.x0.abs(),
+			x1: self.x1 * self.x0.signum()
+		}
+	}
+}
+
+impl<
+	T: Div<T, T> + Neg<T> + Mul<T, T> + Inv<T> + Eps + std::num::Zero
+>
+TryInv<Dual2<T>> for Dual2<T> {
+	fn try_inv(&self, eps: f64) -> Option<Dual2<T>> {
+		if is_singular(&self.x0, eps) {
+			None
+		} else {
+			Some(self.inv())
+		}
+	}
+}
+
+impl<
+	T: Div<T, T> + Sub<T, T> + Mul<T, T> + Eps + std::num::Zero
+>
+TryDiv<Dual2<T>, Dual2<T>> for Dual2<T> {
+	fn try_div(&self, rhs: &Dual2<T>, eps: f64) -> Option<Dual2<T>> {
+		if is_singular(&rhs.x0, eps) {
+			None
+		} else {
+			Some(self.div(rhs))
+		}
+	}
+}
+
+/// A multi-variable Dual type with one infinitesimal part per variable.
+/// It is commonly used for computing gradients in a single pass.
+/// An empty list of infinitesimal parts is treated as all zeros,
+/// also when comparing for equality.
+/// Arithmetic fails if both operands have non-empty infinitesimal parts
+/// of different lengths.
+pub struct DualN<T> {
+	/// The real part of Dual number.
 	x0: T,
-	/// The imaginary dimension of the complex number.
-	x1: T,
+	/// The infinitesimal parts of Dual number.
+	x1: Vector<T>,
 }
 
-/// Creates a new Complex number.
+/// Constructs a new multi-variable dual number.
 #[inline(always)]
-pub fn complex<T>(x0: T, x1: T) -> Complex<T> {
-	Complex::new(x0, x1)
+pub fn dual_n<T>(x0: T, x1: Vector<T>) -> DualN<T> {
+	DualN::new(x0, x1)
 }
 
-impl<T> 
-Complex<T> {
-	/// Creates a new Complex number.
-	pub fn new(x0: T, x1: T) -> Complex<T> {
-		Complex {
-			x0: x0, 
+impl<T>
+DualN<T> {
+	/// Constructs a new multi-variable dual number.
+	pub fn new(x0: T, x1: Vector<T>) -> DualN<T> {
+		DualN {
+			x0: x0,
 			x1: x1
 		}
 	}
 }
 
+impl<T>
+DualN<T> {
+	/// Sets the real part.
+	pub fn set_real(&mut self, x0: T) {
+		self.x0 = x0;
+	}
+
+	/// Sets the infinitesimal parts.
+	pub fn set_dual(&mut self, x1: Vector<T>) {
+		self.x1 = x1;
+	}
+
+	/// Creates a dual number from a tuple of the real and infinitesimal parts.
+	pub fn from_tuple((x0, x1): (T, Vector<T>)) -> DualN<T> {
+		DualN::new(x0, x1)
+	}
+
+	/// Destructures into a tuple of the real and infinitesimal parts.
+	pub fn into_tuple(self) -> (T, Vector<T>) {
+		let DualN { x0, x1 } = self;
+		(x0, x1)
+	}
+}
+
+impl<
+	T: Clone
+>
+DualN<T> {
+	/// Returns the real part.
+	pub fn real(&self) -> T {
+		self.x0.clone()
+	}
+
+	/// Returns the infinitesimal parts.
+	pub fn dual(&self) -> Vector<T> {
+		self.x1.clone()
+	}
+
+	/// Returns a tuple of the real and infinitesimal parts.
+	pub fn to_tuple(&self) -> (T, Vector<T>) {
+		(self.x0.clone(), self.x1.clone())
+	}
+}
+
+impl<
+	T: std::num::Zero + std::num::One
+>
+DualN<T> {
+	/// Constructs the `i`th of `n` independent variables.
+	/// The infinitesimal part is one for `i` and zero for the rest.
+	pub fn variable(x0: T, i: uint, n: uint) -> DualN<T> {
+		DualN {
+			x0: x0,
+			x1: Vector {
+				x: std::vec::from_fn(n, |j| if i == j {
+					std::num::one()
+				} else {
+					std::num::zero()
+				})
+			}
+		}
+	}
+}
+
+/// Returns the number of infinitesimal parts of the result of an operation.
+fn dual_n_len<T>(a: &Vector<T>, b: &Vector<T>) -> uint {
+	let n = a.x.len();
+	let m = b.x.len();
+	if n > m { n } else { m }
+}
+
+/// Returns the infinitesimal parts padded with zeros to length `n`.
+fn dual_n_parts<T: std::num::Zero + Clone>(a: &Vector<T>, n: uint) -> ~[T] {
+	let m = a.x.len();
+	if m == n {
+		a.x.clone()
+	} else if m == 0 {
+		std::vec::from_fn(n, |_| std::num::zero())
+	} else {
+		fail!("DualN infinitesimal parts not of same length");
+	}
+}
+
 impl<
 	T: std::num::Zero
 >
-Scale<T> for Complex<T> {
-	fn scale(factor: T) -> Complex<T> {
-		Complex {
+Scale<T> for DualN<T> {
+	fn scale(factor: T) -> DualN<T> {
+		DualN {
 			x0: factor,
-			x1: std::num::zero()
+			x1: Vector {
+				x: ~[]
+			}
 		}
 	}
 }
 
 impl<
-	T: Add<T, T>
+	T: Add<T, T> + std::num::Zero + Clone
 >
-Add<Complex<T>, Complex<T>> for Complex<T> {
-	fn add(&self, rhs: &Complex<T>) -> Complex<T> {
-		Complex {
+Add<DualN<T>, DualN<T>> for DualN<T> {
+	fn add(&self, rhs: &DualN<T>) -> DualN<T> {
+		let n = dual_n_len(&self.x1, &rhs.x1);
+		let a = Vector::new(dual_n_parts(&self.x1, n));
+		let b = Vector::new(dual_n_parts(&rhs.x1, n));
+		DualN {
 			x0: self.x0 + rhs.x0,
-			x1: self.x1 + rhs.x1
+			x1: a + b
 		}
 	}
 }
 
 impl<
-	T: Sub<T, T>
-> 
-Sub<Complex<T>, Complex<T>> for Complex<T> {
-	fn sub(&self, rhs: &Complex<T>) -> Complex<T> {
-		Complex {
+	T: Sub<T, T> + std::num::Zero + Clone
+>
+Sub<DualN<T>, DualN<T>> for DualN<T> {
+	fn sub(&self, rhs: &DualN<T>) -> DualN<T> {
+		let n = dual_n_len(&self.x1, &rhs.x1);
+		let a = Vector::new(dual_n_parts(&self.x1, n));
+		let b = Vector::new(dual_n_parts(&rhs.x1, n));
+		DualN {
 			x0: self.x0 - rhs.x0,
-			x1: self.x1 - rhs.x1
+			x1: a - b
 		}
 	}
 }
 
 impl<
-	T: Mul<T, T> + Add<T, T> + Sub<T, T>
+	T: Mul<T, T> + Add<T, T> + std::num::Zero + Clone
 >
-Mul<Complex<T>, Complex<T>> for Complex<T> {
-	fn mul(&self, rhs: &Complex<T>) -> Complex<T> {
-		Complex { 
-			x0: self.x0 * rhs.x0 - self.x1 * rhs.x1,
-			x1: self.x0 * rhs.x1 + self.x1 * rhs.x0 
+Mul<DualN<T>, DualN<T>> for DualN<T> {
+	fn mul(&self, rhs: &DualN<T>) -> DualN<T> {
+		let n = dual_n_len(&self.x1, &rhs.x1);
+		let a = dual_n_parts(&self.x1, n);
+		let b = dual_n_parts(&rhs.x1, n);
+		let mut res: ~[T] = ~[];
+		for i in range(0, n) {
+			res.push(self.x0 * b[i] + a[i] * rhs.x0);
+		}
+
+		DualN {
+			x0: self.x0 * rhs.x0,
+			x1: Vector {
+				x: res
+			}
 		}
 	}
 }
 
 impl<
-	T: Div<T, T> + Add<T, T> + Sub<T, T> + Mul<T, T>
+	T: Div<T, T> + Sub<T, T> + Mul<T, T> + std::num::Zero + Clone
 >
-Div<Complex<T>, Complex<T>> for Complex<T> {
-	fn div(&self, rhs: &Complex<T>) -> Complex<T> {
-		let len2 = rhs.x0 * rhs.x0 + rhs.x1 * rhs.x1;
-		Complex {
-			x0: (self.x0 * rhs.x0 + self.x1 * rhs.x1) / len2,
-			x1: (self.x1 * rhs.x0 - self.x0 * rhs.x1) / len2
+Div<DualN<T>, DualN<T>> for DualN<T> {
+	fn div(&self, rhs: &DualN<T>) -> DualN<T> {
+		let n = dual_n_len(&self.x1, &rhs.x1);
+		let a = dual_n_parts(&self.x1, n);
+		let b = dual_n_parts(&rhs.x1, n);
+		let b2 = rhs.x0 * rhs.x0;
+		let mut res: ~[T] = ~[];
+		for i in range(0, n) {
+			res.push((a[i] * rhs.x0 - self.x0 * b[i]) / b2);
+		}
+
+		DualN {
+			x0: self.x0 / rhs.x0,
+			x1: Vector {
+				x: res
+			}
 		}
 	}
 }
 
 impl<
-	T: Add<T, T> + Mul<T, T> + Div<T, T> + Neg<T>
+	T: Div<T, T> + Neg<T> + Mul<T, T> + Inv<T>
 >
-Inv<Complex<T>> for Complex<T> {
-	fn inv(&self) -> Complex<T> {
-		let len2 = self.x0 * self.x0 + self.x1 * self.x1;
-		Complex {
-			x0: self.x0 / len2,
-			x1: -self.x1 / len2
+Inv<DualN<T>> for DualN<T> {
+	fn inv(&self) -> DualN<T> {
+		let b2 = self.x0 * self.x0;
+		let mut res: ~[T] = ~[];
+		for x in self.x1.x.iter() {
+			res.push(-x / b2);
+		}
+
+		DualN {
+			x0: self.x0.inv(),
+			x1: Vector {
+				x: res
+			}
 		}
 	}
 }
@@ -357,106 +838,229 @@ Inv<Complex<T>> for Complex<T> {
 impl<
 	T: Neg<T>
 >
-Neg<Complex<T>> for Complex<T> {
-	fn neg(&self) -> Complex<T> {
-		Complex {
-			x0: -self.x0, 
+Neg<DualN<T>> for DualN<T> {
+	fn neg(&self) -> DualN<T> {
+		DualN {
+			x0: -self.x0,
 			x1: -self.x1
 		}
 	}
 }
 
 impl<
-	T: Mul<T, T> + Add<T, T>
+	T: std::num::Zero
 >
-NormSq<T> for Complex<T> {
-	fn norm_sq(&self) -> T {
-		self.x0 * self.x0 + self.x1 * self.x1
+std::num::Zero for DualN<T> {
+	fn zero() -> DualN<T> {
+		DualN {
+			x0: std::num::zero(),
+			x1: Vector {
+				x: ~[]
+			}
+		}
+	}
+
+	fn is_zero(&self) -> bool {
+		self.x0.is_zero() && self.x1.x.iter().all(|x| x.is_zero())
 	}
 }
 
 impl<
-	T: std::num::One + std::num::Zero
+	T: std::num::One
 >
-std::num::One for Complex<T> {
-	fn one() -> Complex<T> {
-		Complex {
+std::num::One for DualN<T> {
+	fn one() -> DualN<T> {
+		DualN {
 			x0: std::num::one(),
-			x1: std::num::zero()
+			x1: Vector {
+				x: ~[]
+			}
 		}
 	}
 }
 
 impl<
-	T: Eps
+	T: Eq + std::num::Zero
 >
-Eps for Complex<T> {
-	fn close_eps(&self, other: &Complex<T>, eps: f64) -> bool {
-		if !self.x0.close_eps(&other.x0, eps) { 
-			false 
-		} else if !self.x1.close_eps(&other.x1, eps) { 
-			false 
-		} else { 
-			true 
+Eq for DualN<T> {
+	fn eq(&self, other: &DualN<T>) -> bool {
+		let a = &self.x1.x;
+		let b = &other.x1.x;
+		if self.x0 != other.x0 {
+			false
+		} else if a.len() == b.len() {
+			a == b
+		} else if a.len() == 0 {
+			b.iter().all(|x| x.is_zero())
+		} else if b.len() == 0 {
+			a.iter().all(|x| x.is_zero())
+		} else {
+			false
 		}
 	}
 }
 
 impl<
-	T: Mul<T, T> + Add<T, T>
+	T: Eps + std::num::Zero + Clone
 >
-Det<T> 
-for Complex<T> {
-	fn det(&self) -> T {
-		self.x0 * self.x0 + self.x1 * self.x1
+Eps for DualN<T> {
+	fn close_eps(&self, other: &DualN<T>, eps: f64) -> bool {
+		let n = dual_n_len(&self.x1, &other.x1);
+		let a = Vector::new(dual_n_parts(&self.x1, n));
+		let b = Vector::new(dual_n_parts(&other.x1, n));
+		if !self.x0.close_eps(&other.x0, eps) {
+			false
+		} else if !a.close_eps(&b, eps) {
+			false
+		} else {
+			true
+		}
 	}
 }
 
-/// A Quaternion type is commonly used for rotations in 3D.
+impl<
+	T: Div<T, T> + Neg<T> + Mul<T, T> + Inv<T> + Eps + std::num::Zero
+>
+TryInv<DualN<T>> for DualN<T> {
+	fn try_inv(&self, eps: f64) -> Option<DualN<T>> {
+		if is_singular(&self.x0, eps) {
+			None
+		} else {
+			Some(self.inv())
+		}
+	}
+}
+
+impl<
+	T: Div<T, T> + Sub<T, T> + Mul<T, T> + Clone + Eps + std::num::Zero
+>
+TryDiv<DualN<T>, DualN<T>> for DualN<T> {
+	fn try_div(&self, rhs: &DualN<T>, eps: f64) -> Option<DualN<T>> {
+		if is_singular(&rhs.x0, eps) {
+			None
+		} else {
+			Some(self.div(rhs))
+		}
+	}
+}
+
+/// A hyper-dual type with two infinitesimal parts and their product.
+/// It is commonly used for computing exact second derivatives.
 #[deriving(Eq, Zero)]
-pub struct Quaternion<T> {
-	/// The x-dimension of the quaternion.
-	x: T,
-	/// The y-dimension of the quaternion.
-	y: T,
-	/// The z-dimension of the quaternion.
-	z: T,
-	/// The scalar component of the quaternion.
-	w: T,
+pub struct HyperDual<T> {
+	/// The real part of hyper-dual number.
+	x0: T,
+	/// The first infinitesimal part of hyper-dual number.
+	x1: T,
+	/// The second infinitesimal part of hyper-dual number.
+	x2: T,
+	/// The part for the product of both infinitesimals.
+	x12: T,
 }
 
-/// Creates a new Quaternion.
-/// Notice that the scalar component is after the vector.
+/// Constructs a new hyper-dual number.
 #[inline(always)]
-pub fn quaternion<T>(x: T, y: T, z: T, w: T) -> Quaternion<T> {
-	Quaternion::new(x, y, z, w)
+pub fn hyper_dual<T>(x0: T, x1: T, x2: T, x12: T) -> HyperDual<T> {
+	HyperDual::new(x0, x1, x2, x12)
 }
 
-impl<T> 
-Quaternion<T> {
-	/// Creates a new Quaternion.
-	/// Notice that the scalar component is after the vector.
-	pub fn new(x: T, y: T, z: T, w: T) -> Quaternion<T> {
-		Quaternion {
-			x: x, 
-			y: y, 
-			z: z, 
-			w: w 
+impl<T>
+HyperDual<T> {
+	/// Constructs a new hyper-dual number.
+	pub fn new(x0: T, x1: T, x2: T, x12: T) -> HyperDual<T> {
+		HyperDual {
+			x0: x0,
+			x1: x1,
+			x2: x2,
+			x12: x12
 		}
 	}
 }
 
+impl<T>
+HyperDual<T> {
+	/// Sets the real part.
+	pub fn set_real(&mut self, x0: T) {
+		self.x0 = x0;
+	}
+
+	/// Sets the first infinitesimal part.
+	pub fn set_eps1(&mut self, x1: T) {
+		self.x1 = x1;
+	}
+
+	/// Sets the second infinitesimal part.
+	pub fn set_eps2(&mut self, x2: T) {
+		self.x2 = x2;
+	}
+
+	/// Sets the part for the product of both infinitesimals.
+	pub fn set_eps12(&mut self, x12: T) {
+		self.x12 = x12;
+	}
+
+	/// Creates a hyper-dual number from a tuple of its parts.
+	pub fn from_tuple((x0, x1, x2, x12): (T, T, T, T)) -> HyperDual<T> {
+		HyperDual::new(x0, x1, x2, x12)
+	}
+
+	/// Destructures into a tuple of its parts.
+	pub fn into_tuple(self) -> (T, T, T, T) {
+		let HyperDual { x0, x1, x2, x12 } = self;
+		(x0, x1, x2, x12)
+	}
+}
+
+impl<
+	T: Clone
+>
+HyperDual<T> {
+	/// Returns the real part.
+	pub fn real(&self) -> T {
+		self.x0.clone()
+	}
+
+	/// Returns the first infinitesimal part.
+	pub fn eps1(&self) -> T {
+		self.x1.clone()
+	}
+
+	/// Returns the second infinitesimal part.
+	pub fn eps2(&self) -> T {
+		self.x2.clone()
+	}
+
+	/// Returns the part for the product of both infinitesimals.
+	pub fn eps12(&self) -> T {
+		self.x12.clone()
+	}
+
+	/// Creates a hyper-dual number from an array of its parts.
+	pub fn from_array(a: [T, ..4]) -> HyperDual<T> {
+		HyperDual::new(a[0].clone(), a[1].clone(), a[2].clone(), a[3].clone())
+	}
+
+	/// Returns a tuple of its parts.
+	pub fn to_tuple(&self) -> (T, T, T, T) {
+		(self.x0.clone(), self.x1.clone(), self.x2.clone(), self.x12.clone())
+	}
+
+	/// Returns an array of its parts.
+	pub fn to_array(&self) -> [T, ..4] {
+		[self.x0.clone(), self.x1.clone(), self.x2.clone(), self.x12.clone()]
+	}
+}
+
 impl<
 	T: std::num::Zero
 >
-Scale<T> 
-for Quaternion<T> {
-	fn scale(factor: T) -> Quaternion<T> {
-		Quaternion { 
-			x: std::num::zero(),
-			y: std::num::zero(),
-			z: std::num::zero(),
-			w: factor 
+Scale<T> for HyperDual<T> {
+	fn scale(factor: T) -> HyperDual<T> {
+		HyperDual {
+			x0: factor,
+			x1: std::num::zero(),
+			x2: std::num::zero(),
+			x12: std::num::zero()
 		}
 	}
 }
@@ -464,14 +1068,13 @@ for Quaternion<T> {
 impl<
 	T: Add<T, T>
 >
-Add<Quaternion<T>, Quaternion<T>> 
-for Quaternion<T> {
-	fn add(&self, rhs: &Quaternion<T>) -> Quaternion<T> {
-		Quaternion { 
-			x: self.x + rhs.x,
-			y: self.y + rhs.y,
-			z: self.z + rhs.z,
-			w: self.w + rhs.w,
+Add<HyperDual<T>, HyperDual<T>> for HyperDual<T> {
+	fn add(&self, rhs: &HyperDual<T>) -> HyperDual<T> {
+		HyperDual {
+			x0: self.x0 + rhs.x0,
+			x1: self.x1 + rhs.x1,
+			x2: self.x2 + rhs.x2,
+			x12: self.x12 + rhs.x12
 		}
 	}
 }
@@ -479,91 +1082,57 @@ for Quaternion<T> {
 impl<
 	T: Sub<T, T>
 >
-Sub<Quaternion<T>, Quaternion<T>> 
-for Quaternion<T> {
-	fn sub(&self, rhs: &Quaternion<T>) -> Quaternion<T> {
-		Quaternion { 
-			x: self.x - rhs.x,
-			y: self.y - rhs.y,
-			z: self.z - rhs.z,
-			w: self.w - rhs.w
+Sub<HyperDual<T>, HyperDual<T>> for HyperDual<T> {
+	fn sub(&self, rhs: &HyperDual<T>) -> HyperDual<T> {
+		HyperDual {
+			x0: self.x0 - rhs.x0,
+			x1: self.x1 - rhs.x1,
+			x2: self.x2 - rhs.x2,
+			x12: self.x12 - rhs.x12
 		}
 	}
 }
 
 impl<
-	T: Mul<T, T> + Sub<T, T> + Add<T, T>
+	T: Mul<T, T> + Add<T, T>
 >
-Mul<Quaternion<T>, Quaternion<T>> 
-for Quaternion<T> {
-	fn mul(&self, rhs: &Quaternion<T>) -> Quaternion<T> {
-		Quaternion {
-			x: 	self.w*rhs.x
-				-self.z*rhs.y
-				+self.y*rhs.z
-				+self.x*rhs.w,
-			y: 	self.z*rhs.x
-				+self.w*rhs.y
-				-self.x*rhs.z
-				+self.y*rhs.w,
-			z: 	self.x*rhs.y
-				-self.y*rhs.x
-				+self.w*rhs.z
-				+self.z*rhs.w,
-			w: 	self.w*rhs.w
-				-self.x*rhs.x
-				-self.y*rhs.y
-				-self.z*rhs.z,
+Mul<HyperDual<T>, HyperDual<T>> for HyperDual<T> {
+	fn mul(&self, rhs: &HyperDual<T>) -> HyperDual<T> {
+		HyperDual {
+			x0: self.x0 * rhs.x0,
+			x1: self.x0 * rhs.x1 + self.x1 * rhs.x0,
+			x2: self.x0 * rhs.x2 + self.x2 * rhs.x0,
+			x12: self.x0 * rhs.x12
+				+ self.x1 * rhs.x2
+				+ self.x2 * rhs.x1
+				+ self.x12 * rhs.x0
 		}
 	}
 }
 
 impl<
-	T: Mul<T, T> + Sub<T, T> + Add<T, T> + Div<T, T>
+	T: Add<T, T> + Sub<T, T> + Mul<T, T> + Div<T, T> + Neg<T> + Inv<T>
 >
-Div<Quaternion<T>, Quaternion<T>> 
-for Quaternion<T> {
-	fn div(&self, rhs: &Quaternion<T>) -> Quaternion<T> {
-		let len2 = rhs.x * rhs.x 
-			+ rhs.y * rhs.y 
-			+ rhs.z * rhs.z
-			+ rhs.w * rhs.w;
-		Quaternion {
-			x: 	(self.z*rhs.y
-				-self.w*rhs.x
-				-self.y*rhs.z
-				+self.x*rhs.w) / len2,
-			y: 	(self.x*rhs.z	
-				-self.z*rhs.x
-				-self.w*rhs.y
-				+self.y*rhs.w) / len2,
-			z: 	(self.y*rhs.x
-				-self.x*rhs.y
-				-self.w*rhs.z
-				+self.z*rhs.w) / len2,
-			w: 	(self.w*rhs.w
-				+self.x*rhs.x
-				+self.y*rhs.y
-				+self.z*rhs.z) / len2,
-		}
+Div<HyperDual<T>, HyperDual<T>> for HyperDual<T> {
+	fn div(&self, rhs: &HyperDual<T>) -> HyperDual<T> {
+		let inv = rhs.inv();
+		self * inv
 	}
 }
 
 impl<
-	T: Mul<T, T> + Add<T, T> + Div<T, T> + Neg<T>
+	T: Add<T, T> + Sub<T, T> + Mul<T, T> + Div<T, T> + Neg<T> + Inv<T>
 >
-Inv<Quaternion<T>> 
-for Quaternion<T> {
-	fn inv(&self) -> Quaternion<T> {
-		let len2 = self.x * self.x 
-			+ self.y * self.y 
-			+ self.z * self.z
-			+ self.w * self.w;
-		Quaternion {
-			x: 	-self.x / len2,
-			y: 	-self.y / len2,
-			z: 	-self.z / len2,
-			w: 	self.w / len2,
+Inv<HyperDual<T>> for HyperDual<T> {
+	fn inv(&self) -> HyperDual<T> {
+		let b2 = self.x0 * self.x0;
+		let b3 = b2 * self.x0;
+		HyperDual {
+			x0: self.x0.inv(),
+			x1: -self.x1 / b2,
+			x2: -self.x2 / b2,
+			x12: (self.x1 * self.x2 + self.x1 * self.x2) / b3
+				- self.x12 / b2
 		}
 	}
 }
@@ -571,753 +1140,8966 @@ for Quaternion<T> {
 impl<
 	T: Neg<T>
 >
-Neg<Quaternion<T>> 
-for Quaternion<T> {
-	fn neg(&self) -> Quaternion<T> {
-		Quaternion { 
-			x: -self.x,
-			y: -self.y,
-			z: -self.z,
-			w: -self.w 
+Neg<HyperDual<T>> for HyperDual<T> {
+	fn neg(&self) -> HyperDual<T> {
+		HyperDual {
+			x0: -self.x0,
+			x1: -self.x1,
+			x2: -self.x2,
+			x12: -self.x12
 		}
 	}
 }
 
 impl<
-	T: Add<T, T> + Mul<T, T>
+	T: std::num::One + std::num::Zero
 >
-NormSq<T> 
-for Quaternion<T> {
-	fn norm_sq(&self) -> T {
-		self.x * self.x
-		+ self.y * self.y
-		+ self.z * self.z
-		+ self.w * self.w
+std::num::One for HyperDual<T> {
+	fn one() -> HyperDual<T> {
+		HyperDual {
+			x0: std::num::one(),
+			x1: std::num::zero(),
+			x2: std::num::zero(),
+			x12: std::num::zero()
+		}
 	}
 }
 
 impl<
-	T: std::num::One + std::num::Zero
+	T: Eps
 >
-std::num::One 
-for Quaternion<T> {
-	fn one() -> Quaternion<T> {
-		Quaternion { 
-			x: std::num::zero(),
-			y: std::num::zero(),
-			z: std::num::zero(),
-			w: std::num::one() 
+Eps for HyperDual<T> {
+	fn close_eps(&self, other: &HyperDual<T>, eps: f64) -> bool {
+		self.x0.close_eps(&other.x0, eps)
+		&& self.x1.close_eps(&other.x1, eps)
+		&& self.x2.close_eps(&other.x2, eps)
+		&& self.x12.close_eps(&other.x12, eps)
+	}
+}
+
+impl<
+	T: Add<T, T> + Sub<T, T> + Mul<T, T> + Div<T, T> + Neg<T> + Inv<T> + Eps + std::num::Zero
+>
+TryInv<HyperDual<T>> for HyperDual<T> {
+	fn try_inv(&self, eps: f64) -> Option<HyperDual<T>> {
+		if is_singular(&self.x0, eps) {
+			None
+		} else {
+			Some(self.inv())
 		}
 	}
 }
 
 impl<
-	T: Eps
+	T: Add<T, T> + Sub<T, T> + Mul<T, T> + Div<T, T> + Neg<T> + Inv<T> + Eps + std::num::Zero
 >
-Eps 
-for Quaternion<T> {
-	fn close_eps(&self, other: &Quaternion<T>, eps: f64) -> bool {
-		if !self.x.close_eps(&other.x, eps) { 
-			false 
-		} else if !self.y.close_eps(&other.y, eps) { 
-			false 
-		} else if !self.z.close_eps(&other.z, eps) { 
-			false 
-		} else if !self.w.close_eps(&other.w, eps) { 
-			false 
-		} else { 
-			true 
+TryDiv<HyperDual<T>, HyperDual<T>> for HyperDual<T> {
+	fn try_div(&self, rhs: &HyperDual<T>, eps: f64) -> Option<HyperDual<T>> {
+		if is_singular(&rhs.x0, eps) {
+			None
+		} else {
+			Some(self.div(rhs))
 		}
 	}
 }
 
-/// A Matrix4 is commonly used for linear transformations in 3D space.
-#[deriving(Eq, Zero)]
-pub struct Matrix4<T> {
-	/// Element at first row and first column.
-	m11: T, 
-	/// Element at first row and second column.
-	m12: T, 
-	/// Element at first row and third column.
-	m13: T, 
-	/// Element at first row and fourth column.
-	m14: T,
-	
-	/// Element at second row and first column.
-	m21: T, 
-	/// Element at second row and second column.
-	m22: T, 
-	/// Element at second row and third column.
-	m23: T, 
-	/// Element at second row and fourth column.
-	m24: T,
-	
-	/// Element at third row and first column.
-	m31: T, 
-	/// Element at third row and second column.
-	m32: T, 
-	/// Element at third row and third column.
-	m33: T, 
-	/// Element at third row and fourth column.
-	m34: T,
-	
-	/// Element at fourth row and first column.
-	m41: T, 
-	/// Element at fourth row and second column.
-	m42: T, 
-	/// Element at fourt row and third column.
-	m43: T, 
-	/// Element at fourth row and fourth column.
-	m44: T
+/// A node on the tape contains the local partial derivatives
+/// with respect to the nodes it was computed from.
+struct TapeNode<T> {
+	/// Pairs of node index and local partial derivative.
+	deps: ~[(uint, T)],
 }
 
-/// Creates a new matrix with elements.
+/// The shared state of a tape.
+struct TapeData<T> {
+	/// The recorded nodes in order of evaluation.
+	nodes: ~[TapeNode<T>],
+	/// The node indices of the input variables.
+	inputs: ~[uint],
+}
+
+/// A Tape records operations for reverse-mode automatic differentiation.
+/// It is commonly used for functions with many inputs and one output.
+pub struct Tape<T> {
+	/// The recorded operations, shared with the variables.
+	data: @std::cell::RefCell<TapeData<T>>,
+}
+
+/// Creates a new empty tape.
 #[inline(always)]
-pub fn matrix4<T>(
-	m11: T, m12: T, m13: T, m14: T,
-	m21: T, m22: T, m23: T, m24: T,
-	m31: T, m32: T, m33: T, m34: T,
-	m41: T, m42: T, m43: T, m44: T
+pub fn tape<T: 'static>() -> Tape<T> {
+	Tape::new()
+}
+
+impl<
+	T: 'static
+>
+Tape<T> {
+	/// Creates a new empty tape.
+	pub fn new() -> Tape<T> {
+		Tape {
+			data: @std::cell::RefCell::new(TapeData {
+				nodes: ~[],
+				inputs: ~[]
+			})
+		}
+	}
+
+	/// Creates a new input variable on the tape.
+	/// The gradient contains one partial derivative per input in order of creation.
+	pub fn var(&self, value: T) -> Var<T> {
+		let mut borrow = self.data.borrow_mut();
+		let data = borrow.get();
+		let index = data.nodes.len();
+		data.nodes.push(TapeNode {
+			deps: ~[]
+		});
+		data.inputs.push(index);
+		Var {
+			tape: self.data,
+			index: index,
+			value: value
+		}
+	}
+
+	/// Returns the number of recorded nodes.
+	pub fn len(&self) -> uint {
+		let borrow = self.data.borrow();
+		borrow.get().nodes.len()
+	}
+}
+
+/// A Var is a value recorded on a tape.
+/// Operations on variables are recorded for computing the gradient.
+#[deriving(Clone)]
+pub struct Var<T> {
+	/// The tape the variable is recorded on.
+	tape: @std::cell::RefCell<TapeData<T>>,
+	/// The index of the node on the tape.
+	index: uint,
+	/// The value of the variable.
+	value: T,
+}
+
+impl<
+	T: Clone + 'static
+>
+Var<T> {
+	/// Returns the value of the variable.
+	pub fn value(&self) -> T {
+		self.value.clone()
+	}
+
+	/// Records a new node computed from other nodes.
+	fn push(&self, value: T, deps: ~[(uint, T)]) -> Var<T> {
+		let mut borrow = self.tape.borrow_mut();
+		let data = borrow.get();
+		let index = data.nodes.len();
+		data.nodes.push(TapeNode {
+			deps: deps
+		});
+		Var {
+			tape: self.tape,
+			index: index,
+			value: value
+		}
+	}
+
+	/// Fails if the variables are not recorded on the same tape.
+	fn check_tape(&self, other: &Var<T>) {
+		if !std::managed::ptr_eq(self.tape, other.tape) {
+			fail!("Variables not on same tape");
+		}
+	}
+}
+
+impl<
+	T: std::num::Float + 'static
+>
+Var<T> {
+	/// Computes the partial derivatives with respect to the input variables.
+	pub fn grad(&self) -> Vector<T> {
+		let borrow = self.tape.borrow();
+		let data = borrow.get();
+		let mut adj: ~[T] = std::vec::from_elem(
+			data.nodes.len(), std::num::zero());
+		adj[self.index] = std::num::one();
+		let mut i = self.index + 1;
+		while i > 0 {
+			i -= 1;
+			for dep in data.nodes[i].deps.iter() {
+				let (j, ref w) = *dep;
+				let d = adj[i] * *w;
+				adj[j] = adj[j] + d;
+			}
+		}
+
+		Vector {
+			x: data.inputs.iter().map(|&k| adj[k].clone()).collect()
+		}
+	}
+}
+
+impl<
+	T: std::num::Float + 'static
+>
+Add<Var<T>, Var<T>> for Var<T> {
+	fn add(&self, rhs: &Var<T>) -> Var<T> {
+		self.check_tape(rhs);
+		self.push(self.value + rhs.value, ~[
+			(self.index, std::num::one()),
+			(rhs.index, std::num::one())
+		])
+	}
+}
+
+impl<
+	T: std::num::Float + 'static
+>
+Sub<Var<T>, Var<T>> for Var<T> {
+	fn sub(&self, rhs: &Var<T>) -> Var<T> {
+		self.check_tape(rhs);
+		self.push(self.value - rhs.value, ~[
+			(self.index, std::num::one()),
+			(rhs.index, -std::num::one::<T>())
+		])
+	}
+}
+
+impl<
+	T: std::num::Float + 'static
+>
+Mul<Var<T>, Var<T>> for Var<T> {
+	fn mul(&self, rhs: &Var<T>) -> Var<T> {
+		self.check_tape(rhs);
+		self.push(self.value * rhs.value, ~[
+			(self.index, rhs.value.clone()),
+			(rhs.index, self.value.clone())
+		])
+	}
+}
+
+impl<
+	T: std::num::Float + 'static
+>
+Div<Var<T>, Var<T>> for Var<T> {
+	fn div(&self, rhs: &Var<T>) -> Var<T> {
+		self.check_tape(rhs);
+		let b2 = rhs.value * rhs.value;
+		self.push(self.value / rhs.value, ~[
+			(self.index, std::num::one::<T>() / rhs.value),
+			(rhs.index, -self.value / b2)
+		])
+	}
+}
+
+impl<
+	T: std::num::Float + 'static
+>
+Inv<Var<T>> for Var<T> {
+	fn inv(&self) -> Var<T> {
+		let b2 = self.value * self.value;
+		self.push(std::num::one::<T>() / self.value, ~[
+			(self.index, -std::num::one::<T>() / b2)
+		])
+	}
+}
+
+impl<
+	T: std::num::Float + 'static
+>
+Neg<Var<T>> for Var<T> {
+	fn neg(&self) -> Var<T> {
+		self.push(-self.value, ~[
+			(self.index, -std::num::one::<T>())
+		])
+	}
+}
+
+impl<
+	T: std::num::Float + 'static
+>
+NormSq<Var<T>> for Var<T> {
+	fn norm_sq(&self) -> Var<T> {
+		self.push(self.value * self.value, ~[
+			(self.index, self.value + self.value)
+		])
+	}
+}
+
+impl<
+	T: std::num::Float + Eps + 'static
+>
+TryInv<Var<T>> for Var<T> {
+	fn try_inv(&self, eps: f64) -> Option<Var<T>> {
+		if is_singular(&self.value, eps) {
+			None
+		} else {
+			Some(self.inv())
+		}
+	}
+}
+
+impl<
+	T: std::num::Float + Eps + 'static
+>
+TryDiv<Var<T>, Var<T>> for Var<T> {
+	fn try_div(&self, rhs: &Var<T>, eps: f64) -> Option<Var<T>> {
+		if is_singular(&rhs.value, eps) {
+			None
+		} else {
+			Some(self.div(rhs))
+		}
+	}
+}
+
+/// Computes the derivative of a function at a point.
+/// Uses forward-mode differentiation with `Dual2`.
+pub fn derivative<T: std::num::Float>(f: |Dual2<T>| -> Dual2<T>, x: T) -> T {
+	let y = f(Dual2::new(x, std::num::one()));
+	y.x1
+}
+
+/// Computes the gradient of a function at a point.
+/// Uses forward-mode differentiation with `DualN`, one variable per input.
+pub fn gradient<T: std::num::Float>(
+	f: |&[DualN<T>]| -> DualN<T>,
+	x: &Vector<T>
+) -> Vector<T> {
+	let n = x.x.len();
+	let vars: ~[DualN<T>] = std::vec::from_fn(n,
+		|i| DualN::variable(x.x[i].clone(), i, n));
+	let y = f(vars.slice(0, n));
+	Vector {
+		x: dual_n_parts(&y.x1, n)
+	}
+}
+
+/// Computes the Jacobian of a function at a point.
+/// Returns one row of partial derivatives per output.
+pub fn jacobian<T: std::num::Float>(
+	f: |&[DualN<T>]| -> ~[DualN<T>],
+	x: &Vector<T>
+) -> ~[Vector<T>] {
+	let n = x.x.len();
+	let vars: ~[DualN<T>] = std::vec::from_fn(n,
+		|i| DualN::variable(x.x[i].clone(), i, n));
+	let y = f(vars.slice(0, n));
+	y.iter().map(|yi| Vector {
+		x: dual_n_parts(&yi.x1, n)
+	}).collect()
+}
+
+/// Computes the Jacobian of a function with four inputs and four outputs.
+pub fn jacobian4<T: std::num::Float>(
+	f: |&[DualN<T>]| -> ~[DualN<T>],
+	x: &Vector<T>
 ) -> Matrix4<T> {
+	if x.x.len() != 4 { fail!("Jacobian4 requires four inputs"); }
+	let j = jacobian(f, x);
+	if j.len() != 4 { fail!("Jacobian4 requires four outputs"); }
 	Matrix4::new(
-		m11, m12, m13, m14,
-		m21, m22, m23, m24,
-		m31, m32, m33, m34,
-		m41, m42, m43, m44
+		j[0].x[0].clone(), j[0].x[1].clone(), j[0].x[2].clone(), j[0].x[3].clone(),
+		j[1].x[0].clone(), j[1].x[1].clone(), j[1].x[2].clone(), j[1].x[3].clone(),
+		j[2].x[0].clone(), j[2].x[1].clone(), j[2].x[2].clone(), j[2].x[3].clone(),
+		j[3].x[0].clone(), j[3].x[1].clone(), j[3].x[2].clone(), j[3].x[3].clone()
 	)
 }
 
+/// Computes the Hessian of a function at a point.
+/// Uses `HyperDual`, one evaluation per pair of inputs.
+/// Returns one row of second order partial derivatives per input.
+pub fn hessian<T: std::num::Float>(
+	f: |&[HyperDual<T>]| -> HyperDual<T>,
+	x: &Vector<T>
+) -> ~[Vector<T>] {
+	let n = x.x.len();
+	let mut res: ~[~[T]] = std::vec::from_fn(n,
+		|_| std::vec::from_elem(n, std::num::zero()));
+	for i in range(0, n) {
+		for j in range(i, n) {
+			let vars: ~[HyperDual<T>] = std::vec::from_fn(n, |k| {
+				let e1 = if k == i { std::num::one() } else { std::num::zero() };
+				let e2 = if k == j { std::num::one() } else { std::num::zero() };
+				HyperDual::new(x.x[k].clone(), e1, e2, std::num::zero())
+			});
+			let y = f(vars.slice(0, n));
+			res[i][j] = y.x12.clone();
+			res[j][i] = y.x12;
+		}
+	}
+
+	res.move_iter().map(|row| Vector {
+		x: row
+	}).collect()
+}
+
+/// A Complex number is commonly used for rotations in 2D.
+#[deriving(Eq, Zero, Clone)]
+pub struct Complex<T> {
+	/// The real dimension of the complex number.
+	x0: T,
+	/// The imaginary dimension of the complex number.
+	x1: T,
+}
+
+/// Creates a new Complex number.
+#[inline(always)]
+pub fn complex<T>(x0: T, x1: T) -> Complex<T> {
+	Complex::new(x0, x1)
+}
+
 impl<T> 
-Matrix4<T> {
-	/// Creates a new matrix with elements.
-	pub fn new(
-		m11: T, m12: T, m13: T, m14: T,
-		m21: T, m22: T, m23: T, m24: T,
-		m31: T, m32: T, m33: T, m34: T,
-		m41: T, m42: T, m43: T, m44: T
-	) -> Matrix4<T> {
-		Matrix4 {
-			m11: m11, m12: m12, m13: m13, m14: m14,
-			m21: m21, m22: m22, m23: m23, m24: m24,
-			m31: m31, m32: m32, m33: m33, m34: m34,
-			m41: m41, m42: m42, m43: m43, m44: m44
+Complex<T> {
+	/// Creates a new Complex number.
+	pub fn new(x0: T, x1: T) -> Complex<T> {
+		Complex {
+			x0: x0, 
+			x1: x1
 		}
 	}
 }
 
+impl<T>
+Complex<T> {
+	/// Sets the real dimension.
+	pub fn set_re(&mut self, x0: T) {
+		self.x0 = x0;
+	}
+
+	/// Sets the imaginary dimension.
+	pub fn set_im(&mut self, x1: T) {
+		self.x1 = x1;
+	}
+
+	/// Creates a complex number from a tuple of the real and imaginary dimension.
+	pub fn from_tuple((x0, x1): (T, T)) -> Complex<T> {
+		Complex::new(x0, x1)
+	}
+
+	/// Destructures into a tuple of the real and imaginary dimension.
+	pub fn into_tuple(self) -> (T, T) {
+		let Complex { x0, x1 } = self;
+		(x0, x1)
+	}
+}
+
 impl<
-	T: std::num::Zero + Clone
+	T: Clone
 >
-Scale<T> 
-for Matrix4<T> {
-	fn scale(factor: T) -> Matrix4<T> {
-		Matrix4 {
-			m11: factor.clone(),
-			m12: std::num::zero(),
-			m13: std::num::zero(),
-			m14: std::num::zero(),
+Complex<T> {
+	/// Returns the real dimension.
+	pub fn re(&self) -> T {
+		self.x0.clone()
+	}
 
-			m21: std::num::zero(),
-			m22: factor.clone(),
-			m23: std::num::zero(),
-			m24: std::num::zero(),
+	/// Returns the imaginary dimension.
+	pub fn im(&self) -> T {
+		self.x1.clone()
+	}
 
-			m31: std::num::zero(),
-			m32: std::num::zero(),
-			m33: factor.clone(),
-			m34: std::num::zero(),
+	/// Creates a complex number from an array of the real and imaginary dimension.
+	pub fn from_array(a: [T, ..2]) -> Complex<T> {
+		Complex::new(a[0].clone(), a[1].clone())
+	}
 
-			m41: std::num::zero(),
-			m42: std::num::zero(),
-			m43: std::num::zero(),
-			m44: factor.clone()
+	/// Returns a tuple of the real and imaginary dimension.
+	pub fn to_tuple(&self) -> (T, T) {
+		(self.x0.clone(), self.x1.clone())
+	}
+
+	/// Returns an array of the real and imaginary dimension.
+	pub fn to_array(&self) -> [T, ..2] {
+		[self.x0.clone(), self.x1.clone()]
+	}
+}
+
+impl<
+	T: Neg<T> + Clone
+>
+Complex<T> {
+	/// Returns the complex conjugate.
+	pub fn conj(&self) -> Complex<T> {
+		Complex {
+			x0: self.x0.clone(),
+			x1: -self.x1
 		}
 	}
 }
 
 impl<
-	T: Add<T, T>
+	T: std::num::Float
 >
-Add<Matrix4<T>, Matrix4<T>> 
-for Matrix4<T> {
-	fn add(&self, rhs: &Matrix4<T>) -> Matrix4<T> {
-		Matrix4 {
-			m11: self.m11 + rhs.m11,
-			m12: self.m12 + rhs.m12,
-			m13: self.m13 + rhs.m13,
-			m14: self.m14 + rhs.m14,
+Complex<T> {
+	/// Creates a complex number from polar coordinates.
+	pub fn from_polar(r: T, theta: T) -> Complex<T> {
+		Complex {
+			x0: r * theta.cos(),
+			x1: r * theta.sin()
+		}
+	}
 
-			m21: self.m21 + rhs.m21,
-			m22: self.m22 + rhs.m22,
-			m23: self.m23 + rhs.m23,
-			m24: self.m24 + rhs.m24,
+	/// Returns the argument in the principal range `(-pi, pi]`.
+	pub fn arg(&self) -> T {
+		self.x1.atan2(&self.x0)
+	}
 
-			m31: self.m31 + rhs.m31,
-			m32: self.m32 + rhs.m32,
-			m33: self.m33 + rhs.m33,
-			m34: self.m34 + rhs.m34,
+	/// Returns the norm, also called the absolute value or modulus.
+	pub fn norm(&self) -> T {
+		self.x0.hypot(&self.x1)
+	}
 
-			m41: self.m41 + rhs.m41,
-			m42: self.m42 + rhs.m42,
-			m43: self.m43 + rhs.m43,
-			m44: self.m44 + rhs.m44
+	/// Returns the polar coordinates `(norm, arg)`.
+	pub fn to_polar(&self) -> (T, T) {
+		(self.norm(), self.arg())
+	}
+
+	/// Computes the complex exponential.
+	pub fn exp(&self) -> Complex<T> {
+		Complex::from_polar(self.x0.exp(), self.x1.clone())
+	}
+
+	/// Computes the principal natural logarithm.
+	/// The imaginary part is in the range `(-pi, pi]`,
+	/// with the branch cut along the negative real axis.
+	pub fn ln(&self) -> Complex<T> {
+		Complex {
+			x0: self.norm().ln(),
+			x1: self.arg()
+		}
+	}
+
+	/// Computes the principal square root.
+	/// The real part is non-negative,
+	/// with the branch cut along the negative real axis.
+	pub fn sqrt(&self) -> Complex<T> {
+		let zero: T = std::num::zero();
+		let two: T = std::num::one::<T>() + std::num::one();
+		let r = self.norm();
+		if r == zero {
+			return std::num::zero();
+		}
+
+		let t = ((r + self.x0.abs()) / two).sqrt();
+		let u = self.x1.abs() / (two * t);
+		if self.x0 >= zero {
+			Complex {
+				x0: t,
+				x1: self.x1 / (two * t)
+			}
+		} else if self.x1 < zero {
+			Complex {
+				x0: u,
+				x1: -t
+			}
+		} else {
+			Complex {
+				x0: u,
+				x1: t
+			}
+		}
+	}
+
+	/// Raises the number to a real power using the principal argument.
+	/// Zero to the power of zero is one, like `f64::powf`,
+	/// and zero to a negative power is infinity.
+	pub fn powf(&self, n: &T) -> Complex<T> {
+		let zero: T = std::num::zero();
+		let (r, theta) = self.to_polar();
+		if r == zero {
+			return Complex {
+				x0: r.powf(n),
+				x1: zero
+			};
 		}
+
+		Complex::from_polar(r.powf(n), theta * *n)
+	}
+
+	/// Raises the number to a complex power using the principal logarithm.
+	/// Zero to the power of zero is one.
+	/// Zero to a power with negative real part is infinity,
+	/// and with zero real part and non-zero imaginary part it is NaN.
+	pub fn powc(&self, n: &Complex<T>) -> Complex<T> {
+		let zero: T = std::num::zero();
+		if self.norm() == zero {
+			let x0 = if n.x0 == zero && n.x1 == zero {
+				std::num::one()
+			} else if n.x0 > zero {
+				zero.clone()
+			} else if n.x0 < zero {
+				std::num::Float::infinity()
+			} else {
+				std::num::Float::nan()
+			};
+			return Complex {
+				x0: x0,
+				x1: zero
+			};
+		}
+
+		(n * self.ln()).exp()
+	}
+
+	/// Computes the complex sine.
+	pub fn sin(&self) -> Complex<T> {
+		Complex {
+			x0: self.x0.sin() * self.x1.cosh(),
+			x1: self.x0.cos() * self.x1.sinh()
+		}
+	}
+
+	/// Computes the complex cosine.
+	pub fn cos(&self) -> Complex<T> {
+		Complex {
+			x0: self.x0.cos() * self.x1.cosh(),
+			x1: -(self.x0.sin() * self.x1.sinh())
+		}
+	}
+
+	/// Computes the complex tangent.
+	pub fn tan(&self) -> Complex<T> {
+		self.sin() / self.cos()
+	}
+
+	/// Computes the complex hyperbolic sine.
+	pub fn sinh(&self) -> Complex<T> {
+		Complex {
+			x0: self.x0.sinh() * self.x1.cos(),
+			x1: self.x0.cosh() * self.x1.sin()
+		}
+	}
+
+	/// Computes the complex hyperbolic cosine.
+	pub fn cosh(&self) -> Complex<T> {
+		Complex {
+			x0: self.x0.cosh() * self.x1.cos(),
+			x1: self.x0.sinh() * self.x1.sin()
+		}
+	}
+
+	/// Computes the complex hyperbolic tangent.
+	pub fn tanh(&self) -> Complex<T> {
+		self.sinh() / self.cosh()
+	}
+
+	/// Creates a unit complex number that rotates by an angle in radians.
+	pub fn rotation(angle: T) -> Complex<T> {
+		Complex::from_polar(std::num::one(), angle)
+	}
+
+	/// Returns a complex number with the same argument and norm one.
+	pub fn normalize(&self) -> Complex<T> {
+		let r = self.norm();
+		Complex {
+			x0: self.x0 / r,
+			x1: self.x1 / r
+		}
+	}
+
+	/// Rotates and scales a 2D point by multiplication.
+	pub fn rotate_point(&self, (px, py): (T, T)) -> (T, T) {
+		(
+			self.x0 * px - self.x1 * py,
+			self.x1 * px + self.x0 * py
+		)
+	}
+
+	/// Interpolates linearly between two unit complex numbers and normalizes.
+	/// This is faster than `slerp` but does not have constant angular velocity.
+	pub fn nlerp(&self, other: &Complex<T>, t: T) -> Complex<T> {
+		let s = std::num::one::<T>() - t;
+		let c = Complex {
+			x0: self.x0 * s + other.x0 * t,
+			x1: self.x1 * s + other.x1 * t
+		};
+		c.normalize()
+	}
+
+	/// Interpolates the rotation between two unit complex numbers
+	/// with constant angular velocity along the shortest path.
+	pub fn slerp(&self, other: &Complex<T>, t: T) -> Complex<T> {
+		let d = (self.conj() * *other).arg();
+		self * Complex::rotation(d * t)
+	}
+}
+
+impl<
+	T: std::num::Zero
+>
+Scale<T> for Complex<T> {
+	fn scale(factor: T) -> Complex<T> {
+		Complex {
+			x0: factor,
+			x1: std::num::zero()
+		}
+	}
+}
+
+impl<
+	T: Add<T, T>
+>
+Add<Complex<T>, Complex<T>> for Complex<T> {
+	fn add(&self, rhs: &Complex<T>) -> Complex<T> {
+		Complex {
+			x0: self.x0 + rhs.x0,
+			x1: self.x1 + rhs.x1
+		}
+	}
+}
+
+impl<
+	T: Sub<T, T>
+> 
+Sub<Complex<T>, Complex<T>> for Complex<T> {
+	fn sub(&self, rhs: &Complex<T>) -> Complex<T> {
+		Complex {
+			x0: self.x0 - rhs.x0,
+			x1: self.x1 - rhs.x1
+		}
+	}
+}
+
+impl<
+	T: Mul<T, T> + Add<T, T> + Sub<T, T>
+>
+Mul<Complex<T>, Complex<T>> for Complex<T> {
+	fn mul(&self, rhs: &Complex<T>) -> Complex<T> {
+		Complex { 
+			x0: self.x0 * rhs.x0 - self.x1 * rhs.x1,
+			x1: self.x0 * rhs.x1 + self.x1 * rhs.x0 
+		}
+	}
+}
+
+impl<
+	T: Div<T, T> + Add<T, T> + Sub<T, T> + Mul<T, T>
+>
+Div<Complex<T>, Complex<T>> for Complex<T> {
+	fn div(&self, rhs: &Complex<T>) -> Complex<T> {
+		let len2 = rhs.x0 * rhs.x0 + rhs.x1 * rhs.x1;
+		Complex {
+			x0: (self.x0 * rhs.x0 + self.x1 * rhs.x1) / len2,
+			x1: (self.x1 * rhs.x0 - self.x0 * rhs.x1) / len2
+		}
+	}
+}
+
+impl<
+	T: Add<T, T> + Mul<T, T> + Div<T, T> + Neg<T>
+>
+Inv<Complex<T>> for Complex<T> {
+	fn inv(&self) -> Complex<T> {
+		let len2 = self.x0 * self.x0 + self.x1 * self.x1;
+		Complex {
+			x0: self.x0 / len2,
+			x1: -self.x1 / len2
+		}
+	}
+}
+
+impl<
+	T: Neg<T>
+>
+Neg<Complex<T>> for Complex<T> {
+	fn neg(&self) -> Complex<T> {
+		Complex {
+			x0: -self.x0, 
+			x1: -self.x1
+		}
+	}
+}
+
+impl<
+	T: Mul<T, T> + Add<T, T>
+>
+NormSq<T> for Complex<T> {
+	fn norm_sq(&self) -> T {
+		self.x0 * self.x0 + self.x1 * self.x1
+	}
+}
+
+impl<
+	T: std::num::One + std::num::Zero
+>
+std::num::One for Complex<T> {
+	fn one() -> Complex<T> {
+		Complex {
+			x0: std::num::one(),
+			x1: std::num::zero()
+		}
+	}
+}
+
+impl<
+	T: Eps
+>
+Eps for Complex<T> {
+	fn close_eps(&self, other: &Complex<T>, eps: f64) -> bool {
+		if !self.x0.close_eps(&other.x0, eps) { 
+			false 
+		} else if !self.x1.close_eps(&other.x1, eps) { 
+			false 
+		} else { 
+			true 
+		}
+	}
+}
+
+impl<
+	T: Mul<T, T> + Add<T, T>
+>
+Det<T> 
+for Complex<T> {
+	fn det(&self) -> T {
+		self.x0 * self.x0 + self.x1 * self.x1
+	}
+}
+
+impl<
+	T: Add<T, T> + Mul<T, T> + Div<T, T> + Neg<T> + Eps + std::num::Zero
+>
+TryInv<Complex<T>> for Complex<T> {
+	fn try_inv(&self, eps: f64) -> Option<Complex<T>> {
+		if is_singular(&self.norm_sq(), eps) {
+			None
+		} else {
+			Some(self.inv())
+		}
+	}
+}
+
+impl<
+	T: Div<T, T> + Add<T, T> + Sub<T, T> + Mul<T, T> + Eps + std::num::Zero
+>
+TryDiv<Complex<T>, Complex<T>> for Complex<T> {
+	fn try_div(&self, rhs: &Complex<T>, eps: f64) -> Option<Complex<T>> {
+		if is_singular(&rhs.norm_sq(), eps) {
+			None
+		} else {
+			Some(self.div(rhs))
+		}
+	}
+}
+
+/// A Transform2 is a rigid transform in 2D.
+/// It rotates by a unit complex number and then translates.
+#[deriving(Eq)]
+pub struct Transform2<T> {
+	/// The rotation as a unit complex number.
+	rotation: Complex<T>,
+	/// The translation as a complex number.
+	translation: Complex<T>,
+}
+
+/// Creates a new rigid 2D transform.
+#[inline(always)]
+pub fn transform2<T>(rotation: Complex<T>, (tx, ty): (T, T)) -> Transform2<T> {
+	Transform2::new(rotation, (tx, ty))
+}
+
+impl<T>
+Transform2<T> {
+	/// Creates a new rigid 2D transform.
+	pub fn new(rotation: Complex<T>, (tx, ty): (T, T)) -> Transform2<T> {
+		Transform2 {
+			rotation: rotation,
+			translation: Complex::new(tx, ty)
+		}
+	}
+}
+
+impl<
+	T: Clone
+>
+Transform2<T> {
+	/// Returns the rotation.
+	pub fn rotation(&self) -> Complex<T> {
+		self.rotation.clone()
+	}
+
+	/// Returns the translation.
+	pub fn translation(&self) -> (T, T) {
+		self.translation.to_tuple()
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+Transform2<T> {
+	/// Creates a rigid 2D transform from an angle in radians and a translation.
+	pub fn from_angle(angle: T, (tx, ty): (T, T)) -> Transform2<T> {
+		Transform2::new(Complex::rotation(angle), (tx, ty))
+	}
+
+	/// Transforms a 2D point.
+	pub fn transform_point(&self, p: (T, T)) -> (T, T) {
+		let (x, y) = self.rotation.rotate_point(p);
+		(x + self.translation.x0, y + self.translation.x1)
+	}
+
+	/// Transforms a 2D direction, ignoring the translation.
+	pub fn transform_vector(&self, v: (T, T)) -> (T, T) {
+		self.rotation.rotate_point(v)
+	}
+
+	/// Returns the equivalent homogeneous matrix.
+	/// The 2D plane is embedded as the xy-plane,
+	/// with the translation in the fourth column.
+	pub fn to_matrix4(&self) -> Matrix4<T> {
+		let zero: T = std::num::zero();
+		let one: T = std::num::one();
+		let c = self.rotation.x0.clone();
+		let s = self.rotation.x1.clone();
+		Matrix4::new(
+			c.clone(), -s, zero.clone(), self.translation.x0.clone(),
+			s.clone(), c, zero.clone(), self.translation.x1.clone(),
+			zero.clone(), zero.clone(), one.clone(), zero.clone(),
+			zero.clone(), zero.clone(), zero, one
+		)
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+Mul<Transform2<T>, Transform2<T>>
+for Transform2<T> {
+	fn mul(&self, rhs: &Transform2<T>) -> Transform2<T> {
+		Transform2 {
+			rotation: self.rotation * rhs.rotation,
+			translation: self.rotation * rhs.translation + self.translation
+		}
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+Inv<Transform2<T>>
+for Transform2<T> {
+	fn inv(&self) -> Transform2<T> {
+		let rotation = self.rotation.inv();
+		let translation = -(rotation * self.translation);
+		Transform2 {
+			rotation: rotation,
+			translation: translation
+		}
+	}
+}
+
+impl<
+	T: std::num::One + std::num::Zero
+>
+std::num::One
+for Transform2<T> {
+	fn one() -> Transform2<T> {
+		Transform2 {
+			rotation: std::num::one(),
+			translation: std::num::zero()
+		}
+	}
+}
+
+impl<
+	T: Eps
+>
+Eps
+for Transform2<T> {
+	fn close_eps(&self, other: &Transform2<T>, eps: f64) -> bool {
+		self.rotation.close_eps(&other.rotation, eps)
+		&& self.translation.close_eps(&other.translation, eps)
+	}
+}
+
+impl<
+	T: std::num::Float + Eps
+>
+TryInv<Transform2<T>> for Transform2<T> {
+	fn try_inv(&self, eps: f64) -> Option<Transform2<T>> {
+		if is_singular(&self.rotation.norm_sq(), eps) {
+			None
+		} else {
+			Some(self.inv())
+		}
+	}
+}
+
+/// The order of rotations for Euler angles.
+/// The rotations are about fixed axes, starting with the first axis.
+#[deriving(Eq, Clone)]
+pub enum RotationOrder {
+	/// Rotates about x, then y, then z.
+	XYZ,
+	/// Rotates about x, then z, then y.
+	XZY,
+	/// Rotates about y, then x, then z.
+	YXZ,
+	/// Rotates about y, then z, then x.
+	YZX,
+	/// Rotates about z, then x, then y.
+	ZXY,
+	/// Rotates about z, then y, then x.
+	ZYX,
+}
+
+impl
+RotationOrder {
+	/// Returns the axis indices in order of rotation.
+	fn axes(&self) -> (uint, uint, uint) {
+		match *self {
+			XYZ => (0, 1, 2),
+			XZY => (0, 2, 1),
+			YXZ => (1, 0, 2),
+			YZX => (1, 2, 0),
+			ZXY => (2, 0, 1),
+			ZYX => (2, 1, 0),
+		}
+	}
+
+	/// Returns true if the axes are a cyclic permutation of x, y, z.
+	fn is_cyclic(&self) -> bool {
+		match *self {
+			XYZ | YZX | ZXY => true,
+			XZY | YXZ | ZYX => false,
+		}
+	}
+}
+
+/// A Quaternion type is commonly used for rotations in 3D.
+#[deriving(Eq, Zero)]
+pub struct Quaternion<T> {
+	/// The x-dimension of the quaternion.
+	x: T,
+	/// The y-dimension of the quaternion.
+	y: T,
+	/// The z-dimension of the quaternion.
+	z: T,
+	/// The scalar component of the quaternion.
+	w: T,
+}
+
+/// Creates a new Quaternion.
+/// Notice that the scalar component is after the vector.
+#[inline(always)]
+pub fn quaternion<T>(x: T, y: T, z: T, w: T) -> Quaternion<T> {
+	Quaternion::new(x, y, z, w)
+}
+
+impl<T> 
+Quaternion<T> {
+	/// Creates a new Quaternion.
+	/// Notice that the scalar component is after the vector.
+	pub fn new(x: T, y: T, z: T, w: T) -> Quaternion<T> {
+		Quaternion {
+			x: x, 
+			y: y, 
+			z: z, 
+			w: w 
+		}
+	}
+}
+
+impl<T>
+Quaternion<T> {
+	/// Sets the x-dimension.
+	pub fn set_x(&mut self, x: T) {
+		self.x = x;
+	}
+
+	/// Sets the y-dimension.
+	pub fn set_y(&mut self, y: T) {
+		self.y = y;
+	}
+
+	/// Sets the z-dimension.
+	pub fn set_z(&mut self, z: T) {
+		self.z = z;
+	}
+
+	/// Sets the scalar component.
+	pub fn set_w(&mut self, w: T) {
+		self.w = w;
+	}
+
+	/// Creates a quaternion from a tuple `(x, y, z, w)`.
+	pub fn from_tuple((x, y, z, w): (T, T, T, T)) -> Quaternion<T> {
+		Quaternion::new(x, y, z, w)
+	}
+
+	/// Destructures into a tuple `(x, y, z, w)`.
+	pub fn into_tuple(self) -> (T, T, T, T) {
+		let Quaternion { x, y, z, w } = self;
+		(x, y, z, w)
+	}
+}
+
+impl<
+	T: Clone
+>
+Quaternion<T> {
+	/// Returns the x-dimension.
+	pub fn x(&self) -> T {
+		self.x.clone()
+	}
+
+	/// Returns the y-dimension.
+	pub fn y(&self) -> T {
+		self.y.clone()
+	}
+
+	/// Returns the z-dimension.
+	pub fn z(&self) -> T {
+		self.z.clone()
+	}
+
+	/// Returns the scalar component.
+	pub fn w(&self) -> T {
+		self.w.clone()
+	}
+
+	/// Creates a quaternion from an array `[x, y, z, w]`.
+	pub fn from_array(a: [T, ..4]) -> Quaternion<T> {
+		Quaternion::new(a[0].clone(), a[1].clone(), a[2].clone(), a[3].clone())
+	}
+
+	/// Returns a tuple `(x, y, z, w)`.
+	pub fn to_tuple(&self) -> (T, T, T, T) {
+		(self.x.clone(), self.y.clone(), self.z.clone(), self.w.clone())
+	}
+
+	/// Returns an array `[x, y, z, w]`.
+	pub fn to_array(&self) -> [T, ..4] {
+		[self.x.clone(), self.y.clone(), self.z.clone(), self.w.clone()]
+	}
+}
+
+impl<
+	T: Neg<T> + Clone
+>
+Quaternion<T> {
+	/// Returns the conjugate, which negates the vector part.
+	/// For a unit quaternion this is the inverse rotation.
+	pub fn conjugate(&self) -> Quaternion<T> {
+		Quaternion {
+			x: -self.x,
+			y: -self.y,
+			z: -self.z,
+			w: self.w.clone()
+		}
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+Quaternion<T> {
+	/// Returns the norm/length.
+	pub fn norm(&self) -> T {
+		self.norm_sq().sqrt()
+	}
+
+	/// Returns a quaternion with the same direction and norm one.
+	pub fn normalize(&self) -> Quaternion<T> {
+		let r = self.norm();
+		Quaternion {
+			x: self.x / r,
+			y: self.y / r,
+			z: self.z / r,
+			w: self.w / r
+		}
+	}
+
+	/// Creates a rotation around an axis by an angle in radians.
+	/// The axis does not need to be normalized.
+	/// Returns the identity rotation when the axis is zero.
+	pub fn from_axis_angle((ax, ay, az): (T, T, T), angle: T) -> Quaternion<T> {
+		let two: T = std::num::one::<T>() + std::num::one();
+		let len = (ax * ax + ay * ay + az * az).sqrt();
+		if len == std::num::zero() { return std::num::one(); }
+
+		let half = angle / two;
+		let s = half.sin() / len;
+		Quaternion {
+			x: ax * s,
+			y: ay * s,
+			z: az * s,
+			w: half.cos()
+		}
+	}
+
+	/// Returns the normalized axis and the angle in radians of a unit quaternion.
+	/// The angle is in the range `[0, 2 pi]`.
+	/// When the angle is zero the axis is the x-axis.
+	pub fn to_axis_angle(&self) -> ((T, T, T), T) {
+		let zero: T = std::num::zero();
+		let two: T = std::num::one::<T>() + std::num::one();
+		let len = (self.x * self.x + self.y * self.y + self.z * self.z).sqrt();
+		let angle = two * len.atan2(&self.w);
+		if len == zero {
+			((std::num::one(), zero.clone(), zero), angle)
+		} else {
+			((self.x / len, self.y / len, self.z / len), angle)
+		}
+	}
+
+	/// Creates a rotation around the x, y or z axis by index.
+	fn from_axis_index(axis: uint, angle: T) -> Quaternion<T> {
+		let zero: T = std::num::zero();
+		let one: T = std::num::one();
+		match axis {
+			0 => Quaternion::from_axis_angle((one, zero.clone(), zero), angle),
+			1 => Quaternion::from_axis_angle((zero.clone(), one, zero), angle),
+			_ => Quaternion::from_axis_angle((zero.clone(), zero, one), angle),
+		}
+	}
+
+	/// Creates a rotation from Euler angles in radians.
+	/// The angles are given in the same order as the axes of the rotation order.
+	pub fn from_euler(a: T, b: T, c: T, order: RotationOrder) -> Quaternion<T> {
+		let (i, j, k) = order.axes();
+		let qa = Quaternion::from_axis_index(i, a);
+		let qb = Quaternion::from_axis_index(j, b);
+		let qc = Quaternion::from_axis_index(k, c);
+		qc * qb * qa
+	}
+
+	/// Returns the Euler angles in radians of a unit quaternion.
+	/// The angles are returned in the same order as the axes of the rotation order.
+	/// The second angle is in the range `[-pi/2, pi/2]`.
+	/// In gimbal lock, when the second angle is `pi/2` or `-pi/2`,
+	/// the third angle is zero.
+	pub fn to_euler(&self, order: RotationOrder) -> (T, T, T) {
+		let (i, j, k) = order.axes();
+		let zero: T = std::num::zero();
+		let one: T = std::num::one();
+		let eps: T = std::num::Float::epsilon();
+		let s: T = if order.is_cyclic() { one.clone() } else { -one };
+		let r = self.rotation_rows();
+		let sin_b = -(s * r[k][i]);
+		let sin_b = if sin_b > one {
+				one.clone()
+			} else if sin_b < -one {
+				-one
+			} else {
+				sin_b
+			};
+		let b = sin_b.asin();
+		if r[k][j].hypot(&r[k][k]) <= eps.sqrt() {
+			// The first and third axes line up, so only the first angle is kept.
+			let a = (-(s * r[j][k])).atan2(&r[j][j]);
+			return (a, b, zero);
+		}
+
+		let a = (s * r[k][j]).atan2(&r[k][k]);
+		let c = (s * r[j][i]).atan2(&r[i][i]);
+		(a, b, c)
+	}
+
+	/// Returns the rows of the 3x3 rotation matrix of a unit quaternion.
+	fn rotation_rows(&self) -> [[T, ..3], ..3] {
+		let one: T = std::num::one();
+		let two: T = one + one;
+		let (x, y, z, w) = (&self.x, &self.y, &self.z, &self.w);
+		[
+			[
+				one - two * (*y * *y + *z * *z),
+				two * (*x * *y - *z * *w),
+				two * (*x * *z + *y * *w)
+			],
+			[
+				two * (*x * *y + *z * *w),
+				one - two * (*x * *x + *z * *z),
+				two * (*y * *z - *x * *w)
+			],
+			[
+				two * (*x * *z - *y * *w),
+				two * (*y * *z + *x * *w),
+				one - two * (*x * *x + *y * *y)
+			]
+		]
+	}
+
+	/// Rotates a 3D vector by a unit quaternion.
+	/// This is the same as `q * v * q.conjugate()`.
+	pub fn rotate_vector(&self, (vx, vy, vz): (T, T, T)) -> (T, T, T) {
+		let two: T = std::num::one::<T>() + std::num::one();
+		// t = 2 * cross(q.xyz, v)
+		let tx = two * (self.y * vz - self.z * vy);
+		let ty = two * (self.z * vx - self.x * vz);
+		let tz = two * (self.x * vy - self.y * vx);
+		// v + w * t + cross(q.xyz, t)
+		(
+			vx + self.w * tx + (self.y * tz - self.z * ty),
+			vy + self.w * ty + (self.z * tx - self.x * tz),
+			vz + self.w * tz + (self.x * ty - self.y * tx)
+		)
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+Quaternion<T> {
+	/// Computes the dot product of two quaternions.
+	pub fn dot(&self, other: &Quaternion<T>) -> T {
+		self.x * other.x
+		+ self.y * other.y
+		+ self.z * other.z
+		+ self.w * other.w
+	}
+
+	/// Multiplies every component by a scalar.
+	fn mul_scalar(&self, s: &T) -> Quaternion<T> {
+		Quaternion {
+			x: self.x * *s,
+			y: self.y * *s,
+			z: self.z * *s,
+			w: self.w * *s
+		}
+	}
+
+	/// Interpolates linearly between two unit quaternions and normalizes.
+	/// Takes the shortest path by flipping the sign of `other` when needed.
+	pub fn nlerp(&self, other: &Quaternion<T>, t: T) -> Quaternion<T> {
+		let zero: T = std::num::zero();
+		let s = std::num::one::<T>() - t;
+		let u = if self.dot(other) < zero { -t } else { t };
+		(self.mul_scalar(&s) + other.mul_scalar(&u)).normalize()
+	}
+
+	/// Interpolates along the great arc between two unit quaternions
+	/// without flipping the sign of `other`.
+	fn slerp_arc(&self, other: &Quaternion<T>, cos: T, t: T) -> Quaternion<T> {
+		let one: T = std::num::one();
+		let threshold: T = std::num::cast(0.9995).unwrap();
+		if cos > threshold {
+			// The arc is almost straight, so linear interpolation is accurate.
+			let s = one - t;
+			return (self.mul_scalar(&s) + other.mul_scalar(&t)).normalize();
+		}
+
+		let theta = cos.acos();
+		let sin = theta.sin();
+		let a = ((one - t) * theta).sin() / sin;
+		let b = (t * theta).sin() / sin;
+		self.mul_scalar(&a) + other.mul_scalar(&b)
+	}
+
+	/// Interpolates between two unit quaternions with constant angular velocity.
+	/// Takes the shortest path by flipping the sign of `other` when needed.
+	pub fn slerp(&self, other: &Quaternion<T>, t: T) -> Quaternion<T> {
+		let zero: T = std::num::zero();
+		let cos = self.dot(other);
+		if cos < zero {
+			self.slerp_arc(&-other, -cos, t)
+		} else {
+			self.slerp_arc(other, cos, t)
+		}
+	}
+
+	/// Interpolates with spherical cubic interpolation from `self` to `other`.
+	/// The control points `a` and `b` are computed with `squad_control`.
+	pub fn squad(
+		&self,
+		other: &Quaternion<T>,
+		a: &Quaternion<T>,
+		b: &Quaternion<T>,
+		t: T
+	) -> Quaternion<T> {
+		let one: T = std::num::one();
+		let two: T = one + one;
+		let p = self.slerp_arc(other, self.dot(other), t.clone());
+		let q = a.slerp_arc(b, a.dot(b), t.clone());
+		let h = two * t * (one - t);
+		p.slerp_arc(&q, p.dot(&q), h)
+	}
+
+	/// Computes the control point for `squad` at `self`
+	/// from the previous and the next unit quaternion.
+	pub fn squad_control(
+		&self,
+		prev: &Quaternion<T>,
+		next: &Quaternion<T>
+	) -> Quaternion<T> {
+		let four: T = std::num::cast(4.0).unwrap();
+		let inv = self.conjugate();
+		let a = (inv * *next).ln();
+		let b = (inv * *prev).ln();
+		let c = (a + b).mul_scalar(&(-std::num::one::<T>() / four));
+		self * c.exp()
+	}
+
+	/// Computes the quaternion exponential.
+	pub fn exp(&self) -> Quaternion<T> {
+		let zero: T = std::num::zero();
+		let len = (self.x * self.x + self.y * self.y + self.z * self.z).sqrt();
+		let e = self.w.exp();
+		if len == zero {
+			return Quaternion {
+				x: zero.clone(),
+				y: zero.clone(),
+				z: zero,
+				w: e
+			};
+		}
+
+		let s = e * len.sin() / len;
+		Quaternion {
+			x: self.x * s,
+			y: self.y * s,
+			z: self.z * s,
+			w: e * len.cos()
+		}
+	}
+
+	/// Computes the quaternion natural logarithm.
+	/// For negative real quaternions the vector part is a half turn about the x-axis.
+	pub fn ln(&self) -> Quaternion<T> {
+		let zero: T = std::num::zero();
+		let len = (self.x * self.x + self.y * self.y + self.z * self.z).sqrt();
+		let norm = self.norm();
+		if len == zero {
+			let pi: T = std::num::Real::pi();
+			return Quaternion {
+				x: if self.w < zero { pi } else { zero.clone() },
+				y: zero.clone(),
+				z: zero,
+				w: norm.ln()
+			};
+		}
+
+		let s = len.atan2(&self.w) / len;
+		Quaternion {
+			x: self.x * s,
+			y: self.y * s,
+			z: self.z * s,
+			w: norm.ln()
+		}
+	}
+
+	/// Raises the quaternion to a real power.
+	pub fn powf(&self, n: &T) -> Quaternion<T> {
+		self.ln().mul_scalar(n).exp()
+	}
+
+	/// Integrates a constant angular velocity in world space over a time step.
+	/// The result is normalized to avoid drift.
+	pub fn integrate(&self, (wx, wy, wz): (T, T, T), dt: T) -> Quaternion<T> {
+		let zero: T = std::num::zero();
+		let two: T = std::num::one::<T>() + std::num::one();
+		let h = dt / two;
+		let q = Quaternion {
+			x: wx * h,
+			y: wy * h,
+			z: wz * h,
+			w: zero
+		};
+		(q.exp() * *self).normalize()
+	}
+
+	/// Computes the angular velocity in world space
+	/// that rotates from `self` to `next` over a time step.
+	pub fn angular_velocity(&self, next: &Quaternion<T>, dt: T) -> (T, T, T) {
+		let zero: T = std::num::zero();
+		let two: T = std::num::one::<T>() + std::num::one();
+		let d = if self.dot(next) < zero {
+				-next * self.conjugate()
+			} else {
+				next * self.conjugate()
+			};
+		let l = d.ln();
+		let s = two / dt;
+		(l.x * s, l.y * s, l.z * s)
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+Quaternion<T> {
+	/// Creates a unit quaternion from the upper left 3x3 rotation part of a matrix.
+	/// Uses Shepperd's method, which picks the largest diagonal term
+	/// to avoid dividing by a small number.
+	pub fn from_rotation_matrix(m: &Matrix4<T>) -> Quaternion<T> {
+		let one: T = std::num::one();
+		let two: T = one + one;
+		let four: T = two + two;
+		let trace = m.m11 + m.m22 + m.m33;
+		if trace >= m.m11 && trace >= m.m22 && trace >= m.m33 {
+			let w = (one + trace).sqrt() / two;
+			let d = four * w;
+			Quaternion {
+				x: (m.m32 - m.m23) / d,
+				y: (m.m13 - m.m31) / d,
+				z: (m.m21 - m.m12) / d,
+				w: w
+			}
+		} else if m.m11 >= m.m22 && m.m11 >= m.m33 {
+			let x = (one + m.m11 - m.m22 - m.m33).sqrt() / two;
+			let d = four * x;
+			Quaternion {
+				y: (m.m12 + m.m21) / d,
+				z: (m.m13 + m.m31) / d,
+				w: (m.m32 - m.m23) / d,
+				x: x
+			}
+		} else if m.m22 >= m.m33 {
+			let y = (one - m.m11 + m.m22 - m.m33).sqrt() / two;
+			let d = four * y;
+			Quaternion {
+				x: (m.m12 + m.m21) / d,
+				z: (m.m23 + m.m32) / d,
+				w: (m.m13 - m.m31) / d,
+				y: y
+			}
+		} else {
+			let z = (one - m.m11 - m.m22 + m.m33).sqrt() / two;
+			let d = four * z;
+			Quaternion {
+				x: (m.m13 + m.m31) / d,
+				y: (m.m23 + m.m32) / d,
+				w: (m.m21 - m.m12) / d,
+				z: z
+			}
+		}
+	}
+}
+
+impl<
+	T: std::num::Zero
+>
+Scale<T> 
+for Quaternion<T> {
+	fn scale(factor: T) -> Quaternion<T> {
+		Quaternion { 
+			x: std::num::zero(),
+			y: std::num::zero(),
+			z: std::num::zero(),
+			w: factor 
+		}
+	}
+}
+
+impl<
+	T: Add<T, T>
+>
+Add<Quaternion<T>, Quaternion<T>> 
+for Quaternion<T> {
+	fn add(&self, rhs: &Quaternion<T>) -> Quaternion<T> {
+		Quaternion { 
+			x: self.x + rhs.x,
+			y: self.y + rhs.y,
+			z: self.z + rhs.z,
+			w: self.w + rhs.w,
+		}
+	}
+}
+
+impl<
+	T: Sub<T, T>
+>
+Sub<Quaternion<T>, Quaternion<T>> 
+for Quaternion<T> {
+	fn sub(&self, rhs: &Quaternion<T>) -> Quaternion<T> {
+		Quaternion { 
+			x: self.x - rhs.x,
+			y: self.y - rhs.y,
+			z: self.z - rhs.z,
+			w: self.w - rhs.w
+		}
+	}
+}
+
+impl<
+	T: Mul<T, T> + Sub<T, T> + Add<T, T>
+>
+Mul<Quaternion<T>, Quaternion<T>> 
+for Quaternion<T> {
+	fn mul(&self, rhs: &Quaternion<T>) -> Quaternion<T> {
+		Quaternion {
+			x: 	self.w*rhs.x
+				-self.z*rhs.y
+				+self.y*rhs.z
+				+self.x*rhs.w,
+			y: 	self.z*rhs.x
+				+self.w*rhs.y
+				-self.x*rhs.z
+				+self.y*rhs.w,
+			z: 	self.x*rhs.y
+				-self.y*rhs.x
+				+self.w*rhs.z
+				+self.z*rhs.w,
+			w: 	self.w*rhs.w
+				-self.x*rhs.x
+				-self.y*rhs.y
+				-self.z*rhs.z,
+		}
+	}
+}
+
+impl<
+	T: Mul<T, T> + Sub<T, T> + Add<T, T> + Div<T, T>
+>
+Div<Quaternion<T>, Quaternion<T>> 
+for Quaternion<T> {
+	fn div(&self, rhs: &Quaternion<T>) -> Quaternion<T> {
+		let len2 = rhs.x * rhs.x 
+			+ rhs.y * rhs.y 
+			+ rhs.z * rhs.z
+			+ rhs.w * rhs.w;
+		Quaternion {
+			x: 	(self.z*rhs.y
+				-self.w*rhs.x
+				-self.y*rhs.z
+				+self.x*rhs.w) / len2,
+			y: 	(self.x*rhs.z	
+				-self.z*rhs.x
+				-self.w*rhs.y
+				+self.y*rhs.w) / len2,
+			z: 	(self.y*rhs.x
+				-self.x*rhs.y
+				-self.w*rhs.z
+				+self.z*rhs.w) / len2,
+			w: 	(self.w*rhs.w
+				+self.x*rhs.x
+				+self.y*rhs.y
+				+self.z*rhs.z) / len2,
+		}
+	}
+}
+
+impl<
+	T: Mul<T, T> + Add<T, T> + Div<T, T> + Neg<T>
+>
+Inv<Quaternion<T>> 
+for Quaternion<T> {
+	fn inv(&self) -> Quaternion<T> {
+		let len2 = self.x * self.x 
+			+ self.y * self.y 
+			+ self.z * self.z
+			+ self.w * self.w;
+		Quaternion {
+			x: 	-self.x / len2,
+			y: 	-self.y / len2,
+			z: 	-self.z / len2,
+			w: 	self.w / len2,
+		}
+	}
+}
+
+impl<
+	T: Neg<T>
+>
+Neg<Quaternion<T>> 
+for Quaternion<T> {
+	fn neg(&self) -> Quaternion<T> {
+		Quaternion { 
+			x: -self.x,
+			y: -self.y,
+			z: -self.z,
+			w: -self.w 
+		}
+	}
+}
+
+impl<
+	T: Add<T, T> + Mul<T, T>
+>
+NormSq<T> 
+for Quaternion<T> {
+	fn norm_sq(&self) -> T {
+		self.x * self.x
+		+ self.y * self.y
+		+ self.z * self.z
+		+ self.w * self.w
+	}
+}
+
+impl<
+	T: std::num::One + std::num::Zero
+>
+std::num::One 
+for Quaternion<T> {
+	fn one() -> Quaternion<T> {
+		Quaternion { 
+			x: std::num::zero(),
+			y: std::num::zero(),
+			z: std::num::zero(),
+			w: std::num::one() 
+		}
+	}
+}
+
+impl<
+	T: Eps
+>
+Eps 
+for Quaternion<T> {
+	fn close_eps(&self, other: &Quaternion<T>, eps: f64) -> bool {
+		if !self.x.close_eps(&other.x, eps) { 
+			false 
+		} else if !self.y.close_eps(&other.y, eps) { 
+			false 
+		} else if !self.z.close_eps(&other.z, eps) { 
+			false 
+		} else if !self.w.close_eps(&other.w, eps) { 
+			false 
+		} else { 
+			true 
+		}
+	}
+}
+
+impl<
+	T: Mul<T, T> + Add<T, T> + Sub<T, T> + Div<T, T> + Neg<T> + Eps + std::num::Zero
+>
+TryInv<Quaternion<T>> for Quaternion<T> {
+	fn try_inv(&self, eps: f64) -> Option<Quaternion<T>> {
+		if is_singular(&self.norm_sq(), eps) {
+			None
+		} else {
+			Some(self.inv())
+		}
+	}
+}
+
+impl<
+	T: Mul<T, T> + Add<T, T> + Sub<T, T> + Div<T, T> + Neg<T> + Eps + std::num::Zero
+>
+TryDiv<Quaternion<T>, Quaternion<T>> for Quaternion<T> {
+	fn try_div(&self, rhs: &Quaternion<T>, eps: f64) -> Option<Quaternion<T>> {
+		if is_singular(&rhs.norm_sq(), eps) {
+			None
+		} else {
+			Some(self.div(rhs))
+		}
+	}
+}
+
+/// The handedness of a coordinate system.
+#[deriving(Eq, Clone)]
+pub enum Handedness {
+	/// The camera looks along the positive z-axis.
+	LeftHanded,
+	/// The camera looks along the negative z-axis.
+	RightHanded,
+}
+
+/// The range of depth values in clip space after the perspective divide.
+#[deriving(Eq, Clone)]
+pub enum DepthRange {
+	/// Depth goes from -1 at the near plane to 1 at the far plane, like OpenGL.
+	NegativeOneToOne,
+	/// Depth goes from 0 at the near plane to 1 at the far plane, like Direct3D.
+	ZeroToOne,
+}
+
+/// A Matrix4 is commonly used for linear transformations in 3D space.
+#[deriving(Eq, Zero, Clone)]
+pub struct Matrix4<T> {
+	/// Element at first row and first column.
+	m11: T, 
+	/// Element at first row and second column.
+	m12: T, 
+	/// Element at first row and third column.
+	m13: T, 
+	/// Element at first row and fourth column.
+	m14: T,
+	
+	/// Element at second row and first column.
+	m21: T, 
+	/// Element at second row and second column.
+	m22: T, 
+	/// Element at second row and third column.
+	m23: T, 
+	/// Element at second row and fourth column.
+	m24: T,
+	
+	/// Element at third row and first column.
+	m31: T, 
+	/// Element at third row and second column.
+	m32: T, 
+	/// Element at third row and third column.
+	m33: T, 
+	/// Element at third row and fourth column.
+	m34: T,
+	
+	/// Element at fourth row and first column.
+	m41: T, 
+	/// Element at fourth row and second column.
+	m42: T, 
+	/// Element at fourt row and third column.
+	m43: T, 
+	/// Element at fourth row and fourth column.
+	m44: T
+}
+
+/// Creates a new matrix with elements.
+#[inline(always)]
+pub fn matrix4<T>(
+	m11: T, m12: T, m13: T, m14: T,
+	m21: T, m22: T, m23: T, m24: T,
+	m31: T, m32: T, m33: T, m34: T,
+	m41: T, m42: T, m43: T, m44: T
+) -> Matrix4<T> {
+	Matrix4::new(
+		m11, m12, m13, m14,
+		m21, m22, m23, m24,
+		m31, m32, m33, m34,
+		m41, m42, m43, m44
+	)
+}
+
+impl<T> 
+Matrix4<T> {
+	/// Creates a new matrix with elements.
+	pub fn new(
+		m11: T, m12: T, m13: T, m14: T,
+		m21: T, m22: T, m23: T, m24: T,
+		m31: T, m32: T, m33: T, m34: T,
+		m41: T, m42: T, m43: T, m44: T
+	) -> Matrix4<T> {
+		Matrix4 {
+			m11: m11, m12: m12, m13: m13, m14: m14,
+			m21: m21, m22: m22, m23: m23, m24: m24,
+			m31: m31, m32: m32, m33: m33, m34: m34,
+			m41: m41, m42: m42, m43: m43, m44: m44
+		}
+	}
+}
+
+impl<T>
+Matrix4<T> {
+	/// Returns a reference to the element at a zero-based row and column.
+	pub fn elem<'a>(&'a self, row: uint, col: uint) -> &'a T {
+		match (row, col) {
+			(0, 0) => &self.m11,
+			(0, 1) => &self.m12,
+			(0, 2) => &self.m13,
+			(0, 3) => &self.m14,
+			(1, 0) => &self.m21,
+			(1, 1) => &self.m22,
+			(1, 2) => &self.m23,
+			(1, 3) => &self.m24,
+			(2, 0) => &self.m31,
+			(2, 1) => &self.m32,
+			(2, 2) => &self.m33,
+			(2, 3) => &self.m34,
+			(3, 0) => &self.m41,
+			(3, 1) => &self.m42,
+			(3, 2) => &self.m43,
+			(3, 3) => &self.m44,
+			_ => fail!("Matrix4 index out of bounds")
+		}
+	}
+
+	/// Returns a mutable reference to the element at a zero-based row and column.
+	pub fn elem_mut<'a>(&'a mut self, row: uint, col: uint) -> &'a mut T {
+		match (row, col) {
+			(0, 0) => &mut self.m11,
+			(0, 1) => &mut self.m12,
+			(0, 2) => &mut self.m13,
+			(0, 3) => &mut self.m14,
+			(1, 0) => &mut self.m21,
+			(1, 1) => &mut self.m22,
+			(1, 2) => &mut self.m23,
+			(1, 3) => &mut self.m24,
+			(2, 0) => &mut self.m31,
+			(2, 1) => &mut self.m32,
+			(2, 2) => &mut self.m33,
+			(2, 3) => &mut self.m34,
+			(3, 0) => &mut self.m41,
+			(3, 1) => &mut self.m42,
+			(3, 2) => &mut self.m43,
+			(3, 3) => &mut self.m44,
+			_ => fail!("Matrix4 index out of bounds")
+		}
+	}
+
+	/// Sets the element at a zero-based row and column.
+	pub fn set(&mut self, row: uint, col: uint, val: T) {
+		*self.elem_mut(row, col) = val;
+	}
+
+	/// Destructures into an array of rows.
+	pub fn into_array(self) -> [[T, ..4], ..4] {
+		let Matrix4 {
+			m11, m12, m13, m14,
+			m21, m22, m23, m24,
+			m31, m32, m33, m34,
+			m41, m42, m43, m44
+		} = self;
+		[
+			[m11, m12, m13, m14],
+			[m21, m22, m23, m24],
+			[m31, m32, m33, m34],
+			[m41, m42, m43, m44]
+		]
+	}
+}
+
+impl<
+	T: Clone
+>
+Matrix4<T> {
+	/// Returns the element at a zero-based row and column.
+	pub fn get(&self, row: uint, col: uint) -> T {
+		self.elem(row, col).clone()
+	}
+
+	/// Returns the elements of a zero-based row.
+	pub fn row(&self, i: uint) -> [T, ..4] {
+		[self.get(i, 0), self.get(i, 1), self.get(i, 2), self.get(i, 3)]
+	}
+
+	/// Returns the elements of a zero-based column.
+	pub fn col(&self, j: uint) -> [T, ..4] {
+		[self.get(0, j), self.get(1, j), self.get(2, j), self.get(3, j)]
+	}
+
+	/// Sets the elements of a zero-based row.
+	pub fn set_row(&mut self, i: uint, row: [T, ..4]) {
+		for j in range(0u, 4) {
+			self.set(i, j, row[j].clone());
+		}
+	}
+
+	/// Sets the elements of a zero-based column.
+	pub fn set_col(&mut self, j: uint, col: [T, ..4]) {
+		for i in range(0u, 4) {
+			self.set(i, j, col[i].clone());
+		}
+	}
+
+	/// Creates a matrix from an array of rows.
+	pub fn from_array(a: [[T, ..4], ..4]) -> Matrix4<T> {
+		Matrix4::new(
+			a[0][0].clone(), a[0][1].clone(), a[0][2].clone(), a[0][3].clone(),
+			a[1][0].clone(), a[1][1].clone(), a[1][2].clone(), a[1][3].clone(),
+			a[2][0].clone(), a[2][1].clone(), a[2][2].clone(), a[2][3].clone(),
+			a[3][0].clone(), a[3][1].clone(), a[3][2].clone(), a[3][3].clone()
+		)
+	}
+
+	/// Returns an array of rows.
+	pub fn to_array(&self) -> [[T, ..4], ..4] {
+		[self.row(0), self.row(1), self.row(2), self.row(3)]
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+Matrix4<T> {
+	/// Creates a rotation matrix from a unit quaternion.
+	pub fn from_quaternion(q: &Quaternion<T>) -> Matrix4<T> {
+		let zero: T = std::num::zero();
+		let r = q.rotation_rows();
+		Matrix4::new(
+			r[0][0].clone(), r[0][1].clone(), r[0][2].clone(), zero.clone(),
+			r[1][0].clone(), r[1][1].clone(), r[1][2].clone(), zero.clone(),
+			r[2][0].clone(), r[2][1].clone(), r[2][2].clone(), zero.clone(),
+			zero.clone(), zero.clone(), zero, std::num::one()
+		)
+	}
+
+	/// Creates an affine matrix that scales, then rotates and then translates.
+	pub fn compose(
+		(tx, ty, tz): (T, T, T),
+		rotation: &Quaternion<T>,
+		(sx, sy, sz): (T, T, T)
+	) -> Matrix4<T> {
+		let r = Matrix4::from_quaternion(rotation);
+		let zero: T = std::num::zero();
+		Matrix4::new(
+			r.m11 * sx, r.m12 * sy, r.m13 * sz, tx,
+			r.m21 * sx, r.m22 * sy, r.m23 * sz, ty,
+			r.m31 * sx, r.m32 * sy, r.m33 * sz, tz,
+			zero.clone(), zero.clone(), zero, std::num::one()
+		)
+	}
+
+	/// Decomposes an affine matrix into translation, rotation and scale.
+	/// This is the inverse of `compose` when the matrix has no shear.
+	/// A reflection is returned as a negative scale along the x-axis.
+	/// Returns `None` if a column of the linear part is zero.
+	pub fn decompose(&self) -> Option<((T, T, T), Quaternion<T>, (T, T, T))> {
+		let zero: T = std::num::zero();
+		let translation = (self.m14.clone(), self.m24.clone(), self.m34.clone());
+		let sx = (self.m11 * self.m11 + self.m21 * self.m21 + self.m31 * self.m31).sqrt();
+		let sy = (self.m12 * self.m12 + self.m22 * self.m22 + self.m32 * self.m32).sqrt();
+		let sz = (self.m13 * self.m13 + self.m23 * self.m23 + self.m33 * self.m33).sqrt();
+		if sx == zero || sy == zero || sz == zero { return None; }
+
+		let det3 = self.m11 * (self.m22 * self.m33 - self.m23 * self.m32)
+			- self.m12 * (self.m21 * self.m33 - self.m23 * self.m31)
+			+ self.m13 * (self.m21 * self.m32 - self.m22 * self.m31);
+		let sx = if det3 < zero { -sx } else { sx };
+		let r = Matrix4::new(
+			self.m11 / sx, self.m12 / sy, self.m13 / sz, zero.clone(),
+			self.m21 / sx, self.m22 / sy, self.m23 / sz, zero.clone(),
+			self.m31 / sx, self.m32 / sy, self.m33 / sz, zero.clone(),
+			zero.clone(), zero.clone(), zero, std::num::one()
+		);
+		Some((translation, Quaternion::from_rotation_matrix(&r), (sx, sy, sz)))
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+Matrix4<T> {
+	/// Creates a translation matrix.
+	pub fn translation((tx, ty, tz): (T, T, T)) -> Matrix4<T> {
+		let zero: T = std::num::zero();
+		let one: T = std::num::one();
+		Matrix4::new(
+			one.clone(), zero.clone(), zero.clone(), tx,
+			zero.clone(), one.clone(), zero.clone(), ty,
+			zero.clone(), zero.clone(), one.clone(), tz,
+			zero.clone(), zero.clone(), zero, one
+		)
+	}
+
+	/// Creates a non-uniform scaling matrix.
+	pub fn scaling((sx, sy, sz): (T, T, T)) -> Matrix4<T> {
+		let zero: T = std::num::zero();
+		Matrix4::new(
+			sx, zero.clone(), zero.clone(), zero.clone(),
+			zero.clone(), sy, zero.clone(), zero.clone(),
+			zero.clone(), zero.clone(), sz, zero.clone(),
+			zero.clone(), zero.clone(), zero, std::num::one()
+		)
+	}
+
+	/// Creates a rotation around the x-axis by an angle in radians.
+	pub fn rotation_x(angle: T) -> Matrix4<T> {
+		let zero: T = std::num::zero();
+		let one: T = std::num::one();
+		let (s, c) = angle.sin_cos();
+		Matrix4::new(
+			one.clone(), zero.clone(), zero.clone(), zero.clone(),
+			zero.clone(), c.clone(), -s, zero.clone(),
+			zero.clone(), s, c, zero.clone(),
+			zero.clone(), zero.clone(), zero, one
+		)
+	}
+
+	/// Creates a rotation around the y-axis by an angle in radians.
+	pub fn rotation_y(angle: T) -> Matrix4<T> {
+		let zero: T = std::num::zero();
+		let one: T = std::num::one();
+		let (s, c) = angle.sin_cos();
+		Matrix4::new(
+			c.clone(), zero.clone(), s.clone(), zero.clone(),
+			zero.clone(), one.clone(), zero.clone(), zero.clone(),
+			-s, zero.clone(), c, zero.clone(),
+			zero.clone(), zero.clone(), zero, one
+		)
+	}
+
+	/// Creates a rotation around the z-axis by an angle in radians.
+	pub fn rotation_z(angle: T) -> Matrix4<T> {
+		let zero: T = std::num::zero();
+		let one: T = std::num::one();
+		let (s, c) = angle.sin_cos();
+		Matrix4::new(
+			c.clone(), -s, zero.clone(), zero.clone(),
+			s, c, zero.clone(), zero.clone(),
+			zero.clone(), zero.clone(), one.clone(), zero.clone(),
+			zero.clone(), zero.clone(), zero, one
+		)
+	}
+
+	/// Creates a view matrix for a camera at `eye` looking at `target`.
+	/// The camera looks along negative z for right handed coordinates
+	/// and along positive z for left handed coordinates.
+	pub fn look_at(
+		(ex, ey, ez): (T, T, T),
+		(tx, ty, tz): (T, T, T),
+		(ux, uy, uz): (T, T, T),
+		handedness: Handedness
+	) -> Matrix4<T> {
+		let zero: T = std::num::zero();
+		// Forward direction.
+		let (fx, fy, fz) = (tx - ex, ty - ey, tz - ez);
+		let len = (fx * fx + fy * fy + fz * fz).sqrt();
+		let (fx, fy, fz) = (fx / len, fy / len, fz / len);
+		// Side direction.
+		let (sx, sy, sz) = match handedness {
+				RightHanded => (fy * uz - fz * uy, fz * ux - fx * uz, fx * uy - fy * ux),
+				LeftHanded => (uy * fz - uz * fy, uz * fx - ux * fz, ux * fy - uy * fx),
+			};
+		let len = (sx * sx + sy * sy + sz * sz).sqrt();
+		let (sx, sy, sz) = (sx / len, sy / len, sz / len);
+		// Up direction.
+		let (ux, uy, uz) = match handedness {
+				RightHanded => (sy * fz - sz * fy, sz * fx - sx * fz, sx * fy - sy * fx),
+				LeftHanded => (fy * sz - fz * sy, fz * sx - fx * sz, fx * sy - fy * sx),
+			};
+		// The camera looks along negative z for right handed coordinates.
+		let (zx, zy, zz) = match handedness {
+				RightHanded => (-fx, -fy, -fz),
+				LeftHanded => (fx, fy, fz),
+			};
+		let ts = -(sx * ex + sy * ey + sz * ez);
+		let tu = -(ux * ex + uy * ey + uz * ez);
+		let tz = -(zx * ex + zy * ey + zz * ez);
+		Matrix4::new(
+			sx, sy, sz, ts,
+			ux, uy, uz, tu,
+			zx, zy, zz, tz,
+			zero.clone(), zero.clone(), zero, std::num::one()
+		)
+	}
+
+	/// Creates a perspective projection from a frustum given by the
+	/// left, right, bottom and top planes at the near distance,
+	/// and the near and far distances.
+	pub fn frustum(
+		left: T, right: T,
+		bottom: T, top: T,
+		near: T, far: T,
+		handedness: Handedness,
+		depth: DepthRange
+	) -> Matrix4<T> {
+		let zero: T = std::num::zero();
+		let one: T = std::num::one();
+		let two: T = one + one;
+		let sign: T = match handedness {
+				RightHanded => -one,
+				LeftHanded => one.clone(),
+			};
+		let (m33, m34) = match depth {
+				NegativeOneToOne => (
+					sign * (far + near) / (far - near),
+					-(two * far * near) / (far - near)
+				),
+				ZeroToOne => (
+					sign * far / (far - near),
+					-(far * near) / (far - near)
+				),
+			};
+		Matrix4::new(
+			two * near / (right - left), zero.clone(),
+				-(sign * (right + left) / (right - left)), zero.clone(),
+			zero.clone(), two * near / (top - bottom),
+				-(sign * (top + bottom) / (top - bottom)), zero.clone(),
+			zero.clone(), zero.clone(), m33, m34,
+			zero.clone(), zero.clone(), sign, zero
+		)
+	}
+
+	/// Creates a perspective projection from a vertical field of view in radians,
+	/// the aspect ratio of width over height, and the near and far distances.
+	pub fn perspective(
+		fovy: T, aspect: T,
+		near: T, far: T,
+		handedness: Handedness,
+		depth: DepthRange
+	) -> Matrix4<T> {
+		let two: T = std::num::one::<T>() + std::num::one();
+		let top = near * (fovy / two).tan();
+		let right = top * aspect;
+		Matrix4::frustum(-right, right.clone(), -top, top.clone(), near, far, handedness, depth)
+	}
+
+	/// Creates an orthographic projection from the
+	/// left, right, bottom, top, near and far planes.
+	pub fn orthographic(
+		left: T, right: T,
+		bottom: T, top: T,
+		near: T, far: T,
+		handedness: Handedness,
+		depth: DepthRange
+	) -> Matrix4<T> {
+		let zero: T = std::num::zero();
+		let one: T = std::num::one();
+		let two: T = one + one;
+		let sign: T = match handedness {
+				RightHanded => -one,
+				LeftHanded => one.clone(),
+			};
+		let (m33, m34) = match depth {
+				NegativeOneToOne => (
+					sign * two / (far - near),
+					-(far + near) / (far - near)
+				),
+				ZeroToOne => (
+					sign / (far - near),
+					-near / (far - near)
+				),
+			};
+		Matrix4::new(
+			two / (right - left), zero.clone(), zero.clone(),
+				-(right + left) / (right - left),
+			zero.clone(), two / (top - bottom), zero.clone(),
+				-(top + bottom) / (top - bottom),
+			zero.clone(), zero.clone(), m33, m34,
+			zero.clone(), zero.clone(), zero, one
+		)
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+Matrix4<T> {
+	/// Multiplies a homogeneous vector by the matrix.
+	pub fn transform(&self, v: &Vector4<T>) -> Vector4<T> {
+		Vector4 {
+			x: self.m11 * v.x + self.m12 * v.y + self.m13 * v.z + self.m14 * v.w,
+			y: self.m21 * v.x + self.m22 * v.y + self.m23 * v.z + self.m24 * v.w,
+			z: self.m31 * v.x + self.m32 * v.y + self.m33 * v.z + self.m34 * v.w,
+			w: self.m41 * v.x + self.m42 * v.y + self.m43 * v.z + self.m44 * v.w
+		}
+	}
+
+	/// Transforms a point with an implicit w-component of one.
+	/// The result is divided by the resulting w-component,
+	/// which is needed for perspective projections.
+	pub fn transform_point(&self, p: &Vector3<T>) -> Vector3<T> {
+		let w = self.m41 * p.x + self.m42 * p.y + self.m43 * p.z + self.m44;
+		Vector3 {
+			x: (self.m11 * p.x + self.m12 * p.y + self.m13 * p.z + self.m14) / w,
+			y: (self.m21 * p.x + self.m22 * p.y + self.m23 * p.z + self.m24) / w,
+			z: (self.m31 * p.x + self.m32 * p.y + self.m33 * p.z + self.m34) / w
+		}
+	}
+
+	/// Transforms a direction with an implicit w-component of zero.
+	/// The translation does not affect directions.
+	pub fn transform_vector(&self, v: &Vector3<T>) -> Vector3<T> {
+		Vector3 {
+			x: self.m11 * v.x + self.m12 * v.y + self.m13 * v.z,
+			y: self.m21 * v.x + self.m22 * v.y + self.m23 * v.z,
+			z: self.m31 * v.x + self.m32 * v.y + self.m33 * v.z
+		}
+	}
+
+	/// Transforms a surface normal by the inverse transpose of the matrix.
+	/// This keeps normals perpendicular to surfaces under non-uniform scaling.
+	/// The result is not normalized.
+	pub fn transform_normal(&self, n: &Vector3<T>) -> Vector3<T> {
+		self.inv().transform_vector_transposed(n)
+	}
+
+	/// Transforms a direction by the transpose of the upper left 3x3 part.
+	fn transform_vector_transposed(&self, v: &Vector3<T>) -> Vector3<T> {
+		Vector3 {
+			x: self.m11 * v.x + self.m21 * v.y + self.m31 * v.z,
+			y: self.m12 * v.x + self.m22 * v.y + self.m32 * v.z,
+			z: self.m13 * v.x + self.m23 * v.y + self.m33 * v.z
+		}
+	}
+
+	/// Multiplies a list of homogeneous vectors by the matrix.
+	pub fn transform_all(&self, vs: &[Vector4<T>]) -> ~[Vector4<T>] {
+		vs.iter().map(|v| self.transform(v)).collect()
+	}
+
+	/// Transforms a list of points with perspective divide.
+	pub fn transform_points(&self, ps: &[Vector3<T>]) -> ~[Vector3<T>] {
+		ps.iter().map(|p| self.transform_point(p)).collect()
+	}
+
+	/// Transforms a list of directions.
+	pub fn transform_vectors(&self, vs: &[Vector3<T>]) -> ~[Vector3<T>] {
+		vs.iter().map(|v| self.transform_vector(v)).collect()
+	}
+
+	/// Transforms a list of surface normals.
+	/// The inverse is computed once for the whole list.
+	pub fn transform_normals(&self, ns: &[Vector3<T>]) -> ~[Vector3<T>] {
+		let inv = self.inv();
+		ns.iter().map(|n| inv.transform_vector_transposed(n)).collect()
+	}
+}
+
+impl<
+	T: std::num::Zero + Clone
+>
+Scale<T> 
+for Matrix4<T> {
+	fn scale(factor: T) -> Matrix4<T> {
+		Matrix4 {
+			m11: factor.clone(),
+			m12: std::num::zero(),
+			m13: std::num::zero(),
+			m14: std::num::zero(),
+
+			m21: std::num::zero(),
+			m22: factor.clone(),
+			m23: std::num::zero(),
+			m24: std::num::zero(),
+
+			m31: std::num::zero(),
+			m32: std::num::zero(),
+			m33: factor.clone(),
+			m34: std::num::zero(),
+
+			m41: std::num::zero(),
+			m42: std::num::zero(),
+			m43: std::num::zero(),
+			m44: factor.clone()
+		}
+	}
+}
+
+impl<
+	T: Add<T, T>
+>
+Add<Matrix4<T>, Matrix4<T>> 
+for Matrix4<T> {
+	fn add(&self, rhs: &Matrix4<T>) -> Matrix4<T> {
+		Matrix4 {
+			m11: self.m11 + rhs.m11,
+			m12: self.m12 + rhs.m12,
+			m13: self.m13 + rhs.m13,
+			m14: self.m14 + rhs.m14,
+
+			m21: self.m21 + rhs.m21,
+			m22: self.m22 + rhs.m22,
+			m23: self.m23 + rhs.m23,
+			m24: self.m24 + rhs.m24,
+
+			m31: self.m31 + rhs.m31,
+			m32: self.m32 + rhs.m32,
+			m33: self.m33 + rhs.m33,
+			m34: self.m34 + rhs.m34,
+
+			m41: self.m41 + rhs.m41,
+			m42: self.m42 + rhs.m42,
+			m43: self.m43 + rhs.m43,
+			m44: self.m44 + rhs.m44
+		}
+	}
+}
+
+impl<
+	T: Sub<T, T>
+>
+Sub<Matrix4<T>, Matrix4<T>> 
+for Matrix4<T> {
+	fn sub(&self, rhs: &Matrix4<T>) -> Matrix4<T> {
+		Matrix4 {
+			m11: self.m11 - rhs.m11,
+			m12: self.m12 - rhs.m12,
+			m13: self.m13 - rhs.m13,
+			m14: self.m14 - rhs.m14,
+
+			m21: self.m21 - rhs.m21,
+			m22: self.m22 - rhs.m22,
+			m23: self.m23 - rhs.m23,
+			m24: self.m24 - rhs.m24,
+
+			m31: self.m31 - rhs.m31,
+			m32: self.m32 - rhs.m32,
+			m33: self.m33 - rhs.m33,
+			m34: self.m34 - rhs.m34,
+
+			m41: self.m41 - rhs.m41,
+			m42: self.m42 - rhs.m42,
+			m43: self.m43 - rhs.m43,
+			m44: self.m44 - rhs.m44
+		}
+	}
+}
+
+impl<
+	T: Mul<T, T> + Add<T, T>
+>
+Mul<Matrix4<T>, Matrix4<T>> 
+for Matrix4<T> {
+	fn mul(&self, rhs: &Matrix4<T>) -> Matrix4<T> {
+		Matrix4 {
+			m11: self.m11*rhs.m11
+				+self.m12*rhs.m21
+				+self.m13*rhs.m31
+				+self.m14*rhs.m41,
+			m12: self.m11*rhs.m12
+				+self.m12*rhs.m22
+				+self.m13*rhs.m32
+				+self.m14*rhs.m42,
+			m13: self.m11*rhs.m13
+				+self.m12*rhs.m23
+				+self.m13*rhs.m33
+				+self.m14*rhs.m43,
+			m14: self.m11*rhs.m14
+				+self.m12*rhs.m24
+				+self.m13*rhs.m34
+				+self.m14*rhs.m44,
+
+			m21: self.m21*rhs.m11
+				+self.m22*rhs.m21
+				+self.m23*rhs.m31
+				+self.m24*rhs.m41,
+			m22: self.m21*rhs.m12
+				+self.m22*rhs.m22
+				+self.m23*rhs.m32
+				+self.m24*rhs.m42,
+			m23: self.m21*rhs.m13
+				+self.m22*rhs.m23
+				+self.m23*rhs.m33
+				+self.m24*rhs.m43,
+			m24: self.m21*rhs.m14
+				+self.m22*rhs.m24
+				+self.m23*rhs.m34
+				+self.m24*rhs.m44,
+		
+			m31: self.m31*rhs.m11
+				+self.m32*rhs.m21
+				+self.m33*rhs.m31
+				+self.m34*rhs.m41,
+			m32: self.m31*rhs.m12
+				+self.m32*rhs.m22
+				+self.m33*rhs.m32
+				+self.m34*rhs.m42,
+			m33: self.m31*rhs.m13
+				+self.m32*rhs.m23
+				+self.m33*rhs.m33
+				+self.m34*rhs.m43,
+			m34: self.m31*rhs.m14
+				+self.m32*rhs.m24
+				+self.m33*rhs.m34
+				+self.m34*rhs.m44,
+
+			m41: self.m41*rhs.m11
+				+self.m42*rhs.m21
+				+self.m43*rhs.m31
+				+self.m44*rhs.m41,
+			m42: self.m41*rhs.m12
+				+self.m42*rhs.m22
+				+self.m43*rhs.m32
+				+self.m44*rhs.m42,
+			m43: self.m41*rhs.m13
+				+self.m42*rhs.m23
+				+self.m43*rhs.m33
+				+self.m44*rhs.m43,
+			m44: self.m41*rhs.m14
+				+self.m42*rhs.m24
+				+self.m43*rhs.m34
+				+self.m44*rhs.m44
+		}
+	}
+}
+
+impl<
+	T: Neg<T>
+>
+Neg<Matrix4<T>> 
+for Matrix4<T> {
+	fn neg(&self) -> Matrix4<T> {
+		Matrix4 {
+			m11: -self.m11, 
+			m12: -self.m12, 
+			m13: -self.m13, 
+			m14: -self.m14,
+
+			m21: -self.m21, 
+			m22: -self.m22, 
+			m23: -self.m23, 
+			m24: -self.m24,
+			
+			m31: -self.m31, 
+			m32: -self.m32, 
+			m33: -self.m33, 
+			m34: -self.m34,
+
+			m41: -self.m41, 
+			m42: -self.m42, 
+			m43: -self.m43, 
+			m44: -self.m44
+		}
+	}
+}
+
+impl<
+	T: Add<T, T> + Mul<T, T> + Sub<T, T>
+>
+Det<T> 
+for Matrix4<T> {
+	fn det(&self) -> T {
+		self.m11*self.m22*self.m33*self.m44
+		+self.m11*self.m23*self.m34*self.m42
+		+self.m11*self.m24*self.m32*self.m43
+
+		+self.m12*self.m21*self.m34*self.m43
+		+self.m12*self.m23*self.m31*self.m44
+		+self.m12*self.m24*self.m33*self.m41
+
+		+self.m13*self.m21*self.m32*self.m44
+		+self.m13*self.m22*self.m34*self.m41
+		+self.m13*self.m24*self.m31*self.m42
+
+		+self.m14*self.m21*self.m33*self.m42
+		+self.m14*self.m22*self.m31*self.m43
+		+self.m14*self.m23*self.m32*self.m41
+
+		-self.m11*self.m22*self.m34*self.m43
+		-self.m11*self.m23*self.m32*self.m44
+		-self.m11*self.m24*self.m33*self.m42
+
+		-self.m12*self.m21*self.m33*self.m44
+		-self.m12*self.m23*self.m34*self.m41
+		-self.m12*self.m24*self.m31*self.m43
+
+		-self.m13*self.m21*self.m34*self.m42
+		-self.m13*self.m22*self.m31*self.m44
+		-self.m13*self.m24*self.m32*self.m41
+
+		-self.m14*self.m21*self.m32*self.m43
+		-self.m14*self.m22*self.m33*self.m41
+		-self.m14*self.m23*self.m31*self.m42
+	}
+}
+
+/// Returns the three indices in `0..4` that are different from `i`.
+fn matrix4_others(i: uint) -> (uint, uint, uint) {
+	match i {
+		0 => (1, 2, 3),
+		1 => (0, 2, 3),
+		2 => (0, 1, 3),
+		3 => (0, 1, 2),
+		_ => fail!("Matrix4 index out of bounds")
+	}
+}
+
+impl<
+	T: Clone
+>
+Matrix4<T> {
+	/// Returns the transposed matrix, where rows become columns.
+	pub fn transpose(&self) -> Matrix4<T> {
+		Matrix4::new(
+			self.m11.clone(), self.m21.clone(), self.m31.clone(), self.m41.clone(),
+			self.m12.clone(), self.m22.clone(), self.m32.clone(), self.m42.clone(),
+			self.m13.clone(), self.m23.clone(), self.m33.clone(), self.m43.clone(),
+			self.m14.clone(), self.m24.clone(), self.m34.clone(), self.m44.clone()
+		)
+	}
+}
+
+impl<
+	T: Add<T, T> + Sub<T, T> + Mul<T, T>
+>
+Matrix4<T> {
+	/// Computes the sum of the diagonal elements.
+	pub fn trace(&self) -> T {
+		self.m11 + self.m22 + self.m33 + self.m44
+	}
+
+	/// Computes the determinant of the 3x3 matrix
+	/// left after removing a zero-based row and column.
+	pub fn minor(&self, i: uint, j: uint) -> T {
+		let (c0, c1, c2) = matrix4_others(j);
+		self.det3(i, c0, c1, c2)
+	}
+
+	/// Computes the determinant of the 3x3 matrix of the given columns
+	/// left after removing a zero-based row.
+	fn det3(&self, i: uint, c0: uint, c1: uint, c2: uint) -> T {
+		let (r0, r1, r2) = matrix4_others(i);
+		*self.elem(r0, c0) * (
+			*self.elem(r1, c1) * *self.elem(r2, c2)
+			- *self.elem(r1, c2) * *self.elem(r2, c1))
+		- *self.elem(r0, c1) * (
+			*self.elem(r1, c0) * *self.elem(r2, c2)
+			- *self.elem(r1, c2) * *self.elem(r2, c0))
+		+ *self.elem(r0, c2) * (
+			*self.elem(r1, c0) * *self.elem(r2, c1)
+			- *self.elem(r1, c1) * *self.elem(r2, c0))
+	}
+
+	/// Computes the minor with the sign of the zero-based row and column,
+	/// which is negative when the sum of the indices is odd.
+	pub fn cofactor(&self, i: uint, j: uint) -> T {
+		if (i + j) % 2 == 0 {
+			self.minor(i, j)
+		} else {
+			// Swapping two columns negates the determinant.
+			let (c0, c1, c2) = matrix4_others(j);
+			self.det3(i, c1, c0, c2)
+		}
+	}
+
+	/// Computes the adjugate, which is the transpose of the cofactor matrix.
+	/// The product of a matrix and its adjugate is the determinant times identity.
+	pub fn adjugate(&self) -> Matrix4<T> {
+		Matrix4::new(
+			self.cofactor(0, 0), self.cofactor(1, 0), self.cofactor(2, 0), self.cofactor(3, 0),
+			self.cofactor(0, 1), self.cofactor(1, 1), self.cofactor(2, 1), self.cofactor(3, 1),
+			self.cofactor(0, 2), self.cofactor(1, 2), self.cofactor(2, 2), self.cofactor(3, 2),
+			self.cofactor(0, 3), self.cofactor(1, 3), self.cofactor(2, 3), self.cofactor(3, 3)
+		)
+	}
+}
+
+impl<
+	T: Div<T, T>
+>
+Matrix4<T> {
+	/// Divides every element by a scalar.
+	fn div_scalar(&self, s: &T) -> Matrix4<T> {
+		Matrix4 {
+			m11: self.m11 / *s,
+			m12: self.m12 / *s,
+			m13: self.m13 / *s,
+			m14: self.m14 / *s,
+			m21: self.m21 / *s,
+			m22: self.m22 / *s,
+			m23: self.m23 / *s,
+			m24: self.m24 / *s,
+			m31: self.m31 / *s,
+			m32: self.m32 / *s,
+			m33: self.m33 / *s,
+			m34: self.m34 / *s,
+			m41: self.m41 / *s,
+			m42: self.m42 / *s,
+			m43: self.m43 / *s,
+			m44: self.m44 / *s
+		}
+	}
+}
+
+impl<
+	T: Add<T, T> + Mul<T, T> + Sub<T, T> + Div<T, T>
+>
+Inv<Matrix4<T>> 
+for Matrix4<T> {
+	fn inv(&self) -> Matrix4<T> {
+		let det = self.det();
+		self.adjugate().div_scalar(&det)
+	}
+}
+
+impl<
+	T: Add<T, T> + Mul<T, T> + Sub<T, T> + Div<T, T>
+>
+Div<Matrix4<T>, Matrix4<T>> 
+for Matrix4<T> {
+	fn div(&self, rhs: &Matrix4<T>) -> Matrix4<T> {
+		let inv = rhs.inv();
+		self * inv
+	}
+}
+
+impl<
+	T: Eps
+>
+Eps 
+for Matrix4<T> {
+	fn close_eps(&self, other: &Matrix4<T>, eps: f64) -> bool {
+		self.m11.close_eps(&other.m11, eps)
+		&& self.m12.close_eps(&other.m12, eps)
+		&& self.m13.close_eps(&other.m13, eps)
+		&& self.m14.close_eps(&other.m14, eps)
+
+		&& self.m21.close_eps(&other.m21, eps)
+		&& self.m22.close_eps(&other.m22, eps)
+		&& self.m23.close_eps(&other.m23, eps)
+		&& self.m24.close_eps(&other.m24, eps)
+
+		&& self.m31.close_eps(&other.m31, eps)
+		&& self.m32.close_eps(&other.m32, eps)
+		&& self.m33.close_eps(&other.m33, eps)
+		&& self.m34.close_eps(&other.m34, eps)
+
+		&& self.m41.close_eps(&other.m41, eps)
+		&& self.m42.close_eps(&other.m42, eps)
+		&& self.m43.close_eps(&other.m43, eps)
+		&& self.m44.close_eps(&other.m44, eps)
+	}
+}
+
+impl<
+	T: std::num::Zero + std::num::One
+>
+std::num::One 
+for Matrix4<T> {
+	fn one() -> Matrix4<T> {
+		Matrix4 {
+			m11: std::num::one(),
+			m12: std::num::zero(),
+			m13: std::num::zero(),
+			m14: std::num::zero(),
+
+			m21: std::num::zero(),
+			m22: std::num::one(),
+			m23: std::num::zero(),
+			m24: std::num::zero(),
+
+			m31: std::num::zero(),
+			m32: std::num::zero(),
+			m33: std::num::one(),
+			m34: std::num::zero(),
+
+			m41: std::num::zero(),
+			m42: std::num::zero(),
+			m43: std::num::zero(),
+			m44: std::num::one()
+		}
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+Matrix4<T> {
+	/// Computes the smallest absolute pivot relative to the largest.
+	fn pivot_ratio(&self) -> T {
+		pivot_ratio(std::vec::from_fn(16, |k| self.get(k / 4, k % 4)), 4)
+	}
+}
+
+impl<
+	T: std::num::Float + Eps
+>
+TryInv<Matrix4<T>> for Matrix4<T> {
+	fn try_inv(&self, eps: f64) -> Option<Matrix4<T>> {
+		if is_singular(&self.pivot_ratio(), eps) {
+			None
+		} else {
+			Some(self.inv())
+		}
+	}
+}
+
+impl<
+	T: std::num::Float + Eps
+>
+TryDiv<Matrix4<T>, Matrix4<T>> for Matrix4<T> {
+	fn try_div(&self, rhs: &Matrix4<T>, eps: f64) -> Option<Matrix4<T>> {
+		if is_singular(&rhs.pivot_ratio(), eps) {
+			None
+		} else {
+			Some(self.div(rhs))
+		}
+	}
+}
+
+/// A Matrix2 is commonly used for linear transformations in 2D space.
+#[deriving(Eq, Zero)]
+pub struct Matrix2<T> {
+	/// Element at first row and first column.
+	m11: T, 
+	/// Element at first row and second column.
+	m12: T,
+	
+	/// Element at second row and first column.
+	m21: T, 
+	/// Element at second row and second column.
+	m22: T
+}
+
+/// Creates a new matrix with elements.
+#[inline(always)]
+pub fn matrix2<T>(
+	m11: T, m12: T,
+	m21: T, m22: T
+) -> Matrix2<T> {
+	Matrix2::new(
+		m11, m12,
+		m21, m22
+	)
+}
+
+impl<T> 
+Matrix2<T> {
+	/// Creates a new matrix with elements.
+	pub fn new(
+		m11: T, m12: T,
+		m21: T, m22: T
+	) -> Matrix2<T> {
+		Matrix2 {
+			m11: m11, m12: m12,
+			m21: m21, m22: m22
+		}
+	}
+}
+
+impl<T>
+Matrix2<T> {
+	/// Returns a reference to the element at a zero-based row and column.
+	pub fn elem<'a>(&'a self, row: uint, col: uint) -> &'a T {
+		match (row, col) {
+			(0, 0) => &self.m11,
+			(0, 1) => &self.m12,
+			(1, 0) => &self.m21,
+			(1, 1) => &self.m22,
+			_ => fail!("Matrix2 index out of bounds")
+		}
+	}
+
+	/// Returns a mutable reference to the element at a zero-based row and column.
+	pub fn elem_mut<'a>(&'a mut self, row: uint, col: uint) -> &'a mut T {
+		match (row, col) {
+			(0, 0) => &mut self.m11,
+			(0, 1) => &mut self.m12,
+			(1, 0) => &mut self.m21,
+			(1, 1) => &mut self.m22,
+			_ => fail!("Matrix2 index out of bounds")
+		}
+	}
+
+	/// Sets the element at a zero-based row and column.
+	pub fn set(&mut self, row: uint, col: uint, val: T) {
+		*self.elem_mut(row, col) = val;
+	}
+
+	/// Destructures into an array of rows.
+	pub fn into_array(self) -> [[T, ..2], ..2] {
+		let Matrix2 {
+			m11, m12,
+			m21, m22
+		} = self;
+		[
+			[m11, m12],
+			[m21, m22]
+		]
+	}
+}
+
+impl<
+	T: Clone
+>
+Matrix2<T> {
+	/// Returns the element at a zero-based row and column.
+	pub fn get(&self, row: uint, col: uint) -> T {
+		self.elem(row, col).clone()
+	}
+
+	/// Returns the elements of a zero-based row.
+	pub fn row(&self, i: uint) -> [T, ..2] {
+		[self.get(i, 0), self.get(i, 1)]
+	}
+
+	/// Returns the elements of a zero-based column.
+	pub fn col(&self, j: uint) -> [T, ..2] {
+		[self.get(0, j), self.get(1, j)]
+	}
+
+	/// Sets the elements of a zero-based row.
+	pub fn set_row(&mut self, i: uint, row: [T, ..2]) {
+		for j in range(0u, 2) {
+			self.set(i, j, row[j].clone());
+		}
+	}
+
+	/// Sets the elements of a zero-based column.
+	pub fn set_col(&mut self, j: uint, col: [T, ..2]) {
+		for i in range(0u, 2) {
+			self.set(i, j, col[i].clone());
+		}
+	}
+
+	/// Creates a matrix from an array of rows.
+	pub fn from_array(a: [[T, ..2], ..2]) -> Matrix2<T> {
+		Matrix2::new(
+			a[0][0].clone(), a[0][1].clone(),
+			a[1][0].clone(), a[1][1].clone()
+		)
+	}
+
+	/// Returns an array of rows.
+	pub fn to_array(&self) -> [[T, ..2], ..2] {
+		[self.row(0), self.row(1)]
+	}
+
+	/// Returns the transposed matrix, where rows become columns.
+	pub fn transpose(&self) -> Matrix2<T> {
+		Matrix2::new(
+			self.m11.clone(), self.m21.clone(),
+			self.m12.clone(), self.m22.clone()
+		)
+	}
+}
+
+impl<
+	T: Add<T, T> + Mul<T, T>
+>
+Matrix2<T> {
+	/// Multiplies a 2D vector by the matrix.
+	pub fn transform(&self, (x, y): (T, T)) -> (T, T) {
+		(
+			self.m11 * x + self.m12 * y,
+			self.m21 * x + self.m22 * y
+		)
+	}
+}
+
+impl<
+	T: std::num::Zero + Clone
+>
+Scale<T> 
+for Matrix2<T> {
+	fn scale(factor: T) -> Matrix2<T> {
+		Matrix2 {
+			m11: factor.clone(),
+			m12: std::num::zero(),
+
+			m21: std::num::zero(),
+			m22: factor.clone()
+		}
+	}
+}
+
+impl<
+	T: Add<T, T>
+>
+Add<Matrix2<T>, Matrix2<T>> 
+for Matrix2<T> {
+	fn add(&self, rhs: &Matrix2<T>) -> Matrix2<T> {
+		Matrix2 {
+			m11: self.m11 + rhs.m11,
+			m12: self.m12 + rhs.m12,
+
+			m21: self.m21 + rhs.m21,
+			m22: self.m22 + rhs.m22
+		}
+	}
+}
+
+impl<
+	T: Sub<T, T>
+>
+Sub<Matrix2<T>, Matrix2<T>> 
+for Matrix2<T> {
+	fn sub(&self, rhs: &Matrix2<T>) -> Matrix2<T> {
+		Matrix2 {
+			m11: self.m11 - rhs.m11,
+			m12: self.m12 - rhs.m12,
+
+			m21: self.m21 - rhs.m21,
+			m22: self.m22 - rhs.m22
+		}
+	}
+}
+
+impl<
+	T: Mul<T, T> + Add<T, T>
+>
+Mul<Matrix2<T>, Matrix2<T>> 
+for Matrix2<T> {
+	fn mul(&self, rhs: &Matrix2<T>) -> Matrix2<T> {
+		Matrix2 {
+			m11: self.m11*rhs.m11
+				+self.m12*rhs.m21,
+			m12: self.m11*rhs.m12
+				+self.m12*rhs.m22,
+
+			m21: self.m21*rhs.m11
+				+self.m22*rhs.m21,
+			m22: self.m21*rhs.m12
+				+self.m22*rhs.m22
+		}
+	}
+}
+
+impl<
+	T: Neg<T>
+>
+Neg<Matrix2<T>> 
+for Matrix2<T> {
+	fn neg(&self) -> Matrix2<T> {
+		Matrix2 {
+			m11: -self.m11,
+			m12: -self.m12,
+
+			m21: -self.m21,
+			m22: -self.m22
+		}
+	}
+}
+
+impl<
+	T: Add<T, T> + Sub<T, T> + Mul<T, T>
+>
+Det<T> 
+for Matrix2<T> {
+	fn det(&self) -> T {
+		self.m11*self.m22 - self.m12*self.m21
+	}
+}
+
+impl<
+	T: Add<T, T> + Sub<T, T> + Mul<T, T> + Neg<T> + Clone
+>
+Matrix2<T> {
+	/// Computes the sum of the diagonal elements.
+	pub fn trace(&self) -> T {
+		self.m11 + self.m22
+	}
+
+	/// Returns the element left after removing a zero-based row and column.
+	pub fn minor(&self, i: uint, j: uint) -> T {
+		if i > 1 || j > 1 {
+			fail!("Matrix2 index out of bounds");
+		}
+		self.get(1 - i, 1 - j)
+	}
+
+	/// Computes the minor with the sign of the zero-based row and column,
+	/// which is negative when the sum of the indices is odd.
+	pub fn cofactor(&self, i: uint, j: uint) -> T {
+		if (i + j) % 2 == 0 {
+			self.minor(i, j)
+		} else {
+			-self.minor(i, j)
+		}
+	}
+
+	/// Computes the adjugate, which is the transpose of the cofactor matrix.
+	/// The product of a matrix and its adjugate is the determinant times identity.
+	pub fn adjugate(&self) -> Matrix2<T> {
+		Matrix2::new(
+			self.cofactor(0, 0), self.cofactor(1, 0),
+			self.cofactor(0, 1), self.cofactor(1, 1)
+		)
+	}
+}
+
+impl<
+	T: Add<T, T> + Mul<T, T> + Sub<T, T> + Div<T, T> + Neg<T>
+>
+Inv<Matrix2<T>> 
+for Matrix2<T> {
+	fn inv(&self) -> Matrix2<T> {
+		let det = self.det();
+		Matrix2 {
+			m11: self.m22 / det,
+			m12: -self.m12 / det,
+			m21: -self.m21 / det,
+			m22: self.m11 / det
+		}
+	}
+}
+
+impl<
+	T: Add<T, T> + Mul<T, T> + Sub<T, T> + Div<T, T> + Neg<T>
+>
+Div<Matrix2<T>, Matrix2<T>> 
+for Matrix2<T> {
+	fn div(&self, rhs: &Matrix2<T>) -> Matrix2<T> {
+		let inv = rhs.inv();
+		self * inv
+	}
+}
+
+impl<
+	T: Eps
+>
+Eps 
+for Matrix2<T> {
+	fn close_eps(&self, other: &Matrix2<T>, eps: f64) -> bool {
+		self.m11.close_eps(&other.m11, eps)
+		&& self.m12.close_eps(&other.m12, eps)
+
+		&& self.m21.close_eps(&other.m21, eps)
+		&& self.m22.close_eps(&other.m22, eps)
+	}
+}
+
+impl<
+	T: std::num::Zero + std::num::One
+>
+std::num::One 
+for Matrix2<T> {
+	fn one() -> Matrix2<T> {
+		Matrix2 {
+			m11: std::num::one(),
+			m12: std::num::zero(),
+
+			m21: std::num::zero(),
+			m22: std::num::one()
+		}
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+Matrix2<T> {
+	/// Computes the smallest absolute pivot relative to the largest.
+	fn pivot_ratio(&self) -> T {
+		pivot_ratio(std::vec::from_fn(4, |k| self.get(k / 2, k % 2)), 2)
+	}
+}
+
+impl<
+	T: std::num::Float + Eps
+>
+TryInv<Matrix2<T>> for Matrix2<T> {
+	fn try_inv(&self, eps: f64) -> Option<Matrix2<T>> {
+		if is_singular(&self.pivot_ratio(), eps) {
+			None
+		} else {
+			Some(self.inv())
+		}
+	}
+}
+
+impl<
+	T: std::num::Float + Eps
+>
+TryDiv<Matrix2<T>, Matrix2<T>> for Matrix2<T> {
+	fn try_div(&self, rhs: &Matrix2<T>, eps: f64) -> Option<Matrix2<T>> {
+		if is_singular(&rhs.pivot_ratio(), eps) {
+			None
+		} else {
+			Some(self.div(rhs))
+		}
+	}
+}
+
+impl<
+	T: Clone
+>
+Matrix2<T> {
+	/// Creates a matrix from the upper left part of a Matrix3.
+	pub fn from_matrix3(m: &Matrix3<T>) -> Matrix2<T> {
+		Matrix2::new(
+			m.m11.clone(), m.m12.clone(),
+			m.m21.clone(), m.m22.clone()
+		)
+	}
+}
+
+/// A Matrix3 is commonly used for linear transformations in 3D space
+/// and for affine transformations in 2D space.
+#[deriving(Eq, Zero)]
+pub struct Matrix3<T> {
+	/// Element at first row and first column.
+	m11: T, 
+	/// Element at first row and second column.
+	m12: T, 
+	/// Element at first row and third column.
+	m13: T,
+	
+	/// Element at second row and first column.
+	m21: T, 
+	/// Element at second row and second column.
+	m22: T, 
+	/// Element at second row and third column.
+	m23: T,
+	
+	/// Element at third row and first column.
+	m31: T, 
+	/// Element at third row and second column.
+	m32: T, 
+	/// Element at third row and third column.
+	m33: T
+}
+
+/// Creates a new matrix with elements.
+#[inline(always)]
+pub fn matrix3<T>(
+	m11: T, m12: T, m13: T,
+	m21: T, m22: T, m23: T,
+	m31: T, m32: T, m33: T
+) -> Matrix3<T> {
+	Matrix3::new(
+		m11, m12, m13,
+		m21, m22, m23,
+		m31, m32, m33
+	)
+}
+
+impl<T> 
+Matrix3<T> {
+	/// Creates a new matrix with elements.
+	pub fn new(
+		m11: T, m12: T, m13: T,
+		m21: T, m22: T, m23: T,
+		m31: T, m32: T, m33: T
+	) -> Matrix3<T> {
+		Matrix3 {
+			m11: m11, m12: m12, m13: m13,
+			m21: m21, m22: m22, m23: m23,
+			m31: m31, m32: m32, m33: m33
+		}
+	}
+}
+
+impl<T>
+Matrix3<T> {
+	/// Returns a reference to the element at a zero-based row and column.
+	pub fn elem<'a>(&'a self, row: uint, col: uint) -> &'a T {
+		match (row, col) {
+			(0, 0) => &self.m11,
+			(0, 1) => &self.m12,
+			(0, 2) => &self.m13,
+			(1, 0) => &self.m21,
+			(1, 1) => &self.m22,
+			(1, 2) => &self.m23,
+			(2, 0) => &self.m31,
+			(2, 1) => &self.m32,
+			(2, 2) => &self.m33,
+			_ => fail!("Matrix3 index out of bounds")
+		}
+	}
+
+	/// Returns a mutable reference to the element at a zero-based row and column.
+	pub fn elem_mut<'a>(&'a mut self, row: uint, col: uint) -> &'a mut T {
+		match (row, col) {
+			(0, 0) => &mut self.m11,
+			(0, 1) => &mut self.m12,
+			(0, 2) => &mut self.m13,
+			(1, 0) => &mut self.m21,
+			(1, 1) => &mut self.m22,
+			(1, 2) => &mut self.m23,
+			(2, 0) => &mut self.m31,
+			(2, 1) => &mut self.m32,
+			(2, 2) => &mut self.m33,
+			_ => fail!("Matrix3 index out of bounds")
+		}
+	}
+
+	/// Sets the element at a zero-based row and column.
+	pub fn set(&mut self, row: uint, col: uint, val: T) {
+		*self.elem_mut(row, col) = val;
+	}
+
+	/// Destructures into an array of rows.
+	pub fn into_array(self) -> [[T, ..3], ..3] {
+		let Matrix3 {
+			m11, m12, m13,
+			m21, m22, m23,
+			m31, m32, m33
+		} = self;
+		[
+			[m11, m12, m13],
+			[m21, m22, m23],
+			[m31, m32, m33]
+		]
+	}
+}
+
+impl<
+	T: Clone
+>
+Matrix3<T> {
+	/// Returns the element at a zero-based row and column.
+	pub fn get(&self, row: uint, col: uint) -> T {
+		self.elem(row, col).clone()
+	}
+
+	/// Returns the elements of a zero-based row.
+	pub fn row(&self, i: uint) -> [T, ..3] {
+		[self.get(i, 0), self.get(i, 1), self.get(i, 2)]
+	}
+
+	/// Returns the elements of a zero-based column.
+	pub fn col(&self, j: uint) -> [T, ..3] {
+		[self.get(0, j), self.get(1, j), self.get(2, j)]
+	}
+
+	/// Sets the elements of a zero-based row.
+	pub fn set_row(&mut self, i: uint, row: [T, ..3]) {
+		for j in range(0u, 3) {
+			self.set(i, j, row[j].clone());
+		}
+	}
+
+	/// Sets the elements of a zero-based column.
+	pub fn set_col(&mut self, j: uint, col: [T, ..3]) {
+		for i in range(0u, 3) {
+			self.set(i, j, col[i].clone());
+		}
+	}
+
+	/// Creates a matrix from an array of rows.
+	pub fn from_array(a: [[T, ..3], ..3]) -> Matrix3<T> {
+		Matrix3::new(
+			a[0][0].clone(), a[0][1].clone(), a[0][2].clone(),
+			a[1][0].clone(), a[1][1].clone(), a[1][2].clone(),
+			a[2][0].clone(), a[2][1].clone(), a[2][2].clone()
+		)
+	}
+
+	/// Returns an array of rows.
+	pub fn to_array(&self) -> [[T, ..3], ..3] {
+		[self.row(0), self.row(1), self.row(2)]
+	}
+
+	/// Returns the transposed matrix, where rows become columns.
+	pub fn transpose(&self) -> Matrix3<T> {
+		Matrix3::new(
+			self.m11.clone(), self.m21.clone(), self.m31.clone(),
+			self.m12.clone(), self.m22.clone(), self.m32.clone(),
+			self.m13.clone(), self.m23.clone(), self.m33.clone()
+		)
+	}
+}
+
+impl<
+	T: Add<T, T> + Mul<T, T>
+>
+Matrix3<T> {
+	/// Multiplies a vector by the matrix.
+	pub fn transform(&self, v: &Vector3<T>) -> Vector3<T> {
+		Vector3 {
+			x: self.m11 * v.x + self.m12 * v.y + self.m13 * v.z,
+			y: self.m21 * v.x + self.m22 * v.y + self.m23 * v.z,
+			z: self.m31 * v.x + self.m32 * v.y + self.m33 * v.z
+		}
+	}
+}
+
+impl<
+	T: std::num::Zero + Clone
+>
+Scale<T> 
+for Matrix3<T> {
+	fn scale(factor: T) -> Matrix3<T> {
+		Matrix3 {
+			m11: factor.clone(),
+			m12: std::num::zero(),
+			m13: std::num::zero(),
+
+			m21: std::num::zero(),
+			m22: factor.clone(),
+			m23: std::num::zero(),
+
+			m31: std::num::zero(),
+			m32: std::num::zero(),
+			m33: factor.clone()
+		}
+	}
+}
+
+impl<
+	T: Add<T, T>
+>
+Add<Matrix3<T>, Matrix3<T>> 
+for Matrix3<T> {
+	fn add(&self, rhs: &Matrix3<T>) -> Matrix3<T> {
+		Matrix3 {
+			m11: self.m11 + rhs.m11,
+			m12: self.m12 + rhs.m12,
+			m13: self.m13 + rhs.m13,
+
+			m21: self.m21 + rhs.m21,
+			m22: self.m22 + rhs.m22,
+			m23: self.m23 + rhs.m23,
+
+			m31: self.m31 + rhs.m31,
+			m32: self.m32 + rhs.m32,
+			m33: self.m33 + rhs.m33
+		}
+	}
+}
+
+impl<
+	T: Sub<T, T>
+>
+Sub<Matrix3<T>, Matrix3<T>> 
+for Matrix3<T> {
+	fn sub(&self, rhs: &Matrix3<T>) -> Matrix3<T> {
+		Matrix3 {
+			m11: self.m11 - rhs.m11,
+			m12: self.m12 - rhs.m12,
+			m13: self.m13 - rhs.m13,
+
+			m21: self.m21 - rhs.m21,
+			m22: self.m22 - rhs.m22,
+			m23: self.m23 - rhs.m23,
+
+			m31: self.m31 - rhs.m31,
+			m32: self.m32 - rhs.m32,
+			m33: self.m33 - rhs.m33
+		}
+	}
+}
+
+impl<
+	T: Mul<T, T> + Add<T, T>
+>
+Mul<Matrix3<T>, Matrix3<T>> 
+for Matrix3<T> {
+	fn mul(&self, rhs: &Matrix3<T>) -> Matrix3<T> {
+		Matrix3 {
+			m11: self.m11*rhs.m11
+				+self.m12*rhs.m21
+				+self.m13*rhs.m31,
+			m12: self.m11*rhs.m12
+				+self.m12*rhs.m22
+				+self.m13*rhs.m32,
+			m13: self.m11*rhs.m13
+				+self.m12*rhs.m23
+				+self.m13*rhs.m33,
+
+			m21: self.m21*rhs.m11
+				+self.m22*rhs.m21
+				+self.m23*rhs.m31,
+			m22: self.m21*rhs.m12
+				+self.m22*rhs.m22
+				+self.m23*rhs.m32,
+			m23: self.m21*rhs.m13
+				+self.m22*rhs.m23
+				+self.m23*rhs.m33,
+
+			m31: self.m31*rhs.m11
+				+self.m32*rhs.m21
+				+self.m33*rhs.m31,
+			m32: self.m31*rhs.m12
+				+self.m32*rhs.m22
+				+self.m33*rhs.m32,
+			m33: self.m31*rhs.m13
+				+self.m32*rhs.m23
+				+self.m33*rhs.m33
+		}
+	}
+}
+
+impl<
+	T: Neg<T>
+>
+Neg<Matrix3<T>> 
+for Matrix3<T> {
+	fn neg(&self) -> Matrix3<T> {
+		Matrix3 {
+			m11: -self.m11,
+			m12: -self.m12,
+			m13: -self.m13,
+
+			m21: -self.m21,
+			m22: -self.m22,
+			m23: -self.m23,
+
+			m31: -self.m31,
+			m32: -self.m32,
+			m33: -self.m33
+		}
+	}
+}
+
+impl<
+	T: Add<T, T> + Sub<T, T> + Mul<T, T>
+>
+Det<T> 
+for Matrix3<T> {
+	fn det(&self) -> T {
+		self.m11*(self.m22*self.m33 - self.m23*self.m32)
+		-self.m12*(self.m21*self.m33 - self.m23*self.m31)
+		+self.m13*(self.m21*self.m32 - self.m22*self.m31)
+	}
+}
+
+/// Returns the two indices in `0..3` that are different from `i`.
+fn matrix3_others(i: uint) -> (uint, uint) {
+	match i {
+		0 => (1, 2),
+		1 => (0, 2),
+		2 => (0, 1),
+		_ => fail!("Matrix3 index out of bounds")
+	}
+}
+
+impl<
+	T: Add<T, T> + Sub<T, T> + Mul<T, T> + Neg<T>
+>
+Matrix3<T> {
+	/// Computes the sum of the diagonal elements.
+	pub fn trace(&self) -> T {
+		self.m11 + self.m22 + self.m33
+	}
+
+	/// Computes the determinant of the 2x2 matrix
+	/// left after removing a zero-based row and column.
+	pub fn minor(&self, i: uint, j: uint) -> T {
+		let (r0, r1) = matrix3_others(i);
+		let (c0, c1) = matrix3_others(j);
+		*self.elem(r0, c0) * *self.elem(r1, c1)
+		- *self.elem(r0, c1) * *self.elem(r1, c0)
+	}
+
+	/// Computes the minor with the sign of the zero-based row and column,
+	/// which is negative when the sum of the indices is odd.
+	pub fn cofactor(&self, i: uint, j: uint) -> T {
+		if (i + j) % 2 == 0 {
+			self.minor(i, j)
+		} else {
+			-self.minor(i, j)
+		}
+	}
+
+	/// Computes the adjugate, which is the transpose of the cofactor matrix.
+	/// The product of a matrix and its adjugate is the determinant times identity.
+	pub fn adjugate(&self) -> Matrix3<T> {
+		Matrix3::new(
+			self.cofactor(0, 0), self.cofactor(1, 0), self.cofactor(2, 0),
+			self.cofactor(0, 1), self.cofactor(1, 1), self.cofactor(2, 1),
+			self.cofactor(0, 2), self.cofactor(1, 2), self.cofactor(2, 2)
+		)
+	}
+}
+
+impl<
+	T: Div<T, T>
+>
+Matrix3<T> {
+	/// Divides every element by a scalar.
+	fn div_scalar(&self, s: &T) -> Matrix3<T> {
+		Matrix3 {
+			m11: self.m11 / *s,
+			m12: self.m12 / *s,
+			m13: self.m13 / *s,
+			m21: self.m21 / *s,
+			m22: self.m22 / *s,
+			m23: self.m23 / *s,
+			m31: self.m31 / *s,
+			m32: self.m32 / *s,
+			m33: self.m33 / *s
+		}
+	}
+}
+
+impl<
+	T: Add<T, T> + Mul<T, T> + Sub<T, T> + Div<T, T> + Neg<T>
+>
+Inv<Matrix3<T>> 
+for Matrix3<T> {
+	fn inv(&self) -> Matrix3<T> {
+		let det = self.det();
+		self.adjugate().div_scalar(&det)
+	}
+}
+
+impl<
+	T: Add<T, T> + Mul<T, T> + Sub<T, T> + Div<T, T> + Neg<T>
+>
+Div<Matrix3<T>, Matrix3<T>> 
+for Matrix3<T> {
+	fn div(&self, rhs: &Matrix3<T>) -> Matrix3<T> {
+		let inv = rhs.inv();
+		self * inv
+	}
+}
+
+impl<
+	T: Eps
+>
+Eps 
+for Matrix3<T> {
+	fn close_eps(&self, other: &Matrix3<T>, eps: f64) -> bool {
+		self.m11.close_eps(&other.m11, eps)
+		&& self.m12.close_eps(&other.m12, eps)
+		&& self.m13.close_eps(&other.m13, eps)
+
+		&& self.m21.close_eps(&other.m21, eps)
+		&& self.m22.close_eps(&other.m22, eps)
+		&& self.m23.close_eps(&other.m23, eps)
+
+		&& self.m31.close_eps(&other.m31, eps)
+		&& self.m32.close_eps(&other.m32, eps)
+		&& self.m33.close_eps(&other.m33, eps)
+	}
+}
+
+impl<
+	T: std::num::Zero + std::num::One
+>
+std::num::One 
+for Matrix3<T> {
+	fn one() -> Matrix3<T> {
+		Matrix3 {
+			m11: std::num::one(),
+			m12: std::num::zero(),
+			m13: std::num::zero(),
+
+			m21: std::num::zero(),
+			m22: std::num::one(),
+			m23: std::num::zero(),
+
+			m31: std::num::zero(),
+			m32: std::num::zero(),
+			m33: std::num::one()
+		}
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+Matrix3<T> {
+	/// Computes the smallest absolute pivot relative to the largest.
+	fn pivot_ratio(&self) -> T {
+		pivot_ratio(std::vec::from_fn(9, |k| self.get(k / 3, k % 3)), 3)
+	}
+}
+
+impl<
+	T: std::num::Float + Eps
+>
+TryInv<Matrix3<T>> for Matrix3<T> {
+	fn try_inv(&self, eps: f64) -> Option<Matrix3<T>> {
+		if is_singular(&self.pivot_ratio(), eps) {
+			None
+		} else {
+			Some(self.inv())
+		}
+	}
+}
+
+impl<
+	T: std::num::Float + Eps
+>
+TryDiv<Matrix3<T>, Matrix3<T>> for Matrix3<T> {
+	fn try_div(&self, rhs: &Matrix3<T>, eps: f64) -> Option<Matrix3<T>> {
+		if is_singular(&rhs.pivot_ratio(), eps) {
+			None
+		} else {
+			Some(self.div(rhs))
+		}
+	}
+}
+
+impl<
+	T: Clone
+>
+Matrix3<T> {
+	/// Creates a matrix from the upper left part of a Matrix4.
+	pub fn from_matrix4(m: &Matrix4<T>) -> Matrix3<T> {
+		Matrix3::new(
+			m.m11.clone(), m.m12.clone(), m.m13.clone(),
+			m.m21.clone(), m.m22.clone(), m.m23.clone(),
+			m.m31.clone(), m.m32.clone(), m.m33.clone()
+		)
+	}
+
+	/// Creates a matrix from the first three columns of a Matrix3x4.
+	pub fn from_matrix3x4(m: &Matrix3x4<T>) -> Matrix3<T> {
+		Matrix3::new(
+			m.m11.clone(), m.m12.clone(), m.m13.clone(),
+			m.m21.clone(), m.m22.clone(), m.m23.clone(),
+			m.m31.clone(), m.m32.clone(), m.m33.clone()
+		)
+	}
+}
+
+impl<
+	T: std::num::Zero + std::num::One + Clone
+>
+Matrix3<T> {
+	/// Creates a matrix with a Matrix2 in the upper left part
+	/// and the rest from the identity matrix.
+	pub fn from_matrix2(m: &Matrix2<T>) -> Matrix3<T> {
+		Matrix3::new(
+			m.m11.clone(), m.m12.clone(), std::num::zero(),
+			m.m21.clone(), m.m22.clone(), std::num::zero(),
+			std::num::zero(), std::num::zero(), std::num::one()
+		)
+	}
+}
+
+/// A Matrix3x4 has three rows and four columns.
+/// It is commonly used to store affine transformations in 3D space
+/// without the last row of a Matrix4.
+#[deriving(Eq, Zero)]
+pub struct Matrix3x4<T> {
+	/// Element at first row and first column.
+	m11: T, 
+	/// Element at first row and second column.
+	m12: T, 
+	/// Element at first row and third column.
+	m13: T, 
+	/// Element at first row and fourth column.
+	m14: T,
+	
+	/// Element at second row and first column.
+	m21: T, 
+	/// Element at second row and second column.
+	m22: T, 
+	/// Element at second row and third column.
+	m23: T, 
+	/// Element at second row and fourth column.
+	m24: T,
+	
+	/// Element at third row and first column.
+	m31: T, 
+	/// Element at third row and second column.
+	m32: T, 
+	/// Element at third row and third column.
+	m33: T, 
+	/// Element at third row and fourth column.
+	m34: T
+}
+
+/// Creates a new matrix with elements.
+#[inline(always)]
+pub fn matrix3x4<T>(
+	m11: T, m12: T, m13: T, m14: T,
+	m21: T, m22: T, m23: T, m24: T,
+	m31: T, m32: T, m33: T, m34: T
+) -> Matrix3x4<T> {
+	Matrix3x4::new(
+		m11, m12, m13, m14,
+		m21, m22, m23, m24,
+		m31, m32, m33, m34
+	)
+}
+
+impl<T> 
+Matrix3x4<T> {
+	/// Creates a new matrix with elements.
+	pub fn new(
+		m11: T, m12: T, m13: T, m14: T,
+		m21: T, m22: T, m23: T, m24: T,
+		m31: T, m32: T, m33: T, m34: T
+	) -> Matrix3x4<T> {
+		Matrix3x4 {
+			m11: m11, m12: m12, m13: m13, m14: m14,
+			m21: m21, m22: m22, m23: m23, m24: m24,
+			m31: m31, m32: m32, m33: m33, m34: m34
+		}
+	}
+}
+
+impl<T>
+Matrix3x4<T> {
+	/// Returns a reference to the element at a zero-based row and column.
+	pub fn elem<'a>(&'a self, row: uint, col: uint) -> &'a T {
+		match (row, col) {
+			(0, 0) => &self.m11,
+			(0, 1) => &self.m12,
+			(0, 2) => &self.m13,
+			(0, 3) => &self.m14,
+			(1, 0) => &self.m21,
+			(1, 1) => &self.m22,
+			(1, 2) => &self.m23,
+			(1, 3) => &self.m24,
+			(2, 0) => &self.m31,
+			(2, 1) => &self.m32,
+			(2, 2) => &self.m33,
+			(2, 3) => &self.m34,
+			_ => fail!("Matrix3x4 index out of bounds")
+		}
+	}
+
+	/// Returns a mutable reference to the element at a zero-based row and column.
+	pub fn elem_mut<'a>(&'a mut self, row: uint, col: uint) -> &'a mut T {
+		match (row, col) {
+			(0, 0) => &mut self.m11,
+			(0, 1) => &mut self.m12,
+			(0, 2) => &mut self.m13,
+			(0, 3) => &mut self.m14,
+			(1, 0) => &mut self.m21,
+			(1, 1) => &mut self.m22,
+			(1, 2) => &mut self.m23,
+			(1, 3) => &mut self.m24,
+			(2, 0) => &mut self.m31,
+			(2, 1) => &mut self.m32,
+			(2, 2) => &mut self.m33,
+			(2, 3) => &mut self.m34,
+			_ => fail!("Matrix3x4 index out of bounds")
+		}
+	}
+
+	/// Sets the element at a zero-based row and column.
+	pub fn set(&mut self, row: uint, col: uint, val: T) {
+		*self.elem_mut(row, col) = val;
+	}
+
+	/// Destructures into an array of rows.
+	pub fn into_array(self) -> [[T, ..4], ..3] {
+		let Matrix3x4 {
+			m11, m12, m13, m14,
+			m21, m22, m23, m24,
+			m31, m32, m33, m34
+		} = self;
+		[
+			[m11, m12, m13, m14],
+			[m21, m22, m23, m24],
+			[m31, m32, m33, m34]
+		]
+	}
+}
+
+impl<
+	T: Clone
+>
+Matrix3x4<T> {
+	/// Returns the element at a zero-based row and column.
+	pub fn get(&self, row: uint, col: uint) -> T {
+		self.elem(row, col).clone()
+	}
+
+	/// Returns the elements of a zero-based row.
+	pub fn row(&self, i: uint) -> [T, ..4] {
+		[self.get(i, 0), self.get(i, 1), self.get(i, 2), self.get(i, 3)]
+	}
+
+	/// Returns the elements of a zero-based column.
+	pub fn col(&self, j: uint) -> [T, ..3] {
+		[self.get(0, j), self.get(1, j), self.get(2, j)]
+	}
+
+	/// Sets the elements of a zero-based row.
+	pub fn set_row(&mut self, i: uint, row: [T, ..4]) {
+		for j in range(0u, 4) {
+			self.set(i, j, row[j].clone());
+		}
+	}
+
+	/// Sets the elements of a zero-based column.
+	pub fn set_col(&mut self, j: uint, col: [T, ..3]) {
+		for i in range(0u, 3) {
+			self.set(i, j, col[i].clone());
+		}
+	}
+
+	/// Creates a matrix from an array of rows.
+	pub fn from_array(a: [[T, ..4], ..3]) -> Matrix3x4<T> {
+		Matrix3x4::new(
+			a[0][0].clone(), a[0][1].clone(), a[0][2].clone(), a[0][3].clone(),
+			a[1][0].clone(), a[1][1].clone(), a[1][2].clone(), a[1][3].clone(),
+			a[2][0].clone(), a[2][1].clone(), a[2][2].clone(), a[2][3].clone()
+		)
+	}
+
+	/// Returns an array of rows.
+	pub fn to_array(&self) -> [[T, ..4], ..3] {
+		[self.row(0), self.row(1), self.row(2)]
+	}
+
+	/// Returns the transposed matrix, where rows become columns.
+	pub fn transpose(&self) -> Matrix4x3<T> {
+		Matrix4x3::new(
+			self.m11.clone(), self.m21.clone(), self.m31.clone(),
+			self.m12.clone(), self.m22.clone(), self.m32.clone(),
+			self.m13.clone(), self.m23.clone(), self.m33.clone(),
+			self.m14.clone(), self.m24.clone(), self.m34.clone()
+		)
+	}
+}
+
+impl<
+	T: Add<T, T> + Mul<T, T>
+>
+Matrix3x4<T> {
+	/// Transforms a point with an implicit w-component of one.
+	/// The fourth column acts as a translation.
+	pub fn transform_point(&self, p: &Vector3<T>) -> Vector3<T> {
+		Vector3 {
+			x: self.m11 * p.x + self.m12 * p.y + self.m13 * p.z + self.m14,
+			y: self.m21 * p.x + self.m22 * p.y + self.m23 * p.z + self.m24,
+			z: self.m31 * p.x + self.m32 * p.y + self.m33 * p.z + self.m34
+		}
+	}
+}
+
+impl<
+	T: Add<T, T>
+>
+Add<Matrix3x4<T>, Matrix3x4<T>> 
+for Matrix3x4<T> {
+	fn add(&self, rhs: &Matrix3x4<T>) -> Matrix3x4<T> {
+		Matrix3x4 {
+			m11: self.m11 + rhs.m11,
+			m12: self.m12 + rhs.m12,
+			m13: self.m13 + rhs.m13,
+			m14: self.m14 + rhs.m14,
+
+			m21: self.m21 + rhs.m21,
+			m22: self.m22 + rhs.m22,
+			m23: self.m23 + rhs.m23,
+			m24: self.m24 + rhs.m24,
+
+			m31: self.m31 + rhs.m31,
+			m32: self.m32 + rhs.m32,
+			m33: self.m33 + rhs.m33,
+			m34: self.m34 + rhs.m34
+		}
+	}
+}
+
+impl<
+	T: Sub<T, T>
+>
+Sub<Matrix3x4<T>, Matrix3x4<T>> 
+for Matrix3x4<T> {
+	fn sub(&self, rhs: &Matrix3x4<T>) -> Matrix3x4<T> {
+		Matrix3x4 {
+			m11: self.m11 - rhs.m11,
+			m12: self.m12 - rhs.m12,
+			m13: self.m13 - rhs.m13,
+			m14: self.m14 - rhs.m14,
+
+			m21: self.m21 - rhs.m21,
+			m22: self.m22 - rhs.m22,
+			m23: self.m23 - rhs.m23,
+			m24: self.m24 - rhs.m24,
+
+			m31: self.m31 - rhs.m31,
+			m32: self.m32 - rhs.m32,
+			m33: self.m33 - rhs.m33,
+			m34: self.m34 - rhs.m34
+		}
+	}
+}
+
+impl<
+	T: Mul<T, T> + Add<T, T>
+>
+Mul<Matrix4x3<T>, Matrix3<T>> 
+for Matrix3x4<T> {
+	fn mul(&self, rhs: &Matrix4x3<T>) -> Matrix3<T> {
+		Matrix3 {
+			m11: self.m11*rhs.m11
+				+self.m12*rhs.m21
+				+self.m13*rhs.m31
+				+self.m14*rhs.m41,
+			m12: self.m11*rhs.m12
+				+self.m12*rhs.m22
+				+self.m13*rhs.m32
+				+self.m14*rhs.m42,
+			m13: self.m11*rhs.m13
+				+self.m12*rhs.m23
+				+self.m13*rhs.m33
+				+self.m14*rhs.m43,
+
+			m21: self.m21*rhs.m11
+				+self.m22*rhs.m21
+				+self.m23*rhs.m31
+				+self.m24*rhs.m41,
+			m22: self.m21*rhs.m12
+				+self.m22*rhs.m22
+				+self.m23*rhs.m32
+				+self.m24*rhs.m42,
+			m23: self.m21*rhs.m13
+				+self.m22*rhs.m23
+				+self.m23*rhs.m33
+				+self.m24*rhs.m43,
+
+			m31: self.m31*rhs.m11
+				+self.m32*rhs.m21
+				+self.m33*rhs.m31
+				+self.m34*rhs.m41,
+			m32: self.m31*rhs.m12
+				+self.m32*rhs.m22
+				+self.m33*rhs.m32
+				+self.m34*rhs.m42,
+			m33: self.m31*rhs.m13
+				+self.m32*rhs.m23
+				+self.m33*rhs.m33
+				+self.m34*rhs.m43
+		}
+	}
+}
+
+impl<
+	T: Mul<T, T> + Add<T, T>
+>
+Matrix3x4<T> {
+	/// Multiplies with a Matrix4 from the right.
+	pub fn mul_matrix4(&self, rhs: &Matrix4<T>) -> Matrix3x4<T> {
+		Matrix3x4 {
+			m11: self.m11*rhs.m11
+				+self.m12*rhs.m21
+				+self.m13*rhs.m31
+				+self.m14*rhs.m41,
+			m12: self.m11*rhs.m12
+				+self.m12*rhs.m22
+				+self.m13*rhs.m32
+				+self.m14*rhs.m42,
+			m13: self.m11*rhs.m13
+				+self.m12*rhs.m23
+				+self.m13*rhs.m33
+				+self.m14*rhs.m43,
+			m14: self.m11*rhs.m14
+				+self.m12*rhs.m24
+				+self.m13*rhs.m34
+				+self.m14*rhs.m44,
+
+			m21: self.m21*rhs.m11
+				+self.m22*rhs.m21
+				+self.m23*rhs.m31
+				+self.m24*rhs.m41,
+			m22: self.m21*rhs.m12
+				+self.m22*rhs.m22
+				+self.m23*rhs.m32
+				+self.m24*rhs.m42,
+			m23: self.m21*rhs.m13
+				+self.m22*rhs.m23
+				+self.m23*rhs.m33
+				+self.m24*rhs.m43,
+			m24: self.m21*rhs.m14
+				+self.m22*rhs.m24
+				+self.m23*rhs.m34
+				+self.m24*rhs.m44,
+
+			m31: self.m31*rhs.m11
+				+self.m32*rhs.m21
+				+self.m33*rhs.m31
+				+self.m34*rhs.m41,
+			m32: self.m31*rhs.m12
+				+self.m32*rhs.m22
+				+self.m33*rhs.m32
+				+self.m34*rhs.m42,
+			m33: self.m31*rhs.m13
+				+self.m32*rhs.m23
+				+self.m33*rhs.m33
+				+self.m34*rhs.m43,
+			m34: self.m31*rhs.m14
+				+self.m32*rhs.m24
+				+self.m33*rhs.m34
+				+self.m34*rhs.m44
+		}
+	}
+}
+
+impl<
+	T: Neg<T>
+>
+Neg<Matrix3x4<T>> 
+for Matrix3x4<T> {
+	fn neg(&self) -> Matrix3x4<T> {
+		Matrix3x4 {
+			m11: -self.m11,
+			m12: -self.m12,
+			m13: -self.m13,
+			m14: -self.m14,
+
+			m21: -self.m21,
+			m22: -self.m22,
+			m23: -self.m23,
+			m24: -self.m24,
+
+			m31: -self.m31,
+			m32: -self.m32,
+			m33: -self.m33,
+			m34: -self.m34
+		}
+	}
+}
+
+impl<
+	T: Add<T, T> + Mul<T, T> + Sub<T, T> + Div<T, T> + Neg<T>
+>
+Div<Matrix4<T>, Matrix3x4<T>> 
+for Matrix3x4<T> {
+	fn div(&self, rhs: &Matrix4<T>) -> Matrix3x4<T> {
+		let inv = rhs.inv();
+		self.mul_matrix4(&inv)
+	}
+}
+
+impl<
+	T: Eps
+>
+Eps 
+for Matrix3x4<T> {
+	fn close_eps(&self, other: &Matrix3x4<T>, eps: f64) -> bool {
+		self.m11.close_eps(&other.m11, eps)
+		&& self.m12.close_eps(&other.m12, eps)
+		&& self.m13.close_eps(&other.m13, eps)
+		&& self.m14.close_eps(&other.m14, eps)
+
+		&& self.m21.close_eps(&other.m21, eps)
+		&& self.m22.close_eps(&other.m22, eps)
+		&& self.m23.close_eps(&other.m23, eps)
+		&& self.m24.close_eps(&other.m24, eps)
+
+		&& self.m31.close_eps(&other.m31, eps)
+		&& self.m32.close_eps(&other.m32, eps)
+		&& self.m33.close_eps(&other.m33, eps)
+		&& self.m34.close_eps(&other.m34, eps)
+	}
+}
+
+impl<
+	T: std::num::Float + Eps
+>
+TryDiv<Matrix4<T>, Matrix3x4<T>> for Matrix3x4<T> {
+	fn try_div(&self, rhs: &Matrix4<T>, eps: f64) -> Option<Matrix3x4<T>> {
+		if is_singular(&rhs.pivot_ratio(), eps) {
+			None
+		} else {
+			Some(self.div(rhs))
+		}
+	}
+}
+
+impl<
+	T: Clone
+>
+Matrix3x4<T> {
+	/// Creates a matrix from the first three rows of a Matrix4.
+	pub fn from_matrix4(m: &Matrix4<T>) -> Matrix3x4<T> {
+		Matrix3x4::new(
+			m.m11.clone(), m.m12.clone(), m.m13.clone(), m.m14.clone(),
+			m.m21.clone(), m.m22.clone(), m.m23.clone(), m.m24.clone(),
+			m.m31.clone(), m.m32.clone(), m.m33.clone(), m.m34.clone()
+		)
+	}
+}
+
+/// A Matrix4x3 has four rows and three columns.
+/// It has the shape of the transpose of a Matrix3x4.
+#[deriving(Eq, Zero)]
+pub struct Matrix4x3<T> {
+	/// Element at first row and first column.
+	m11: T, 
+	/// Element at first row and second column.
+	m12: T, 
+	/// Element at first row and third column.
+	m13: T,
+	
+	/// Element at second row and first column.
+	m21: T, 
+	/// Element at second row and second column.
+	m22: T, 
+	/// Element at second row and third column.
+	m23: T,
+	
+	/// Element at third row and first column.
+	m31: T, 
+	/// Element at third row and second column.
+	m32: T, 
+	/// Element at third row and third column.
+	m33: T,
+	
+	/// Element at fourth row and first column.
+	m41: T, 
+	/// Element at fourth row and second column.
+	m42: T, 
+	/// Element at fourth row and third column.
+	m43: T
+}
+
+/// Creates a new matrix with elements.
+#[inline(always)]
+pub fn matrix4x3<T>(
+	m11: T, m12: T, m13: T,
+	m21: T, m22: T, m23: T,
+	m31: T, m32: T, m33: T,
+	m41: T, m42: T, m43: T
+) -> Matrix4x3<T> {
+	Matrix4x3::new(
+		m11, m12, m13,
+		m21, m22, m23,
+		m31, m32, m33,
+		m41, m42, m43
+	)
+}
+
+impl<T> 
+Matrix4x3<T> {
+	/// Creates a new matrix with elements.
+	pub fn new(
+		m11: T, m12: T, m13: T,
+		m21: T, m22: T, m23: T,
+		m31: T, m32: T, m33: T,
+		m41: T, m42: T, m43: T
+	) -> Matrix4x3<T> {
+		Matrix4x3 {
+			m11: m11, m12: m12, m13: m13,
+			m21: m21, m22: m22, m23: m23,
+			m31: m31, m32: m32, m33: m33,
+			m41: m41, m42: m42, m43: m43
+		}
+	}
+}
+
+impl<T>
+Matrix4x3<T> {
+	/// Returns a reference to the element at a zero-based row and column.
+	pub fn elem<'a>(&'a self, row: uint, col: uint) -> &'a T {
+		match (row, col) {
+			(0, 0) => &self.m11,
+			(0, 1) => &self.m12,
+			(0, 2) => &self.m13,
+			(1, 0) => &self.m21,
+			(1, 1) => &self.m22,
+			(1, 2) => &self.m23,
+			(2, 0) => &self.m31,
+			(2, 1) => &self.m32,
+			(2, 2) => &self.m33,
+			(3, 0) => &self.m41,
+			(3, 1) => &self.m42,
+			(3, 2) => &self.m43,
+			_ => fail!("Matrix4x3 index out of bounds")
+		}
+	}
+
+	/// Returns a mutable reference to the element at a zero-based row and column.
+	pub fn elem_mut<'a>(&'a mut self, row: uint, col: uint) -> &'a mut T {
+		match (row, col) {
+			(0, 0) => &mut self.m11,
+			(0, 1) => &mut self.m12,
+			(0, 2) => &mut self.m13,
+			(1, 0) => &mut self.m21,
+			(1, 1) => &mut self.m22,
+			(1, 2) => &mut self.m23,
+			(2, 0) => &mut self.m31,
+			(2, 1) => &mut self.m32,
+			(2, 2) => &mut self.m33,
+			(3, 0) => &mut self.m41,
+			(3, 1) => &mut self.m42,
+			(3, 2) => &mut self.m43,
+			_ => fail!("Matrix4x3 index out of bounds")
+		}
+	}
+
+	/// Sets the element at a zero-based row and column.
+	pub fn set(&mut self, row: uint, col: uint, val: T) {
+		*self.elem_mut(row, col) = val;
+	}
+
+	/// Destructures into an array of rows.
+	pub fn into_array(self) -> [[T, ..3], ..4] {
+		let Matrix4x3 {
+			m11, m12, m13,
+			m21, m22, m23,
+			m31, m32, m33,
+			m41, m42, m43
+		} = self;
+		[
+			[m11, m12, m13],
+			[m21, m22, m23],
+			[m31, m32, m33],
+			[m41, m42, m43]
+		]
+	}
+}
+
+impl<
+	T: Clone
+>
+Matrix4x3<T> {
+	/// Returns the element at a zero-based row and column.
+	pub fn get(&self, row: uint, col: uint) -> T {
+		self.elem(row, col).clone()
+	}
+
+	/// Returns the elements of a zero-based row.
+	pub fn row(&self, i: uint) -> [T, ..3] {
+		[self.get(i, 0), self.get(i, 1), self.get(i, 2)]
+	}
+
+	/// Returns the elements of a zero-based column.
+	pub fn col(&self, j: uint) -> [T, ..4] {
+		[self.get(0, j), self.get(1, j), self.get(2, j), self.get(3, j)]
+	}
+
+	/// Sets the elements of a zero-based row.
+	pub fn set_row(&mut self, i: uint, row: [T, ..3]) {
+		for j in range(0u, 3) {
+			self.set(i, j, row[j].clone());
+		}
+	}
+
+	/// Sets the elements of a zero-based column.
+	pub fn set_col(&mut self, j: uint, col: [T, ..4]) {
+		for i in range(0u, 4) {
+			self.set(i, j, col[i].clone());
+		}
+	}
+
+	/// Creates a matrix from an array of rows.
+	pub fn from_array(a: [[T, ..3], ..4]) -> Matrix4x3<T> {
+		Matrix4x3::new(
+			a[0][0].clone(), a[0][1].clone(), a[0][2].clone(),
+			a[1][0].clone(), a[1][1].clone(), a[1][2].clone(),
+			a[2][0].clone(), a[2][1].clone(), a[2][2].clone(),
+			a[3][0].clone(), a[3][1].clone(), a[3][2].clone()
+		)
+	}
+
+	/// Returns an array of rows.
+	pub fn to_array(&self) -> [[T, ..3], ..4] {
+		[self.row(0), self.row(1), self.row(2), self.row(3)]
+	}
+
+	/// Returns the transposed matrix, where rows become columns.
+	pub fn transpose(&self) -> Matrix3x4<T> {
+		Matrix3x4::new(
+			self.m11.clone(), self.m21.clone(), self.m31.clone(), self.m41.clone(),
+			self.m12.clone(), self.m22.clone(), self.m32.clone(), self.m42.clone(),
+			self.m13.clone(), self.m23.clone(), self.m33.clone(), self.m43.clone()
+		)
+	}
+}
+
+impl<
+	T: Add<T, T>
+>
+Add<Matrix4x3<T>, Matrix4x3<T>> 
+for Matrix4x3<T> {
+	fn add(&self, rhs: &Matrix4x3<T>) -> Matrix4x3<T> {
+		Matrix4x3 {
+			m11: self.m11 + rhs.m11,
+			m12: self.m12 + rhs.m12,
+			m13: self.m13 + rhs.m13,
+
+			m21: self.m21 + rhs.m21,
+			m22: self.m22 + rhs.m22,
+			m23: self.m23 + rhs.m23,
+
+			m31: self.m31 + rhs.m31,
+			m32: self.m32 + rhs.m32,
+			m33: self.m33 + rhs.m33,
+
+			m41: self.m41 + rhs.m41,
+			m42: self.m42 + rhs.m42,
+			m43: self.m43 + rhs.m43
+		}
+	}
+}
+
+impl<
+	T: Sub<T, T>
+>
+Sub<Matrix4x3<T>, Matrix4x3<T>> 
+for Matrix4x3<T> {
+	fn sub(&self, rhs: &Matrix4x3<T>) -> Matrix4x3<T> {
+		Matrix4x3 {
+			m11: self.m11 - rhs.m11,
+			m12: self.m12 - rhs.m12,
+			m13: self.m13 - rhs.m13,
+
+			m21: self.m21 - rhs.m21,
+			m22: self.m22 - rhs.m22,
+			m23: self.m23 - rhs.m23,
+
+			m31: self.m31 - rhs.m31,
+			m32: self.m32 - rhs.m32,
+			m33: self.m33 - rhs.m33,
+
+			m41: self.m41 - rhs.m41,
+			m42: self.m42 - rhs.m42,
+			m43: self.m43 - rhs.m43
+		}
+	}
+}
+
+impl<
+	T: Mul<T, T> + Add<T, T>
+>
+Mul<Matrix3x4<T>, Matrix4<T>> 
+for Matrix4x3<T> {
+	fn mul(&self, rhs: &Matrix3x4<T>) -> Matrix4<T> {
+		Matrix4 {
+			m11: self.m11*rhs.m11
+				+self.m12*rhs.m21
+				+self.m13*rhs.m31,
+			m12: self.m11*rhs.m12
+				+self.m12*rhs.m22
+				+self.m13*rhs.m32,
+			m13: self.m11*rhs.m13
+				+self.m12*rhs.m23
+				+self.m13*rhs.m33,
+			m14: self.m11*rhs.m14
+				+self.m12*rhs.m24
+				+self.m13*rhs.m34,
+
+			m21: self.m21*rhs.m11
+				+self.m22*rhs.m21
+				+self.m23*rhs.m31,
+			m22: self.m21*rhs.m12
+				+self.m22*rhs.m22
+				+self.m23*rhs.m32,
+			m23: self.m21*rhs.m13
+				+self.m22*rhs.m23
+				+self.m23*rhs.m33,
+			m24: self.m21*rhs.m14
+				+self.m22*rhs.m24
+				+self.m23*rhs.m34,
+
+			m31: self.m31*rhs.m11
+				+self.m32*rhs.m21
+				+self.m33*rhs.m31,
+			m32: self.m31*rhs.m12
+				+self.m32*rhs.m22
+				+self.m33*rhs.m32,
+			m33: self.m31*rhs.m13
+				+self.m32*rhs.m23
+				+self.m33*rhs.m33,
+			m34: self.m31*rhs.m14
+				+self.m32*rhs.m24
+				+self.m33*rhs.m34,
+
+			m41: self.m41*rhs.m11
+				+self.m42*rhs.m21
+				+self.m43*rhs.m31,
+			m42: self.m41*rhs.m12
+				+self.m42*rhs.m22
+				+self.m43*rhs.m32,
+			m43: self.m41*rhs.m13
+				+self.m42*rhs.m23
+				+self.m43*rhs.m33,
+			m44: self.m41*rhs.m14
+				+self.m42*rhs.m24
+				+self.m43*rhs.m34
+		}
+	}
+}
+
+impl<
+	T: Mul<T, T> + Add<T, T>
+>
+Matrix4x3<T> {
+	/// Multiplies with a Matrix3 from the right.
+	pub fn mul_matrix3(&self, rhs: &Matrix3<T>) -> Matrix4x3<T> {
+		Matrix4x3 {
+			m11: self.m11*rhs.m11
+				+self.m12*rhs.m21
+				+self.m13*rhs.m31,
+			m12: self.m11*rhs.m12
+				+self.m12*rhs.m22
+				+self.m13*rhs.m32,
+			m13: self.m11*rhs.m13
+				+self.m12*rhs.m23
+				+self.m13*rhs.m33,
+
+			m21: self.m21*rhs.m11
+				+self.m22*rhs.m21
+				+self.m23*rhs.m31,
+			m22: self.m21*rhs.m12
+				+self.m22*rhs.m22
+				+self.m23*rhs.m32,
+			m23: self.m21*rhs.m13
+				+self.m22*rhs.m23
+				+self.m23*rhs.m33,
+
+			m31: self.m31*rhs.m11
+				+self.m32*rhs.m21
+				+self.m33*rhs.m31,
+			m32: self.m31*rhs.m12
+				+self.m32*rhs.m22
+				+self.m33*rhs.m32,
+			m33: self.m31*rhs.m13
+				+self.m32*rhs.m23
+				+self.m33*rhs.m33,
+
+			m41: self.m41*rhs.m11
+				+self.m42*rhs.m21
+				+self.m43*rhs.m31,
+			m42: self.m41*rhs.m12
+				+self.m42*rhs.m22
+				+self.m43*rhs.m32,
+			m43: self.m41*rhs.m13
+				+self.m42*rhs.m23
+				+self.m43*rhs.m33
+		}
+	}
+}
+
+impl<
+	T: Neg<T>
+>
+Neg<Matrix4x3<T>> 
+for Matrix4x3<T> {
+	fn neg(&self) -> Matrix4x3<T> {
+		Matrix4x3 {
+			m11: -self.m11,
+			m12: -self.m12,
+			m13: -self.m13,
+
+			m21: -self.m21,
+			m22: -self.m22,
+			m23: -self.m23,
+
+			m31: -self.m31,
+			m32: -self.m32,
+			m33: -self.m33,
+
+			m41: -self.m41,
+			m42: -self.m42,
+			m43: -self.m43
+		}
+	}
+}
+
+impl<
+	T: Add<T, T> + Mul<T, T> + Sub<T, T> + Div<T, T> + Neg<T>
+>
+Div<Matrix3<T>, Matrix4x3<T>> 
+for Matrix4x3<T> {
+	fn div(&self, rhs: &Matrix3<T>) -> Matrix4x3<T> {
+		let inv = rhs.inv();
+		self.mul_matrix3(&inv)
+	}
+}
+
+impl<
+	T: Eps
+>
+Eps 
+for Matrix4x3<T> {
+	fn close_eps(&self, other: &Matrix4x3<T>, eps: f64) -> bool {
+		self.m11.close_eps(&other.m11, eps)
+		&& self.m12.close_eps(&other.m12, eps)
+		&& self.m13.close_eps(&other.m13, eps)
+
+		&& self.m21.close_eps(&other.m21, eps)
+		&& self.m22.close_eps(&other.m22, eps)
+		&& self.m23.close_eps(&other.m23, eps)
+
+		&& self.m31.close_eps(&other.m31, eps)
+		&& self.m32.close_eps(&other.m32, eps)
+		&& self.m33.close_eps(&other.m33, eps)
+
+		&& self.m41.close_eps(&other.m41, eps)
+		&& self.m42.close_eps(&other.m42, eps)
+		&& self.m43.close_eps(&other.m43, eps)
+	}
+}
+
+impl<
+	T: std::num::Float + Eps
+>
+TryDiv<Matrix3<T>, Matrix4x3<T>> for Matrix4x3<T> {
+	fn try_div(&self, rhs: &Matrix3<T>, eps: f64) -> Option<Matrix4x3<T>> {
+		if is_singular(&rhs.pivot_ratio(), eps) {
+			None
+		} else {
+			Some(self.div(rhs))
+		}
+	}
+}
+
+impl<
+	T: Clone
+>
+Matrix4x3<T> {
+	/// Creates a matrix from the first three columns of a Matrix4.
+	pub fn from_matrix4(m: &Matrix4<T>) -> Matrix4x3<T> {
+		Matrix4x3::new(
+			m.m11.clone(), m.m12.clone(), m.m13.clone(),
+			m.m21.clone(), m.m22.clone(), m.m23.clone(),
+			m.m31.clone(), m.m32.clone(), m.m33.clone(),
+			m.m41.clone(), m.m42.clone(), m.m43.clone()
+		)
+	}
+}
+
+impl<
+	T: std::num::Zero + std::num::One + Clone
+>
+Matrix4<T> {
+	/// Creates a matrix with a Matrix3 in the upper left part
+	/// and the rest from the identity matrix.
+	pub fn from_matrix3(m: &Matrix3<T>) -> Matrix4<T> {
+		Matrix4::new(
+			m.m11.clone(), m.m12.clone(), m.m13.clone(), std::num::zero(),
+			m.m21.clone(), m.m22.clone(), m.m23.clone(), std::num::zero(),
+			m.m31.clone(), m.m32.clone(), m.m33.clone(), std::num::zero(),
+			std::num::zero(), std::num::zero(), std::num::zero(), std::num::one()
+		)
+	}
+
+	/// Creates a matrix from a Matrix3x4
+	/// with the last row from the identity matrix.
+	pub fn from_matrix3x4(m: &Matrix3x4<T>) -> Matrix4<T> {
+		Matrix4::new(
+			m.m11.clone(), m.m12.clone(), m.m13.clone(), m.m14.clone(),
+			m.m21.clone(), m.m22.clone(), m.m23.clone(), m.m24.clone(),
+			m.m31.clone(), m.m32.clone(), m.m33.clone(), m.m34.clone(),
+			std::num::zero(), std::num::zero(), std::num::zero(), std::num::one()
+		)
+	}
+
+	/// Creates a matrix from a Matrix4x3
+	/// with the last column from the identity matrix.
+	pub fn from_matrix4x3(m: &Matrix4x3<T>) -> Matrix4<T> {
+		Matrix4::new(
+			m.m11.clone(), m.m12.clone(), m.m13.clone(), std::num::zero(),
+			m.m21.clone(), m.m22.clone(), m.m23.clone(), std::num::zero(),
+			m.m31.clone(), m.m32.clone(), m.m33.clone(), std::num::zero(),
+			m.m41.clone(), m.m42.clone(), m.m43.clone(), std::num::one()
+		)
+	}
+}
+
+/// A Vector3 is a vector with three components.
+/// It is commonly used for points and directions in 3D.
+#[deriving(Eq, Zero)]
+pub struct Vector3<T> {
+	/// The x-component.
+	x: T,
+	/// The y-component.
+	y: T,
+	/// The z-component.
+	z: T,
+}
+
+/// Creates a new Vector3.
+#[inline(always)]
+pub fn vector3<T>(x: T, y: T, z: T) -> Vector3<T> {
+	Vector3::new(x, y, z)
+}
+
+impl<T>
+Vector3<T> {
+	/// Creates a new Vector3.
+	pub fn new(x: T, y: T, z: T) -> Vector3<T> {
+		Vector3 {
+			x: x,
+			y: y,
+			z: z
+		}
+	}
+
+	/// Creates a vector from a tuple.
+	pub fn from_tuple((x, y, z): (T, T, T)) -> Vector3<T> {
+		Vector3::new(x, y, z)
+	}
+
+	/// Destructures into a tuple.
+	pub fn into_tuple(self) -> (T, T, T) {
+		let Vector3 { x, y, z } = self;
+		(x, y, z)
+	}
+}
+
+impl<
+	T: Clone
+>
+Vector3<T> {
+	/// Returns the x-component.
+	pub fn x(&self) -> T {
+		self.x.clone()
+	}
+
+	/// Returns the y-component.
+	pub fn y(&self) -> T {
+		self.y.clone()
+	}
+
+	/// Returns the z-component.
+	pub fn z(&self) -> T {
+		self.z.clone()
+	}
+
+	/// Returns a tuple of the components.
+	pub fn to_tuple(&self) -> (T, T, T) {
+		(self.x.clone(), self.y.clone(), self.z.clone())
+	}
+}
+
+impl<
+	T: Add<T, T>
+>
+Add<Vector3<T>, Vector3<T>> 
+for Vector3<T> {
+	fn add(&self, rhs: &Vector3<T>) -> Vector3<T> {
+		Vector3 {
+			x: self.x + rhs.x,
+			y: self.y + rhs.y,
+			z: self.z + rhs.z
+		}
+	}
+}
+
+impl<
+	T: Sub<T, T>
+>
+Sub<Vector3<T>, Vector3<T>> 
+for Vector3<T> {
+	fn sub(&self, rhs: &Vector3<T>) -> Vector3<T> {
+		Vector3 {
+			x: self.x - rhs.x,
+			y: self.y - rhs.y,
+			z: self.z - rhs.z
+		}
+	}
+}
+
+impl<
+	T: Mul<T, T>
+>
+Mul<Vector3<T>, Vector3<T>> 
+for Vector3<T> {
+	fn mul(&self, rhs: &Vector3<T>) -> Vector3<T> {
+		Vector3 {
+			x: self.x * rhs.x,
+			y: self.y * rhs.y,
+			z: self.z * rhs.z
+		}
+	}
+}
+
+impl<
+	T: Neg<T>
+>
+Neg<Vector3<T>> 
+for Vector3<T> {
+	fn neg(&self) -> Vector3<T> {
+		Vector3 {
+			x: -self.x,
+			y: -self.y,
+			z: -self.z
+		}
+	}
+}
+
+impl<
+	T: Add<T, T> + Mul<T, T>
+>
+NormSq<T> 
+for Vector3<T> {
+	fn norm_sq(&self) -> T {
+		self.x * self.x
+		+ self.y * self.y
+		+ self.z * self.z
+	}
+}
+
+impl<
+	T: Eps
+>
+Eps 
+for Vector3<T> {
+	fn close_eps(&self, other: &Vector3<T>, eps: f64) -> bool {
+		self.x.close_eps(&other.x, eps)
+		&& self.y.close_eps(&other.y, eps)
+		&& self.z.close_eps(&other.z, eps)
+	}
+}
+
+impl<
+	T: Add<T, T> + Sub<T, T> + Mul<T, T>
+>
+Vector3<T> {
+	/// Computes the dot product.
+	pub fn dot(&self, other: &Vector3<T>) -> T {
+		self.x * other.x + self.y * other.y + self.z * other.z
+	}
+
+	/// Computes the cross product.
+	pub fn cross(&self, other: &Vector3<T>) -> Vector3<T> {
+		Vector3 {
+			x: self.y * other.z - self.z * other.y,
+			y: self.z * other.x - self.x * other.z,
+			z: self.x * other.y - self.y * other.x
+		}
+	}
+}
+
+/// A Vector4 is a vector with four components.
+/// It is commonly used for homogeneous coordinates in 3D.
+#[deriving(Eq, Zero)]
+pub struct Vector4<T> {
+	/// The x-component.
+	x: T,
+	/// The y-component.
+	y: T,
+	/// The z-component.
+	z: T,
+	/// The w-component.
+	w: T,
+}
+
+/// Creates a new Vector4.
+#[inline(always)]
+pub fn vector4<T>(x: T, y: T, z: T, w: T) -> Vector4<T> {
+	Vector4::new(x, y, z, w)
+}
+
+impl<T>
+Vector4<T> {
+	/// Creates a new Vector4.
+	pub fn new(x: T, y: T, z: T, w: T) -> Vector4<T> {
+		Vector4 {
+			x: x,
+			y: y,
+			z: z,
+			w: w
+		}
+	}
+
+	/// Creates a vector from a tuple.
+	pub fn from_tuple((x, y, z, w): (T, T, T, T)) -> Vector4<T> {
+		Vector4::new(x, y, z, w)
+	}
+
+	/// Destructures into a tuple.
+	pub fn into_tuple(self) -> (T, T, T, T) {
+		let Vector4 { x, y, z, w } = self;
+		(x, y, z, w)
+	}
+}
+
+impl<
+	T: Clone
+>
+Vector4<T> {
+	/// Returns the x-component.
+	pub fn x(&self) -> T {
+		self.x.clone()
+	}
+
+	/// Returns the y-component.
+	pub fn y(&self) -> T {
+		self.y.clone()
+	}
+
+	/// Returns the z-component.
+	pub fn z(&self) -> T {
+		self.z.clone()
+	}
+
+	/// Returns the w-component.
+	pub fn w(&self) -> T {
+		self.w.clone()
+	}
+
+	/// Returns a tuple of the components.
+	pub fn to_tuple(&self) -> (T, T, T, T) {
+		(self.x.clone(), self.y.clone(), self.z.clone(), self.w.clone())
+	}
+}
+
+impl<
+	T: Add<T, T>
+>
+Add<Vector4<T>, Vector4<T>> 
+for Vector4<T> {
+	fn add(&self, rhs: &Vector4<T>) -> Vector4<T> {
+		Vector4 {
+			x: self.x + rhs.x,
+			y: self.y + rhs.y,
+			z: self.z + rhs.z,
+			w: self.w + rhs.w
+		}
+	}
+}
+
+impl<
+	T: Sub<T, T>
+>
+Sub<Vector4<T>, Vector4<T>> 
+for Vector4<T> {
+	fn sub(&self, rhs: &Vector4<T>) -> Vector4<T> {
+		Vector4 {
+			x: self.x - rhs.x,
+			y: self.y - rhs.y,
+			z: self.z - rhs.z,
+			w: self.w - rhs.w
+		}
+	}
+}
+
+impl<
+	T: Mul<T, T>
+>
+Mul<Vector4<T>, Vector4<T>> 
+for Vector4<T> {
+	fn mul(&self, rhs: &Vector4<T>) -> Vector4<T> {
+		Vector4 {
+			x: self.x * rhs.x,
+			y: self.y * rhs.y,
+			z: self.z * rhs.z,
+			w: self.w * rhs.w
+		}
+	}
+}
+
+impl<
+	T: Neg<T>
+>
+Neg<Vector4<T>> 
+for Vector4<T> {
+	fn neg(&self) -> Vector4<T> {
+		Vector4 {
+			x: -self.x,
+			y: -self.y,
+			z: -self.z,
+			w: -self.w
+		}
+	}
+}
+
+impl<
+	T: Add<T, T> + Mul<T, T>
+>
+NormSq<T> 
+for Vector4<T> {
+	fn norm_sq(&self) -> T {
+		self.x * self.x
+		+ self.y * self.y
+		+ self.z * self.z
+		+ self.w * self.w
+	}
+}
+
+impl<
+	T: Eps
+>
+Eps 
+for Vector4<T> {
+	fn close_eps(&self, other: &Vector4<T>, eps: f64) -> bool {
+		self.x.close_eps(&other.x, eps)
+		&& self.y.close_eps(&other.y, eps)
+		&& self.z.close_eps(&other.z, eps)
+		&& self.w.close_eps(&other.w, eps)
+	}
+}
+
+impl<
+	T: Add<T, T> + Mul<T, T>
+>
+Vector4<T> {
+	/// Computes the dot product.
+	pub fn dot(&self, other: &Vector4<T>) -> T {
+		self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+	}
+}
+
+impl<
+	T: Clone
+>
+Vector4<T> {
+	/// Creates a homogeneous vector from a 3D vector and a w-component.
+	/// Use one for points and zero for directions.
+	pub fn from_vector3(v: &Vector3<T>, w: T) -> Vector4<T> {
+		Vector4 {
+			x: v.x.clone(),
+			y: v.y.clone(),
+			z: v.z.clone(),
+			w: w
+		}
+	}
+
+	/// Returns the x, y and z components without perspective divide.
+	pub fn xyz(&self) -> Vector3<T> {
+		Vector3 {
+			x: self.x.clone(),
+			y: self.y.clone(),
+			z: self.z.clone()
+		}
+	}
+}
+
+/// A Vector type contains a list of values.
+/// It is commonly used for list operations.
+#[deriving(Eq, Clone)]
+pub struct Vector<T> {
+	/// Contains the items in the vector.
+	x: ~[T],
+}
+
+/// Creates a new vector from a list of values.
+#[inline(always)]
+pub fn vector<T>(x: ~[T]) -> Vector<T> {
+	Vector::new(x)
+}
+
+impl<T> 
+Vector<T> {
+	/// Creates a new vector from a list of values.
+	pub fn new(x: ~[T]) -> Vector<T> {
+		Vector {
+			x: x 
+		}
+	}
+}
+
+impl<T>
+Vector<T> {
+	/// Returns the number of items.
+	pub fn len(&self) -> uint {
+		self.x.len()
+	}
+
+	/// Returns the items as a slice.
+	pub fn as_slice<'a>(&'a self) -> &'a [T] {
+		self.x.slice(0, self.x.len())
+	}
+
+	/// Sets the item at an index.
+	pub fn set(&mut self, i: uint, val: T) {
+		self.x[i] = val;
+	}
+
+	/// Destructures into the list of items.
+	pub fn into_vec(self) -> ~[T] {
+		let Vector { x } = self;
+		x
+	}
+}
+
+impl<
+	T: Clone
+>
+Vector<T> {
+	/// Returns the item at an index.
+	pub fn get(&self, i: uint) -> T {
+		self.x[i].clone()
+	}
+
+	/// Returns a copy of the list of items.
+	pub fn to_vec(&self) -> ~[T] {
+		self.x.clone()
+	}
+}
+
+impl<
+	T: Add<T, T>
+>
+Add<Vector<T>, Vector<T>> 
+for Vector<T> {
+	fn add(&self, rhs: &Vector<T>) -> Vector<T> {
+		if self.x.len() != rhs.x.len() {
+			Vector { 
+				x: ~[] 
+			}
+		} else {
+			let mut res: ~[T] = ~[];
+			let mut i = 0;
+			let n = self.x.len();
+			while i < n {
+				res.push(self.x[i] + rhs.x[i]);
+				i += 1;
+			}
+
+			Vector { 
+				x: res 
+			}
+		}
+	}
+}
+
+impl<
+	T: Sub<T, T>
+>
+Sub<Vector<T>, Vector<T>> 
+for Vector<T> {
+	fn sub(&self, rhs: &Vector<T>) -> Vector<T> {
+		if self.x.len() != rhs.x.len() {
+			Vector { 
+				x: ~[] 
+			}
+		} else {
+			let mut res: ~[T] = ~[];
+			let mut i = 0;
+			let n = self.x.len();
+			while i < n {
+				res.push(self.x[i] - rhs.x[i]);
+				i += 1;
+			}
+
+			Vector { 
+				x: res 
+			}
+		}
+	}
+}
+
+impl<
+	T: Mul<T, T>
+>
+Mul<Vector<T>, Vector<T>> 
+for Vector<T> {
+	fn mul(&self, rhs: &Vector<T>) -> Vector<T> {
+		if self.x.len() != rhs.x.len() {
+			Vector { 
+				x: ~[] 
+			}
+		} else {
+			let mut res: ~[T] = ~[];
+			let mut i = 0;
+			let n = self.x.len();
+			while i < n {
+				res.push(self.x[i] * rhs.x[i]);
+				i += 1;
+			}
+
+			Vector { 
+				x: res 
+			}
+		}
+	}
+}
+
+impl<
+	T: Div<T, T>
+>
+Div<Vector<T>, Vector<T>> 
+for Vector<T> {
+	fn div(&self, rhs: &Vector<T>) -> Vector<T> {
+		if self.x.len() != rhs.x.len() {
+			Vector { 
+				x: ~[] 
+			}
+		} else {
+			let mut res: ~[T] = ~[];
+			let mut i = 0;
+			let n = self.x.len();
+			while i < n {
+				res.push(self.x[i] / rhs.x[i]);
+				i += 1;
+			}
+
+			Vector { 
+				x: res 
+			}
+		}
+	}
+}
+
+impl<
+	T: Inv<T>
+>
+Inv<Vector<T>> 
+for Vector<T> {
+	fn inv(&self) -> Vector<T> {
+		let mut res: ~[T] = ~[];
+		for i in range(0, self.x.len()) {
+			res.push(self.x[i].inv());
+		}
+
+		Vector { 
+			x: res 
+		}
+	}
+}
+
+impl<
+	T: Neg<T>
+>
+Neg<Vector<T>> 
+for Vector<T> {
+	fn neg(&self) -> Vector<T> {
+		let mut res: ~[T] = ~[];
+		for x in self.x.iter() {
+			res.push(-x);
+		}
+
+		Vector { 
+			x: res 
+		}
+	}
+}
+
+impl<
+	T: Add<T, T> + Mul<T, T> + std::num::Zero
+>
+NormSq<T> 
+for Vector<T> {
+	fn norm_sq(&self) -> T {
+		let mut res: T = std::num::zero();
+		for x in self.x.iter() {
+			res = res + *x * *x;
+		}
+
+		res
+	}
+}
+
+impl<
+	T: Eps
+> 
+Eps 
+for Vector<T> {
+	fn close_eps(&self, other: &Vector<T>, eps: f64) -> bool {
+		let n = self.x.len();
+		let m = other.x.len();
+		if n != m { fail!("Vectors not of same length"); }
+
+		if range(0, n).any(
+			|i| !self.x[i].close_eps(&other.x[i], eps)
+		) {
+			false
+		} else {
+			true
+		}
+	}
+}
+
+impl<
+	T: TryInv<T>
+>
+TryInv<Vector<T>> for Vector<T> {
+	fn try_inv(&self, eps: f64) -> Option<Vector<T>> {
+		let mut res: ~[T] = ~[];
+		for x in self.x.iter() {
+			match x.try_inv(eps) {
+				Some(y) => res.push(y),
+				None => return None
+			}
+		}
+
+		Some(Vector {
+			x: res
+		})
+	}
+}
+
+impl<
+	T: Div<T, T> + Eps + std::num::Zero
+>
+TryDiv<Vector<T>, Vector<T>> for Vector<T> {
+	fn try_div(&self, rhs: &Vector<T>, eps: f64) -> Option<Vector<T>> {
+		if rhs.x.iter().any(|x| is_singular(x, eps)) {
+			None
+		} else {
+			Some(self.div(rhs))
+		}
+	}
+}
+
+/// A Matrix type has dimensions that are known at runtime.
+/// The elements are stored row by row in a contiguous list.
+#[deriving(Eq, Clone)]
+pub struct Matrix<T> {
+	/// The number of rows.
+	rows: uint,
+	/// The number of columns.
+	cols: uint,
+	/// Contains the elements row by row.
+	x: ~[T],
+}
+
+/// Creates a new matrix from a list of elements stored row by row.
+#[inline(always)]
+pub fn matrix<T>(rows: uint, cols: uint, x: ~[T]) -> Matrix<T> {
+	Matrix::new(rows, cols, x)
+}
+
+impl<T>
+Matrix<T> {
+	/// Creates a new matrix from a list of elements stored row by row.
+	/// Fails if the number of elements does not match the dimensions.
+	pub fn new(rows: uint, cols: uint, x: ~[T]) -> Matrix<T> {
+		if x.len() != rows * cols {
+			fail!("Matrix elements do not match dimensions");
+		}
+
+		Matrix {
+			rows: rows,
+			cols: cols,
+			x: x
+		}
+	}
+
+	/// Creates a new matrix by calling a function with zero-based row and column.
+	pub fn from_fn(rows: uint, cols: uint, f: |uint, uint| -> T) -> Matrix<T> {
+		Matrix {
+			rows: rows,
+			cols: cols,
+			x: std::vec::from_fn(rows * cols, |k| f(k / cols, k % cols))
+		}
+	}
+
+	/// Returns the number of rows.
+	pub fn rows(&self) -> uint {
+		self.rows
+	}
+
+	/// Returns the number of columns.
+	pub fn cols(&self) -> uint {
+		self.cols
+	}
+
+	/// Returns the number of rows and columns.
+	pub fn shape(&self) -> (uint, uint) {
+		(self.rows, self.cols)
+	}
+
+	/// Returns true if the number of rows equals the number of columns.
+	pub fn is_square(&self) -> bool {
+		self.rows == self.cols
+	}
+
+	/// Returns the index in the list of elements.
+	fn index(&self, row: uint, col: uint) -> uint {
+		if row >= self.rows || col >= self.cols {
+			fail!("Matrix index out of bounds");
+		}
+
+		row * self.cols + col
+	}
+
+	/// Fails if the matrices do not have the same shape.
+	fn check_shape(&self, other: &Matrix<T>) {
+		if self.rows != other.rows || self.cols != other.cols {
+			fail!("Matrices not of same shape");
+		}
+	}
+
+	/// Fails if the matrix is not square.
+	fn check_square(&self) {
+		if !self.is_square() {
+			fail!("Matrix is not square");
+		}
+	}
+
+	/// Returns a reference to the element at a zero-based row and column.
+	pub fn elem<'a>(&'a self, row: uint, col: uint) -> &'a T {
+		&self.x[self.index(row, col)]
+	}
+
+	/// Returns a mutable reference to the element at a zero-based row and column.
+	pub fn elem_mut<'a>(&'a mut self, row: uint, col: uint) -> &'a mut T {
+		let i = self.index(row, col);
+		&mut self.x[i]
+	}
+
+	/// Sets the element at a zero-based row and column.
+	pub fn set(&mut self, row: uint, col: uint, val: T) {
+		*self.elem_mut(row, col) = val;
+	}
+
+	/// Returns a view of a zero-based row.
+	pub fn row_slice<'a>(&'a self, i: uint) -> &'a [T] {
+		if i >= self.rows { fail!("Matrix index out of bounds"); }
+		self.x.slice(i * self.cols, (i + 1) * self.cols)
+	}
+
+	/// Returns a mutable view of a zero-based row.
+	pub fn row_slice_mut<'a>(&'a mut self, i: uint) -> &'a mut [T] {
+		if i >= self.rows { fail!("Matrix index out of bounds"); }
+		let cols = self.cols;
+		self.x.mut_slice(i * cols, (i + 1) * cols)
+	}
+
+	/// Returns the elements as a slice, stored row by row.
+	pub fn as_slice<'a>(&'a self) -> &'a [T] {
+		self.x.slice(0, self.x.len())
+	}
+
+	/// Destructures into the list of elements, stored row by row.
+	pub fn into_vec(self) -> ~[T] {
+		let Matrix { x, .. } = self;
+		x
+	}
+
+	/// Swaps two zero-based rows.
+	pub fn swap_rows(&mut self, i: uint, j: uint) {
+		if i >= self.rows || j >= self.rows { fail!("Matrix index out of bounds"); }
+		if i == j { return; }
+		for k in range(0, self.cols) {
+			self.x.swap(i * self.cols + k, j * self.cols + k);
+		}
+	}
+
+	/// Creates a new matrix by applying a function to each element.
+	fn map<U>(&self, f: |&T| -> U) -> Matrix<U> {
+		Matrix {
+			rows: self.rows,
+			cols: self.cols,
+			x: self.x.iter().map(f).collect()
+		}
+	}
+
+	/// Creates a new matrix by applying a function to pairs of elements.
+	/// Fails if the matrices do not have the same shape.
+	fn zip_with<U>(&self, rhs: &Matrix<T>, f: |&T, &T| -> U) -> Matrix<U> {
+		self.check_shape(rhs);
+		Matrix {
+			rows: self.rows,
+			cols: self.cols,
+			x: self.x.iter().zip(rhs.x.iter()).map(|(a, b)| f(a, b)).collect()
+		}
+	}
+}
+
+impl<
+	T: Clone
+>
+Matrix<T> {
+	/// Creates a new matrix with all elements set to a value.
+	pub fn from_elem(rows: uint, cols: uint, val: T) -> Matrix<T> {
+		Matrix {
+			rows: rows,
+			cols: cols,
+			x: std::vec::from_elem(rows * cols, val)
+		}
+	}
+
+	/// Creates a new matrix from a list of rows.
+	/// Fails if the rows are not of same length.
+	pub fn from_rows(rows: &[Vector<T>]) -> Matrix<T> {
+		let cols = if rows.len() == 0 { 0 } else { rows[0].len() };
+		let mut x: ~[T] = ~[];
+		for row in rows.iter() {
+			if row.len() != cols { fail!("Matrix rows not of same length"); }
+			x.push_all(row.as_slice());
+		}
+
+		Matrix {
+			rows: rows.len(),
+			cols: cols,
+			x: x
+		}
+	}
+
+	/// Creates a new matrix from a Matrix4.
+	pub fn from_matrix4(m: &Matrix4<T>) -> Matrix<T> {
+		Matrix::from_fn(4, 4, |i, j| m.get(i, j))
+	}
+
+	/// Converts to a Matrix4.
+	/// Fails if the matrix is not 4x4.
+	pub fn to_matrix4(&self) -> Matrix4<T> {
+		if self.rows != 4 || self.cols != 4 { fail!("Matrix is not 4x4"); }
+		Matrix4::from_array([
+			[self.get(0, 0), self.get(0, 1), self.get(0, 2), self.get(0, 3)],
+			[self.get(1, 0), self.get(1, 1), self.get(1, 2), self.get(1, 3)],
+			[self.get(2, 0), self.get(2, 1), self.get(2, 2), self.get(2, 3)],
+			[self.get(3, 0), self.get(3, 1), self.get(3, 2), self.get(3, 3)]
+		])
+	}
+
+	/// Returns the element at a zero-based row and column.
+	pub fn get(&self, row: uint, col: uint) -> T {
+		self.elem(row, col).clone()
+	}
+
+	/// Returns the elements of a zero-based row.
+	pub fn row(&self, i: uint) -> Vector<T> {
+		Vector {
+			x: self.row_slice(i).to_owned()
+		}
+	}
+
+	/// Returns the elements of a zero-based column.
+	pub fn col(&self, j: uint) -> Vector<T> {
+		Vector {
+			x: std::vec::from_fn(self.rows, |i| self.get(i, j))
+		}
+	}
+
+	/// Sets the elements of a zero-based row.
+	/// Fails if the length does not match the number of columns.
+	pub fn set_row(&mut self, i: uint, row: &Vector<T>) {
+		if row.len() != self.cols { fail!("Row length does not match matrix"); }
+		for j in range(0, self.cols) {
+			self.set(i, j, row.x[j].clone());
+		}
+	}
+
+	/// Sets the elements of a zero-based column.
+	/// Fails if the length does not match the number of rows.
+	pub fn set_col(&mut self, j: uint, col: &Vector<T>) {
+		if col.len() != self.rows { fail!("Column length does not match matrix"); }
+		for i in range(0, self.rows) {
+			self.set(i, j, col.x[i].clone());
+		}
+	}
+
+	/// Returns a copy of the elements, stored row by row.
+	pub fn to_vec(&self) -> ~[T] {
+		self.x.clone()
+	}
+
+	/// Returns the transposed matrix, where rows become columns.
+	pub fn transpose(&self) -> Matrix<T> {
+		Matrix::from_fn(self.cols, self.rows, |i, j| self.get(j, i))
+	}
+}
+
+impl<
+	T: std::num::Zero
+>
+Matrix<T> {
+	/// Creates a new matrix with all elements set to zero.
+	pub fn zeros(rows: uint, cols: uint) -> Matrix<T> {
+		Matrix::from_fn(rows, cols, |_, _| std::num::zero())
+	}
+}
+
+impl<
+	T: std::num::Zero + std::num::One
+>
+Matrix<T> {
+	/// Creates a new identity matrix with `n` rows and columns.
+	pub fn identity(n: uint) -> Matrix<T> {
+		Matrix::from_fn(n, n, |i, j| if i == j {
+			std::num::one()
+		} else {
+			std::num::zero()
+		})
+	}
+}
+
+impl<
+	T: Add<T, T> + Mul<T, T> + std::num::Zero
+>
+Matrix<T> {
+	/// Computes the sum of the diagonal elements.
+	/// Fails if the matrix is not square.
+	pub fn trace(&self) -> T {
+		self.check_square();
+		let mut res: T = std::num::zero();
+		for i in range(0, self.rows) {
+			res = res + *self.elem(i, i);
+		}
+
+		res
+	}
+
+	/// Multiplies a vector by the matrix.
+	/// Fails if the length does not match the number of columns.
+	pub fn transform(&self, v: &Vector<T>) -> Vector<T> {
+		if v.len() != self.cols { fail!("Vector length does not match matrix"); }
+		Vector {
+			x: std::vec::from_fn(self.rows, |i| {
+				let mut res: T = std::num::zero();
+				for (a, b) in self.row_slice(i).iter().zip(v.x.iter()) {
+					res = res + *a * *b;
+				}
+				res
+			})
+		}
+	}
+}
+
+impl<
+	T: Mul<T, T>
+>
+Matrix<T> {
+	/// Multiplies element by element.
+	/// Fails if the matrices do not have the same shape.
+	pub fn mul_elem(&self, rhs: &Matrix<T>) -> Matrix<T> {
+		self.zip_with(rhs, |a, b| *a * *b)
+	}
+}
+
+impl<
+	T: Div<T, T>
+>
+Matrix<T> {
+	/// Divides element by element.
+	/// Fails if the matrices do not have the same shape.
+	pub fn div_elem(&self, rhs: &Matrix<T>) -> Matrix<T> {
+		self.zip_with(rhs, |a, b| *a / *b)
+	}
+}
+
+impl<
+	T: Add<T, T>
+>
+Add<Matrix<T>, Matrix<T>> 
+for Matrix<T> {
+	fn add(&self, rhs: &Matrix<T>) -> Matrix<T> {
+		self.zip_with(rhs, |a, b| *a + *b)
+	}
+}
+
+impl<
+	T: Sub<T, T>
+>
+Sub<Matrix<T>, Matrix<T>> 
+for Matrix<T> {
+	fn sub(&self, rhs: &Matrix<T>) -> Matrix<T> {
+		self.zip_with(rhs, |a, b| *a - *b)
+	}
+}
+
+impl<
+	T: Add<T, T> + Mul<T, T> + std::num::Zero
+>
+Mul<Matrix<T>, Matrix<T>> 
+for Matrix<T> {
+	fn mul(&self, rhs: &Matrix<T>) -> Matrix<T> {
+		if self.cols != rhs.rows { fail!("Matrix shapes do not match for product"); }
+		let n = self.cols;
+		Matrix::from_fn(self.rows, rhs.cols, |i, j| {
+			let mut res: T = std::num::zero();
+			for k in range(0, n) {
+				res = res + self.x[i * n + k] * rhs.x[k * rhs.cols + j];
+			}
+			res
+		})
+	}
+}
+
+impl<
+	T: Neg<T>
+>
+Neg<Matrix<T>> 
+for Matrix<T> {
+	fn neg(&self) -> Matrix<T> {
+		self.map(|x| -x)
+	}
+}
+
+/// Returns the row at or below `k` with the largest absolute value in column `k`.
+/// The matrix is square with `n` rows, stored row by row.
+fn matrix_pivot_row<T: std::num::Float>(a: &[T], n: uint, k: uint) -> uint {
+	let mut p = k;
+	for i in range(k + 1, n) {
+		if a[i * n + k].abs() > a[p * n + k].abs() {
+			p = i;
+		}
+	}
+
+	p
+}
+
+impl<
+	T: std::num::Float
+>
+Det<T> 
+for Matrix<T> {
+	/// Uses Gaussian elimination with partial pivoting.
+	/// Fails if the matrix is not square.
+	fn det(&self) -> T {
+		self.lu().det()
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+Inv<Matrix<T>> 
+for Matrix<T> {
+	/// Uses Gaussian elimination with partial pivoting.
+	/// Fails if the matrix is not square.
+	fn inv(&self) -> Matrix<T> {
+		self.lu().inv()
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+Div<Matrix<T>, Matrix<T>> 
+for Matrix<T> {
+	fn div(&self, rhs: &Matrix<T>) -> Matrix<T> {
+		let inv = rhs.inv();
+		self * inv
+	}
+}
+
+impl<
+	T: Add<T, T> + Mul<T, T> + std::num::Zero
+>
+NormSq<T> 
+for Matrix<T> {
+	/// Computes the square of the Frobenius norm.
+	fn norm_sq(&self) -> T {
+		let mut res: T = std::num::zero();
+		for x in self.x.iter() {
+			res = res + *x * *x;
+		}
+
+		res
+	}
+}
+
+impl<
+	T: Eps
+> 
+Eps 
+for Matrix<T> {
+	fn close_eps(&self, other: &Matrix<T>, eps: f64) -> bool {
+		self.check_shape(other);
+		range(0, self.x.len()).all(
+			|i| self.x[i].close_eps(&other.x[i], eps)
+		)
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+Matrix<T> {
+	/// Computes the smallest absolute pivot relative to the largest.
+	/// Fails if the matrix is not square.
+	fn pivot_ratio(&self) -> T {
+		self.check_square();
+		pivot_ratio(self.x.clone(), self.rows)
+	}
+}
+
+impl<
+	T: std::num::Float + Eps
+>
+TryInv<Matrix<T>> for Matrix<T> {
+	fn try_inv(&self, eps: f64) -> Option<Matrix<T>> {
+		if is_singular(&self.pivot_ratio(), eps) {
+			None
+		} else {
+			Some(self.inv())
+		}
+	}
+}
+
+impl<
+	T: std::num::Float + Eps
+>
+TryDiv<Matrix<T>, Matrix<T>> for Matrix<T> {
+	fn try_div(&self, rhs: &Matrix<T>, eps: f64) -> Option<Matrix<T>> {
+		if is_singular(&rhs.pivot_ratio(), eps) {
+			None
+		} else {
+			Some(self.div(rhs))
+		}
+	}
+}
+
+/// An LU decomposition with partial pivoting.
+/// The rows of the matrix are permuted such that `P A = L U`,
+/// where `L` is lower triangular with ones on the diagonal
+/// and `U` is upper triangular.
+#[deriving(Clone)]
+pub struct LU<T> {
+	/// The factors, `L` below the diagonal and `U` on and above it.
+	lu: Matrix<T>,
+	/// Row `i` of `P A` is row `perm[i]` of `A`.
+	perm: ~[uint],
+	/// Whether an odd number of rows were swapped.
+	odd: bool,
+}
+
+impl<
+	T: std::num::Float
+>
+Matrix<T> {
+	/// Computes the LU decomposition with partial pivoting.
+	/// A zero pivot column is skipped, which makes the `U` factor singular.
+	/// Fails if the matrix is not square.
+	pub fn lu(&self) -> LU<T> {
+		self.check_square();
+		let n = self.rows;
+		let mut a = self.x.clone();
+		let mut perm: ~[uint] = std::vec::from_fn(n, |i| i);
+		let mut odd = false;
+		for k in range(0, n) {
+			let p = matrix_pivot_row(a, n, k);
+			if p != k {
+				for j in range(0, n) { a.swap(k * n + j, p * n + j); }
+				perm.swap(k, p);
+				odd = !odd;
+			}
+
+			let pivot = a[k * n + k].clone();
+			if pivot == std::num::zero() { continue; }
+			for i in range(k + 1, n) {
+				let f = a[i * n + k] / pivot;
+				for j in range(k + 1, n) {
+					let v = a[i * n + j] - f * a[k * n + j];
+					a[i * n + j] = v;
+				}
+				a[i * n + k] = f;
+			}
+		}
+
+		LU {
+			lu: Matrix {
+				rows: n,
+				cols: n,
+				x: a
+			},
+			perm: perm,
+			odd: odd
+		}
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+LU<T> {
+	/// Returns the lower triangular factor with ones on the diagonal.
+	pub fn l(&self) -> Matrix<T> {
+		let n = self.lu.rows;
+		Matrix::from_fn(n, n, |i, j| if i > j {
+			self.lu.get(i, j)
+		} else if i == j {
+			std::num::one()
+		} else {
+			std::num::zero()
+		})
+	}
+
+	/// Returns the upper triangular factor.
+	pub fn u(&self) -> Matrix<T> {
+		let n = self.lu.rows;
+		Matrix::from_fn(n, n, |i, j| if i <= j {
+			self.lu.get(i, j)
+		} else {
+			std::num::zero()
+		})
+	}
+
+	/// Returns the permutation matrix `P`.
+	pub fn p(&self) -> Matrix<T> {
+		let n = self.lu.rows;
+		Matrix::from_fn(n, n, |i, j| if self.perm[i] == j {
+			std::num::one()
+		} else {
+			std::num::zero()
+		})
+	}
+
+	/// Returns the row permutation.
+	/// Row `i` of `P A` is row `perm[i]` of `A`.
+	pub fn permutation<'a>(&'a self) -> &'a [uint] {
+		self.perm.slice(0, self.perm.len())
+	}
+
+	/// Computes the determinant from the diagonal of `U`.
+	pub fn det(&self) -> T {
+		let mut res: T = std::num::one();
+		for i in range(0, self.lu.rows) {
+			res = res * *self.lu.elem(i, i);
+		}
+
+		if self.odd { -res } else { res }
+	}
+
+	/// Solves `A x = b` using forward and back substitution.
+	/// Fails if the length does not match the matrix.
+	pub fn solve(&self, b: &Vector<T>) -> Vector<T> {
+		let n = self.lu.rows;
+		if b.len() != n { fail!("Vector length does not match matrix"); }
+		let a = &self.lu.x;
+		let mut x: ~[T] = self.perm.iter().map(|&i| b.x[i].clone()).collect();
+		for i in range(0, n) {
+			for j in range(0, i) {
+				let v = x[i] - a[i * n + j] * x[j];
+				x[i] = v;
+			}
+		}
+		for ii in range(0, n) {
+			let i = n - 1 - ii;
+			for j in range(i + 1, n) {
+				let v = x[i] - a[i * n + j] * x[j];
+				x[i] = v;
+			}
+			let v = x[i] / a[i * n + i];
+			x[i] = v;
+		}
+
+		Vector {
+			x: x
+		}
+	}
+
+	/// Solves `A x = b` and improves the solution by iterative refinement.
+	/// Each iteration solves for the residual `b - A x` and corrects the solution.
+	/// The matrix must be the one that was factorized.
+	pub fn solve_refined(&self, a: &Matrix<T>, b: &Vector<T>, iterations: uint) -> Vector<T> {
+		let mut x = self.solve(b);
+		for _ in range(0, iterations) {
+			let r = *b - a.transform(&x);
+			let dx = self.solve(&r);
+			x = x + dx;
+		}
+
+		x
+	}
+
+	/// Computes the inverse by solving for each column of the identity matrix.
+	pub fn inv(&self) -> Matrix<T> {
+		let n = self.lu.rows;
+		let mut res: Matrix<T> = Matrix::zeros(n, n);
+		for j in range(0, n) {
+			let e = Vector {
+				x: std::vec::from_fn(n, |i| if i == j {
+					std::num::one()
+				} else {
+					std::num::zero()
+				})
+			};
+			res.set_col(j, &self.solve(&e));
+		}
+
+		res
+	}
+}
+
+impl<
+	T: std::num::Float + Eps
+>
+LU<T> {
+	/// Returns the number of pivots that are not closer to zero than the epsilon.
+	/// This is the rank of the matrix when the pivots are well separated from zero.
+	pub fn rank(&self, eps: f64) -> uint {
+		range(0, self.lu.rows).count(|i| !is_singular(self.lu.elem(i, i), eps))
+	}
+
+	/// Returns true if any pivot is closer to zero than the epsilon.
+	pub fn is_singular(&self, eps: f64) -> bool {
+		self.rank(eps) < self.lu.rows
+	}
+
+	/// Solves `A x = b` if the matrix is not singular.
+	pub fn try_solve(&self, b: &Vector<T>, eps: f64) -> Option<Vector<T>> {
+		if self.is_singular(eps) {
+			None
+		} else {
+			Some(self.solve(b))
+		}
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+Matrix4<T> {
+	/// Computes the LU decomposition with partial pivoting.
+	pub fn lu(&self) -> LU<T> {
+		Matrix::from_matrix4(self).lu()
+	}
+
+	/// Solves `A x = b` using the LU decomposition.
+	pub fn solve(&self, b: &Vector4<T>) -> Vector4<T> {
+		let x = self.lu().solve(&Vector {
+			x: ~[b.x.clone(), b.y.clone(), b.z.clone(), b.w.clone()]
+		});
+		Vector4 {
+			x: x.get(0),
+			y: x.get(1),
+			z: x.get(2),
+			w: x.get(3)
+		}
+	}
+}
+
+/// A QR decomposition computed with Householder reflections.
+/// The columns of the matrix are permuted such that `A P = Q R`,
+/// where `Q` is orthogonal and `R` is upper triangular.
+/// Without column pivoting `P` is the identity.
+#[deriving(Clone)]
+pub struct QR<T> {
+	/// The upper triangular factor.
+	r: Matrix<T>,
+	/// The Householder vectors, one per reflection.
+	/// Reflection `k` acts on the elements from `k` and down.
+	vs: ~[~[T]],
+	/// Column `j` of `A P` is column `perm[j]` of `A`.
+	perm: ~[uint],
+}
+
+/// Applies the Householder reflection `I - 2 v v^T / (v^T v)`
+/// to the elements of `x` from `k` and down.
+fn householder_reflect<T: std::num::Float>(v: &[T], x: &mut [T], k: uint) {
+	let mut vv: T = std::num::zero();
+	let mut vx: T = std::num::zero();
+	for i in range(0, v.len()) {
+		vv = vv + v[i] * v[i];
+		vx = vx + v[i] * x[k + i];
+	}
+	if vv == std::num::zero() { return; }
+
+	let s = (vx + vx) / vv;
+	for i in range(0, v.len()) {
+		let y = x[k + i] - s * v[i];
+		x[k + i] = y;
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+Matrix<T> {
+	/// Computes the QR decomposition without column pivoting.
+	pub fn qr(&self) -> QR<T> {
+		self.householder(false)
+	}
+
+	/// Computes the QR decomposition with column pivoting.
+	/// The column with the largest remaining norm is picked at each step,
+	/// which makes the diagonal of `R` decreasing in magnitude
+	/// and reveals the rank of the matrix.
+	pub fn qr_pivoted(&self) -> QR<T> {
+		self.householder(true)
+	}
+
+	/// Reduces the matrix to upper triangular form with Householder reflections.
+	fn householder(&self, pivoting: bool) -> QR<T> {
+		let (m, n) = (self.rows, self.cols);
+		let zero: T = std::num::zero();
+		let mut r = self.clone();
+		let mut perm: ~[uint] = std::vec::from_fn(n, |j| j);
+		let mut vs: ~[~[T]] = ~[];
+		let steps = if m < n { m } else { n };
+		for k in range(0, steps) {
+			if pivoting {
+				let mut p = k;
+				let mut best = zero.clone();
+				for j in range(k, n) {
+					let mut s = zero.clone();
+					for i in range(k, m) {
+						s = s + r.x[i * n + j] * r.x[i * n + j];
+					}
+					if s > best {
+						best = s;
+						p = j;
+					}
+				}
+				if p != k {
+					for i in range(0, m) { r.x.swap(i * n + k, i * n + p); }
+					perm.swap(k, p);
+				}
+			}
+
+			let mut v: ~[T] = std::vec::from_fn(m - k, |i| r.x[(k + i) * n + k].clone());
+			let mut norm_sq = zero.clone();
+			for x in v.iter() {
+				norm_sq = norm_sq + *x * *x;
+			}
+			let norm = norm_sq.sqrt();
+			let alpha = if v[0] < zero { norm } else { -norm };
+			let v0 = v[0] - alpha;
+			v[0] = v0;
+
+			let mut col: ~[T] = std::vec::from_elem(m, zero.clone());
+			for j in range(k, n) {
+				for i in range(k, m) { col[i] = r.x[i * n + j].clone(); }
+				householder_reflect(v.slice(0, m - k), col.mut_slice(0, m), k);
+				for i in range(k, m) { r.x[i * n + j] = col[i].clone(); }
+			}
+			for i in range(k + 1, m) {
+				r.x[i * n + k] = zero.clone();
+			}
+			vs.push(v);
+		}
+
+		QR {
+			r: r,
+			vs: vs,
+			perm: perm
+		}
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+QR<T> {
+	/// Computes `Q^T x` in place.
+	fn apply_qt(&self, x: &mut [T]) {
+		for k in range(0, self.vs.len()) {
+			householder_reflect(self.vs[k].slice(0, self.vs[k].len()), x, k);
+		}
+	}
+
+	/// Computes `Q x` in place.
+	fn apply_q(&self, x: &mut [T]) {
+		for kk in range(0, self.vs.len()) {
+			let k = self.vs.len() - 1 - kk;
+			householder_reflect(self.vs[k].slice(0, self.vs[k].len()), x, k);
+		}
+	}
+
+	/// Returns the orthogonal factor as a square matrix.
+	pub fn q(&self) -> Matrix<T> {
+		let m = self.r.rows;
+		let mut res: Matrix<T> = Matrix::zeros(m, m);
+		for j in range(0, m) {
+			let mut e: ~[T] = std::vec::from_fn(m, |i| if i == j {
+				std::num::one()
+			} else {
+				std::num::zero()
+			});
+			self.apply_q(e.mut_slice(0, m));
+			res.set_col(j, &Vector { x: e });
+		}
+
+		res
+	}
+
+	/// Returns the upper triangular factor.
+	pub fn r(&self) -> Matrix<T> {
+		self.r.clone()
+	}
+
+	/// Returns the permutation matrix `P`.
+	pub fn p(&self) -> Matrix<T> {
+		let n = self.r.cols;
+		Matrix::from_fn(n, n, |i, j| if self.perm[j] == i {
+			std::num::one()
+		} else {
+			std::num::zero()
+		})
+	}
+
+	/// Returns the column permutation.
+	/// Column `j` of `A P` is column `perm[j]` of `A`.
+	pub fn permutation<'a>(&'a self) -> &'a [uint] {
+		self.perm.slice(0, self.perm.len())
+	}
+
+	/// Solves `A x = b` in the least-squares sense,
+	/// minimizing the norm of `A x - b`.
+	/// The columns of the matrix must be linearly independent.
+	/// Fails if the matrix has fewer rows than columns
+	/// or if the length does not match the number of rows.
+	pub fn solve(&self, b: &Vector<T>) -> Vector<T> {
+		let (m, n) = (self.r.rows, self.r.cols);
+		if m < n { fail!("Matrix has fewer rows than columns"); }
+		if b.len() != m { fail!("Vector length does not match matrix"); }
+		let mut y: ~[T] = b.x.clone();
+		self.apply_qt(y.mut_slice(0, m));
+		for ii in range(0, n) {
+			let i = n - 1 - ii;
+			for j in range(i + 1, n) {
+				let v = y[i] - *self.r.elem(i, j) * y[j];
+				y[i] = v;
+			}
+			let v = y[i] / *self.r.elem(i, i);
+			y[i] = v;
+		}
+
+		let mut x: ~[T] = std::vec::from_elem(n, std::num::zero());
+		for j in range(0, n) {
+			x[self.perm[j]] = y[j].clone();
+		}
+
+		Vector {
+			x: x
+		}
+	}
+}
+
+impl<
+	T: std::num::Float + Eps
+>
+QR<T> {
+	/// Returns the number of diagonal elements of `R`
+	/// that are not closer to zero than the epsilon.
+	/// This is only reliable for a decomposition with column pivoting.
+	pub fn rank(&self, eps: f64) -> uint {
+		let k = if self.r.rows < self.r.cols { self.r.rows } else { self.r.cols };
+		range(0, k).count(|i| !is_singular(self.r.elem(i, i), eps))
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+Matrix4<T> {
+	/// Computes the QR decomposition without column pivoting.
+	pub fn qr(&self) -> QR<T> {
+		Matrix::from_matrix4(self).qr()
+	}
+}
+
+/// Solves an overdetermined system `A x = b` in the least-squares sense.
+/// Uses a QR decomposition with column pivoting.
+pub fn least_squares<T: std::num::Float>(a: &Matrix<T>, b: &Vector<T>) -> Vector<T> {
+	a.qr_pivoted().solve(b)
+}
+
+/// A Cholesky decomposition `A = L L^T` of a symmetric positive definite matrix,
+/// where `L` is lower triangular with positive diagonal.
+#[deriving(Clone)]
+pub struct Cholesky<T> {
+	/// The lower triangular factor.
+	l: Matrix<T>,
+}
+
+/// An LDL^T decomposition `A = L D L^T` of a symmetric matrix,
+/// where `L` is lower triangular with ones on the diagonal
+/// and `D` is diagonal.
+/// Unlike Cholesky it requires no square roots
+/// and works for matrices that are not positive definite.
+#[deriving(Clone)]
+pub struct LDL<T> {
+	/// The lower triangular factor with ones on the diagonal.
+	l: Matrix<T>,
+	/// The diagonal of `D`.
+	d: ~[T],
+}
+
+/// Solves `L y = b` where `L` is lower triangular.
+/// Uses ones instead of the diagonal when `unit` is true.
+fn forward_substitute<T: std::num::Float>(l: &Matrix<T>, unit: bool, b: &[T]) -> ~[T] {
+	let n = l.rows;
+	let mut y: ~[T] = b.to_owned();
+	for i in range(0, n) {
+		for j in range(0, i) {
+			let v = y[i] - l.x[i * n + j] * y[j];
+			y[i] = v;
+		}
+		if !unit {
+			let v = y[i] / l.x[i * n + i];
+			y[i] = v;
+		}
+	}
+
+	y
+}
+
+/// Solves `L^T x = y` where `L` is lower triangular.
+/// Uses ones instead of the diagonal when `unit` is true.
+fn back_substitute_transposed<T: std::num::Float>(l: &Matrix<T>, unit: bool, y: &[T]) -> ~[T] {
+	let n = l.rows;
+	let mut x: ~[T] = y.to_owned();
+	for ii in range(0, n) {
+		let i = n - 1 - ii;
+		for j in range(i + 1, n) {
+			let v = x[i] - l.x[j * n + i] * x[j];
+			x[i] = v;
+		}
+		if !unit {
+			let v = x[i] / l.x[i * n + i];
+			x[i] = v;
+		}
+	}
+
+	x
+}
+
+impl<
+	T: Eps
+>
+Matrix<T> {
+	/// Returns true if the matrix is square and equal to its transpose
+	/// within the epsilon.
+	pub fn is_symmetric(&self, eps: f64) -> bool {
+		self.is_square() && range(0, self.rows).all(|i| range(0, i).all(
+			|j| self.elem(i, j).close_eps(self.elem(j, i), eps)
+		))
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+Matrix<T> {
+	/// Computes the Cholesky decomposition.
+	/// Only the lower triangular part of the matrix is read.
+	/// Returns `None` if the matrix is not positive definite.
+	/// Fails if the matrix is not square.
+	pub fn cholesky(&self) -> Option<Cholesky<T>> {
+		self.check_square();
+		let n = self.rows;
+		let zero: T = std::num::zero();
+		let mut l: Matrix<T> = Matrix::zeros(n, n);
+		for j in range(0, n) {
+			let mut s = self.x[j * n + j].clone();
+			for k in range(0, j) {
+				s = s - l.x[j * n + k] * l.x[j * n + k];
+			}
+			// Also rejects NaN.
+			if !(s > zero) { return None; }
+
+			let d = s.sqrt();
+			for i in range(j + 1, n) {
+				let mut s = self.x[i * n + j].clone();
+				for k in range(0, j) {
+					s = s - l.x[i * n + k] * l.x[j * n + k];
+				}
+				l.x[i * n + j] = s / d;
+			}
+			l.x[j * n + j] = d;
+		}
+
+		Some(Cholesky {
+			l: l
+		})
+	}
+
+	/// Computes the LDL^T decomposition without pivoting.
+	/// Only the lower triangular part of the matrix is read.
+	/// Returns `None` if a zero pivot is encountered.
+	/// Fails if the matrix is not square.
+	pub fn ldl(&self) -> Option<LDL<T>> {
+		self.check_square();
+		let n = self.rows;
+		let zero: T = std::num::zero();
+		let mut l: Matrix<T> = Matrix::identity(n);
+		let mut d: ~[T] = ~[];
+		for j in range(0, n) {
+			let mut dj = self.x[j * n + j].clone();
+			for k in range(0, j) {
+				dj = dj - l.x[j * n + k] * l.x[j * n + k] * d[k];
+			}
+			if dj == zero { return None; }
+
+			for i in range(j + 1, n) {
+				let mut s = self.x[i * n + j].clone();
+				for k in range(0, j) {
+					s = s - l.x[i * n + k] * l.x[j * n + k] * d[k];
+				}
+				l.x[i * n + j] = s / dj;
+			}
+			d.push(dj);
+		}
+
+		Some(LDL {
+			l: l,
+			d: d
+		})
+	}
+}
+
+impl<
+	T: std::num::Float + Eps
+>
+Matrix<T> {
+	/// Returns true if the matrix is symmetric and positive definite.
+	pub fn is_positive_definite(&self, eps: f64) -> bool {
+		self.is_symmetric(eps) && self.cholesky().is_some()
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+Matrix4<T> {
+	/// Computes the Cholesky decomposition.
+	/// Returns `None` if the matrix is not positive definite.
+	pub fn cholesky(&self) -> Option<Cholesky<T>> {
+		Matrix::from_matrix4(self).cholesky()
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+Cholesky<T> {
+	/// Returns the lower triangular factor.
+	pub fn l(&self) -> Matrix<T> {
+		self.l.clone()
+	}
+
+	/// Solves `A x = b`.
+	/// Fails if the length does not match the matrix.
+	pub fn solve(&self, b: &Vector<T>) -> Vector<T> {
+		if b.len() != self.l.rows { fail!("Vector length does not match matrix"); }
+		let y = forward_substitute(&self.l, false, b.as_slice());
+		Vector {
+			x: back_substitute_transposed(&self.l, false, y.slice(0, y.len()))
+		}
+	}
+
+	/// Computes the determinant, which is the squared product of the diagonal of `L`.
+	pub fn det(&self) -> T {
+		let mut res: T = std::num::one();
+		for i in range(0, self.l.rows) {
+			res = res * *self.l.elem(i, i);
+		}
+
+		res * res
+	}
+
+	/// Computes the natural logarithm of the determinant.
+	/// This does not overflow for large matrices like the determinant does.
+	pub fn log_det(&self) -> T {
+		let mut res: T = std::num::zero();
+		for i in range(0, self.l.rows) {
+			res = res + self.l.elem(i, i).ln();
+		}
+
+		res + res
+	}
+
+	/// Computes the inverse by solving for each column of the identity matrix.
+	pub fn inv(&self) -> Matrix<T> {
+		let n = self.l.rows;
+		let mut res: Matrix<T> = Matrix::zeros(n, n);
+		for j in range(0, n) {
+			let e = Vector {
+				x: std::vec::from_fn(n, |i| if i == j {
+					std::num::one()
+				} else {
+					std::num::zero()
+				})
+			};
+			res.set_col(j, &self.solve(&e));
+		}
+
+		res
+	}
+
+	/// Applies a rank-one modification `A + sign x x^T` to the factor.
+	/// Returns `None` if the result is not positive definite.
+	fn modify(&self, x: &Vector<T>, downdate: bool) -> Option<Cholesky<T>> {
+		let n = self.l.rows;
+		if x.len() != n { fail!("Vector length does not match matrix"); }
+		let zero: T = std::num::zero();
+		let mut l = self.l.clone();
+		let mut x = x.x.clone();
+		for k in range(0, n) {
+			let lkk = l.x[k * n + k].clone();
+			let r2 = if downdate {
+				lkk * lkk - x[k] * x[k]
+			} else {
+				lkk * lkk + x[k] * x[k]
+			};
+			if !(r2 > zero) { return None; }
+
+			let r = r2.sqrt();
+			let c = r / lkk;
+			let s = x[k] / lkk;
+			l.x[k * n + k] = r;
+			for i in range(k + 1, n) {
+				let lik = if downdate {
+					(l.x[i * n + k] - s * x[i]) / c
+				} else {
+					(l.x[i * n + k] + s * x[i]) / c
+				};
+				let xi = c * x[i] - s * lik;
+				l.x[i * n + k] = lik;
+				x[i] = xi;
+			}
+		}
+
+		Some(Cholesky {
+			l: l
+		})
+	}
+
+	/// Computes the factor of `A + x x^T` in quadratic time.
+	/// Fails if the length does not match the matrix.
+	pub fn update(&self, x: &Vector<T>) -> Cholesky<T> {
+		match self.modify(x, false) {
+			Some(res) => res,
+			None => fail!("Cholesky update lost positive definiteness")
+		}
+	}
+
+	/// Computes the factor of `A - x x^T` in quadratic time.
+	/// Returns `None` if the result is not positive definite.
+	/// Fails if the length does not match the matrix.
+	pub fn downdate(&self, x: &Vector<T>) -> Option<Cholesky<T>> {
+		self.modify(x, true)
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+LDL<T> {
+	/// Returns the lower triangular factor with ones on the diagonal.
+	pub fn l(&self) -> Matrix<T> {
+		self.l.clone()
+	}
+
+	/// Returns the diagonal of `D`.
+	pub fn d(&self) -> Vector<T> {
+		Vector {
+			x: self.d.clone()
+		}
+	}
+
+	/// Returns true if all elements of `D` are positive,
+	/// which means the decomposed matrix is positive definite.
+	pub fn is_positive_definite(&self) -> bool {
+		let zero: T = std::num::zero();
+		self.d.iter().all(|d| *d > zero)
+	}
+
+	/// Solves `A x = b`.
+	/// Fails if the length does not match the matrix.
+	pub fn solve(&self, b: &Vector<T>) -> Vector<T> {
+		if b.len() != self.l.rows { fail!("Vector length does not match matrix"); }
+		let mut y = forward_substitute(&self.l, true, b.as_slice());
+		for i in range(0, y.len()) {
+			let v = y[i] / self.d[i];
+			y[i] = v;
+		}
+
+		Vector {
+			x: back_substitute_transposed(&self.l, true, y.slice(0, y.len()))
+		}
+	}
+
+	/// Computes the determinant, which is the product of the diagonal of `D`.
+	pub fn det(&self) -> T {
+		let mut res: T = std::num::one();
+		for d in self.d.iter() {
+			res = res * *d;
+		}
+
+		res
+	}
+
+	/// Computes the natural logarithm of the determinant.
+	/// The result is NaN if the matrix is not positive definite.
+	pub fn log_det(&self) -> T {
+		let mut res: T = std::num::zero();
+		for d in self.d.iter() {
+			res = res + d.ln();
+		}
+
+		res
+	}
+
+	/// Computes the factors of `A + alpha x x^T` in quadratic time.
+	/// A negative `alpha` gives a downdate.
+	/// Returns `None` if a zero pivot is encountered.
+	/// Fails if the length does not match the matrix.
+	pub fn update(&self, x: &Vector<T>, alpha: T) -> Option<LDL<T>> {
+		let n = self.l.rows;
+		if x.len() != n { fail!("Vector length does not match matrix"); }
+		let zero: T = std::num::zero();
+		let mut l = self.l.clone();
+		let mut d = self.d.clone();
+		let mut x = x.x.clone();
+		let mut a = alpha;
+		for j in range(0, n) {
+			let p = x[j].clone();
+			let dj = d[j] + a * p * p;
+			if dj == zero { return None; }
+
+			let b = p * a / dj;
+			a = d[j] * a / dj;
+			d[j] = dj;
+			for i in range(j + 1, n) {
+				let xi = x[i] - p * l.x[i * n + j];
+				let lij = l.x[i * n + j] + b * xi;
+				x[i] = xi;
+				l.x[i * n + j] = lij;
+			}
+		}
+
+		Some(LDL {
+			l: l,
+			d: d
+		})
+	}
+}
+
+/// Sorts eigenvalues in ascending order together with the columns of eigenvectors.
+/// The eigenvectors are stored row by row in a square matrix with `n` rows.
+fn sort_eigen<T: std::num::Float>(d: &mut [T], v: &mut [T], n: uint) {
+	for i in range(0, n) {
+		let mut k = i;
+		for j in range(i + 1, n) {
+			if d[j] < d[k] { k = j; }
+		}
+		if k != i {
+			d.swap(i, k);
+			for r in range(0, n) { v.swap(r * n + i, r * n + k); }
+		}
+	}
+}
+
+/// Computes eigenvalues and eigenvectors of a symmetric matrix
+/// with the cyclic Jacobi method.
+/// The matrix is stored row by row with `n` rows.
+/// Returns the eigenvalues in ascending order
+/// and the eigenvectors as columns of a matrix stored row by row.
+/// Fails if the off-diagonal elements do not vanish within 64 sweeps.
+fn jacobi_eigen<T: std::num::Float>(mut a: ~[T], n: uint) -> (~[T], ~[T]) {
+	let zero: T = std::num::zero();
+	let one: T = std::num::one();
+	let two = one + one;
+	let hundred: T = std::num::cast(100).unwrap();
+	let mut v: ~[T] = std::vec::from_fn(n * n, |k| if k / n == k % n {
+		one.clone()
+	} else {
+		zero.clone()
+	});
+	let mut sweeps = 0;
+	loop {
+		let mut off = zero.clone();
+		for p in range(0, n) {
+			for q in range(0, n) {
+				if p != q { off = off + a[p * n + q] * a[p * n + q]; }
+			}
+		}
+		if off == zero { break; }
+		if sweeps == 64 { fail!("Eigenvalues did not converge"); }
+		sweeps += 1;
+
+		for p in range(0, n) {
+			for q in range(p + 1, n) {
+				let apq = a[p * n + q].clone();
+				// Drop elements that are too small to change the diagonal.
+				let g = hundred * apq.abs();
+				let (app, aqq) = (a[p * n + p].abs(), a[q * n + q].abs());
+				if app + g == app && aqq + g == aqq {
+					a[p * n + q] = zero.clone();
+					a[q * n + p] = zero.clone();
+					continue;
+				}
+
+				let theta = (a[q * n + q] - a[p * n + p]) / (two * apq);
+				let sign = if theta < zero { -one } else { one.clone() };
+				let t = sign / (theta.abs() + (theta * theta + one).sqrt());
+				let c = one / (t * t + one).sqrt();
+				let s = t * c;
+				for k in range(0, n) {
+					let (akp, akq) = (a[k * n + p].clone(), a[k * n + q].clone());
+					a[k * n + p] = c * akp - s * akq;
+					a[k * n + q] = s * akp + c * akq;
+				}
+				for k in range(0, n) {
+					let (apk, aqk) = (a[p * n + k].clone(), a[q * n + k].clone());
+					a[p * n + k] = c * apk - s * aqk;
+					a[q * n + k] = s * apk + c * aqk;
+				}
+				for k in range(0, n) {
+					let (vkp, vkq) = (v[k * n + p].clone(), v[k * n + q].clone());
+					v[k * n + p] = c * vkp - s * vkq;
+					v[k * n + q] = s * vkp + c * vkq;
+				}
+			}
+		}
+	}
+
+	let mut d: ~[T] = std::vec::from_fn(n, |i| a[i * n + i].clone());
+	sort_eigen(d.mut_slice(0, n), v.mut_slice(0, n * n), n);
+	(d, v)
+}
+
+/// Computes eigenvalues and eigenvectors of a symmetric matrix
+/// by Householder reduction to tridiagonal form followed by the QL method
+/// with implicit shifts.
+/// This follows the `tred2` and `tql2` procedures from EISPACK.
+/// The matrix is stored row by row with `n` rows.
+/// Returns the eigenvalues in ascending order
+/// and the eigenvectors as columns of a matrix stored row by row.
+fn tridiagonal_eigen<T: std::num::Float>(mut v: ~[T], n: uint) -> (~[T], ~[T]) {
+	let zero: T = std::num::zero();
+	let one: T = std::num::one();
+	let two = one + one;
+	let mut d: ~[T] = std::vec::from_elem(n, zero.clone());
+	let mut e: ~[T] = std::vec::from_elem(n, zero.clone());
+	if n == 0 { return (d, v); }
+
+	// Householder reduction to tridiagonal form.
+	for j in range(0, n) { d[j] = v[(n - 1) * n + j].clone(); }
+	for ii in range(1, n) {
+		let i = n - ii;
+		let mut scale = zero.clone();
+		let mut h = zero.clone();
+		for k in range(0, i) { scale = scale + d[k].abs(); }
+		if scale == zero {
+			e[i] = d[i - 1].clone();
+			for j in range(0, i) {
+				d[j] = v[(i - 1) * n + j].clone();
+				v[i * n + j] = zero.clone();
+				v[j * n + i] = zero.clone();
+			}
+		} else {
+			for k in range(0, i) {
+				d[k] = d[k] / scale;
+				h = h + d[k] * d[k];
+			}
+			let mut f = d[i - 1].clone();
+			let mut g = h.sqrt();
+			if f > zero { g = -g; }
+			e[i] = scale * g;
+			h = h - f * g;
+			d[i - 1] = f - g;
+			for j in range(0, i) { e[j] = zero.clone(); }
+			for j in range(0, i) {
+				f = d[j].clone();
+				v[j * n + i] = f.clone();
+				g = e[j] + v[j * n + j] * f;
+				for k in range(j + 1, i) {
+					g = g + v[k * n + j] * d[k];
+					e[k] = e[k] + v[k * n + j] * f;
+				}
+				e[j] = g;
+			}
+			f = zero.clone();
+			for j in range(0, i) {
+				e[j] = e[j] / h;
+				f = f + e[j] * d[j];
+			}
+			let hh = f / (h + h);
+			for j in range(0, i) { e[j] = e[j] - hh * d[j]; }
+			for j in range(0, i) {
+				f = d[j].clone();
+				g = e[j].clone();
+				for k in range(j, i) {
+					v[k * n + j] = v[k * n + j] - (f * e[k] + g * d[k]);
+				}
+				d[j] = v[(i - 1) * n + j].clone();
+				v[i * n + j] = zero.clone();
+			}
+		}
+		d[i] = h;
+	}
+
+	// Accumulate the transformations.
+	for i in range(0, n - 1) {
+		v[(n - 1) * n + i] = v[i * n + i].clone();
+		v[i * n + i] = one.clone();
+		let h = d[i + 1].clone();
+		if h != zero {
+			for k in range(0, i + 1) { d[k] = v[k * n + i + 1] / h; }
+			for j in range(0, i + 1) {
+				let mut g = zero.clone();
+				for k in range(0, i + 1) { g = g + v[k * n + i + 1] * v[k * n + j]; }
+				for k in range(0, i + 1) { v[k * n + j] = v[k * n + j] - g * d[k]; }
+			}
+		}
+		for k in range(0, i + 1) { v[k * n + i + 1] = zero.clone(); }
+	}
+	for j in range(0, n) {
+		d[j] = v[(n - 1) * n + j].clone();
+		v[(n - 1) * n + j] = zero.clone();
+	}
+	v[(n - 1) * n + n - 1] = one.clone();
+	e[0] = zero.clone();
+
+	// Diagonalize the tridiagonal matrix with the QL method.
+	for i in range(1, n) { e[i - 1] = e[i].clone(); }
+	e[n - 1] = zero.clone();
+	let eps: T = std::num::Float::epsilon();
+	let mut f = zero.clone();
+	let mut tst1 = zero.clone();
+	for l in range(0, n) {
+		let t = d[l].abs() + e[l].abs();
+		if t > tst1 { tst1 = t; }
+		let mut m = l;
+		while m < n {
+			if e[m].abs() <= eps * tst1 { break; }
+			m += 1;
+		}
+
+		if m > l {
+			let mut iter = 0;
+			loop {
+				iter += 1;
+				if iter > 30 { fail!("Eigenvalues did not converge"); }
+
+				let mut g = d[l].clone();
+				let mut p = (d[l + 1] - g) / (two * e[l]);
+				let mut r = p.hypot(&one);
+				if p < zero { r = -r; }
+				d[l] = e[l] / (p + r);
+				d[l + 1] = e[l] * (p + r);
+				let dl1 = d[l + 1].clone();
+				let mut h = g - d[l];
+				for i in range(l + 2, n) { d[i] = d[i] - h; }
+				f = f + h;
+
+				p = d[m].clone();
+				let mut c = one.clone();
+				let mut c2 = c.clone();
+				let mut c3 = c.clone();
+				let el1 = e[l + 1].clone();
+				let mut s = zero.clone();
+				let mut s2 = zero.clone();
+				for ii in range(l, m) {
+					let i = m - 1 - (ii - l);
+					c3 = c2.clone();
+					c2 = c.clone();
+					s2 = s.clone();
+					g = c * e[i];
+					h = c * p;
+					r = p.hypot(&e[i]);
+					e[i + 1] = s * r;
+					s = e[i] / r;
+					c = p / r;
+					p = c * d[i] - s * g;
+					d[i + 1] = h + s * (c * g + s * d[i]);
+					for k in range(0, n) {
+						h = v[k * n + i + 1].clone();
+						v[k * n + i + 1] = s * v[k * n + i] + c * h;
+						v[k * n + i] = c * v[k * n + i] - s * h;
+					}
+				}
+				p = -s * s2 * c3 * el1 * e[l] / dl1;
+				e[l] = s * p;
+				d[l] = c * p;
+				if !(e[l].abs() > eps * tst1) { break; }
+			}
+		}
+		d[l] = d[l] + f;
+		e[l] = zero.clone();
+	}
+
+	sort_eigen(d.mut_slice(0, n), v.mut_slice(0, n * n), n);
+	(d, v)
+}
+
+impl<
+	T: std::num::Float
+>
+Matrix<T> {
+	/// Computes eigenvalues and eigenvectors of a symmetric matrix.
+	/// Uses Householder reduction to tridiagonal form and the QL method.
+	/// Returns the eigenvalues in ascending order
+	/// and an orthonormal matrix with the eigenvectors as columns.
+	/// The matrix is assumed to be symmetric.
+	/// Fails if the matrix is not square.
+	pub fn symmetric_eigen(&self) -> (Vector<T>, Matrix<T>) {
+		self.check_square();
+		let n = self.rows;
+		let (d, v) = tridiagonal_eigen(self.x.clone(), n);
+		(Vector { x: d }, Matrix { rows: n, cols: n, x: v })
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+Matrix3<T> {
+	/// Computes eigenvalues and eigenvectors of a symmetric matrix.
+	/// Uses the cyclic Jacobi method.
+	/// Returns the eigenvalues in ascending order
+	/// and an orthonormal matrix with the eigenvectors as columns.
+	/// The matrix is assumed to be symmetric.
+	pub fn symmetric_eigen(&self) -> (Vector3<T>, Matrix3<T>) {
+		let a: ~[T] = std::vec::from_fn(9, |k| self.get(k / 3, k % 3));
+		let (d, v) = jacobi_eigen(a, 3);
+		(
+			Vector3::new(d[0].clone(), d[1].clone(), d[2].clone()),
+			Matrix3::new(
+				v[0].clone(), v[1].clone(), v[2].clone(),
+				v[3].clone(), v[4].clone(), v[5].clone(),
+				v[6].clone(), v[7].clone(), v[8].clone()
+			)
+		)
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+Matrix4<T> {
+	/// Computes eigenvalues and eigenvectors of a symmetric matrix.
+	/// Uses the cyclic Jacobi method.
+	/// Returns the eigenvalues in ascending order
+	/// and an orthonormal matrix with the eigenvectors as columns.
+	/// The matrix is assumed to be symmetric.
+	pub fn symmetric_eigen(&self) -> (Vector4<T>, Matrix4<T>) {
+		let a: ~[T] = std::vec::from_fn(16, |k| self.get(k / 4, k % 4));
+		let (d, v) = jacobi_eigen(a, 4);
+		(
+			Vector4::new(d[0].clone(), d[1].clone(), d[2].clone(), d[3].clone()),
+			Matrix4::new(
+				v[0].clone(), v[1].clone(), v[2].clone(), v[3].clone(),
+				v[4].clone(), v[5].clone(), v[6].clone(), v[7].clone(),
+				v[8].clone(), v[9].clone(), v[10].clone(), v[11].clone(),
+				v[12].clone(), v[13].clone(), v[14].clone(), v[15].clone()
+			)
+		)
+	}
+}
+
+/// A singular value decomposition `A = U S V^T`,
+/// where `U` and `V` have orthonormal columns
+/// and `S` is diagonal with non-negative singular values in descending order.
+/// Columns of `U` that belong to zero singular values
+/// are completed to an orthonormal basis.
+/// The decomposition is thin, so a matrix with `m` rows and `n` columns
+/// has `min(m, n)` singular values.
+#[deriving(Clone)]
+pub struct SVD<T> {
+	/// The left singular vectors as columns.
+	u: Matrix<T>,
+	/// The singular values in descending order.
+	s: ~[T],
+	/// The right singular vectors as columns.
+	v: Matrix<T>,
+}
+
+/// Computes the thin SVD of a matrix with at least as many rows as columns
+/// using the one-sided Jacobi method.
+/// Columns are rotated in pairs until they are mutually orthogonal.
+/// Fails if the columns are not orthogonal within 64 sweeps.
+fn jacobi_svd<T: std::num::Float>(a: &Matrix<T>) -> SVD<T> {
+	let (m, n) = (a.rows, a.cols);
+	let zero: T = std::num::zero();
+	let one: T = std::num::one();
+	let two = one + one;
+	let eps: T = std::num::Float::epsilon();
+	let mut u = a.x.clone();
+	let id: Matrix<T> = Matrix::identity(n);
+	let mut v = id.into_vec();
+	let mut sweeps = 0;
+	loop {
+		if sweeps == 64 { fail!("Singular values did not converge"); }
+		sweeps += 1;
+
+		let mut rotated = false;
+		for p in range(0, n) {
+			for q in range(p + 1, n) {
+				let mut alpha = zero.clone();
+				let mut beta = zero.clone();
+				let mut gamma = zero.clone();
+				for i in range(0, m) {
+					alpha = alpha + u[i * n + p] * u[i * n + p];
+					beta = beta + u[i * n + q] * u[i * n + q];
+					gamma = gamma + u[i * n + p] * u[i * n + q];
+				}
+				if !(gamma.abs() > eps * (alpha * beta).sqrt()) { continue; }
+
+				rotated = true;
+				let zeta = (beta - alpha) / (two * gamma);
+				let sign = if zeta < zero { -one } else { one.clone() };
+				let t = sign / (zeta.abs() + (one + zeta * zeta).sqrt());
+				let c = one / (one + t * t).sqrt();
+				let s = c * t;
+				for i in range(0, m) {
+					let (up, uq) = (u[i * n + p].clone(), u[i * n + q].clone());
+					u[i * n + p] = c * up - s * uq;
+					u[i * n + q] = s * up + c * uq;
+				}
+				for i in range(0, n) {
+					let (vp, vq) = (v[i * n + p].clone(), v[i * n + q].clone());
+					v[i * n + p] = c * vp - s * vq;
+					v[i * n + q] = s * vp + c * vq;
+				}
+			}
+		}
+		if !rotated { break; }
+	}
+
+	// The singular values are the norms of the columns.
+	let mut s: ~[T] = std::vec::from_fn(n, |j| {
+		let mut sum = zero.clone();
+		for i in range(0, m) { sum = sum + u[i * n + j] * u[i * n + j]; }
+		sum.sqrt()
+	});
+
+	// Sort in descending order.
+	for i in range(0, n) {
+		let mut k = i;
+		for j in range(i + 1, n) {
+			if s[j] > s[k] { k = j; }
+		}
+		if k != i {
+			s.swap(i, k);
+			for r in range(0, m) { u.swap(r * n + i, r * n + k); }
+			for r in range(0, n) { v.swap(r * n + i, r * n + k); }
+		}
+	}
+
+	// Normalize the columns. Columns of negligible singular values carry no
+	// direction, so they are replaced by unit vectors orthogonal to the others
+	// using Gram-Schmidt against the identity.
+	let size: T = std::num::cast(if m > n { m } else { n }).unwrap();
+	let tol = match s.head_opt() {
+		Some(max) => size * eps * *max,
+		None => zero.clone()
+	};
+	let half: T = std::num::cast(0.5).unwrap();
+	for j in range(0, n) {
+		if s[j] > tol {
+			for i in range(0, m) { u[i * n + j] = u[i * n + j] / s[j]; }
+			continue;
+		}
+
+		for k in range(0, m) {
+			let mut w: ~[T] = std::vec::from_fn(m, |i| if i == k { one.clone() } else { zero.clone() });
+			// Orthogonalize twice for numerical stability.
+			for _ in range(0, 2) {
+				for l in range(0, j) {
+					let mut d = zero.clone();
+					for i in range(0, m) { d = d + u[i * n + l] * w[i]; }
+					for i in range(0, m) { w[i] = w[i] - d * u[i * n + l]; }
+				}
+			}
+			let mut norm = zero.clone();
+			for i in range(0, m) { norm = norm + w[i] * w[i]; }
+			let norm = norm.sqrt();
+			if norm > half {
+				for i in range(0, m) { u[i * n + j] = w[i] / norm; }
+				break;
+			}
+		}
+	}
+
+	SVD {
+		u: Matrix { rows: m, cols: n, x: u },
+		s: s,
+		v: Matrix { rows: n, cols: n, x: v }
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+Matrix<T> {
+	/// Computes the thin singular value decomposition.
+	pub fn svd(&self) -> SVD<T> {
+		if self.rows >= self.cols {
+			jacobi_svd(self)
+		} else {
+			let SVD { u, s, v } = jacobi_svd(&self.transpose());
+			SVD {
+				u: v,
+				s: s,
+				v: u
+			}
+		}
+	}
+
+	/// Returns the number of singular values larger than the epsilon
+	/// times the largest singular value.
+	pub fn rank(&self, eps: f64) -> uint {
+		self.svd().rank(eps)
+	}
+
+	/// Computes the condition number in the spectral norm.
+	pub fn cond(&self) -> T {
+		self.svd().cond()
+	}
+
+	/// Computes the spectral norm, which is the largest singular value.
+	pub fn spectral_norm(&self) -> T {
+		self.svd().spectral_norm()
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+Matrix4<T> {
+	/// Computes the singular value decomposition.
+	pub fn svd(&self) -> SVD<T> {
+		Matrix::from_matrix4(self).svd()
+	}
+
+	/// Returns the number of singular values larger than the epsilon
+	/// times the largest singular value.
+	pub fn rank(&self, eps: f64) -> uint {
+		self.svd().rank(eps)
+	}
+
+	/// Computes the condition number in the spectral norm.
+	pub fn cond(&self) -> T {
+		self.svd().cond()
+	}
+
+	/// Computes the spectral norm, which is the largest singular value.
+	pub fn spectral_norm(&self) -> T {
+		self.svd().spectral_norm()
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+SVD<T> {
+	/// Returns the left singular vectors as columns.
+	pub fn u(&self) -> Matrix<T> {
+		self.u.clone()
+	}
+
+	/// Returns the singular values in descending order.
+	pub fn s(&self) -> Vector<T> {
+		Vector {
+			x: self.s.clone()
+		}
+	}
+
+	/// Returns the right singular vectors as columns.
+	pub fn v(&self) -> Matrix<T> {
+		self.v.clone()
+	}
+
+	/// Returns the number of singular values larger than the epsilon
+	/// times the largest singular value.
+	pub fn rank(&self, eps: f64) -> uint {
+		let tol = self.tolerance(eps);
+		self.s.iter().count(|s| *s > tol)
+	}
+
+	/// Computes the condition number, which is the ratio
+	/// of the largest singular value to the smallest.
+	/// Returns infinity for a singular matrix.
+	pub fn cond(&self) -> T {
+		let zero: T = std::num::zero();
+		match (self.s.head_opt(), self.s.last_opt()) {
+			(Some(max), Some(min)) if *min != zero => *max / *min,
+			_ => std::num::Float::infinity()
+		}
+	}
+
+	/// Returns the spectral norm, which is the largest singular value.
+	pub fn spectral_norm(&self) -> T {
+		match self.s.head_opt() {
+			Some(max) => max.clone(),
+			None => std::num::zero()
+		}
+	}
+
+	/// Returns the epsilon relative to the largest singular value.
+	fn tolerance(&self, eps: f64) -> T {
+		let eps: T = std::num::cast(eps).unwrap();
+		eps * self.spectral_norm()
+	}
+
+	/// Computes the pseudo-inverse `V S^+ U^T`, where singular values
+	/// not larger than the epsilon times the largest one are treated as zero.
+	pub fn pinv(&self, eps: f64) -> Matrix<T> {
+		let (m, n, k) = (self.u.rows, self.v.rows, self.s.len());
+		let zero: T = std::num::zero();
+		let one: T = std::num::one();
+		let tol = self.tolerance(eps);
+		let inv: ~[T] = self.s.iter().map(|s| if *s > tol {
+			one / *s
+		} else {
+			zero.clone()
+		}).collect();
+		Matrix::from_fn(n, m, |i, j| {
+			let mut sum = zero.clone();
+			for l in range(0, k) {
+				sum = sum + self.v.x[i * k + l] * inv[l] * self.u.x[j * k + l];
+			}
+			sum
+		})
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+PInv<Matrix<T>> 
+for Matrix<T> {
+	fn pinv(&self, eps: f64) -> Matrix<T> {
+		self.svd().pinv(eps)
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+PInv<Matrix4<T>> 
+for Matrix4<T> {
+	fn pinv(&self, eps: f64) -> Matrix4<T> {
+		self.svd().pinv(eps).to_matrix4()
+	}
+}
+
+/// Reduces a matrix to upper Hessenberg form with Householder reflections.
+/// This follows the `orthes` procedure from EISPACK.
+/// The matrix is stored row by row with `n` rows.
+fn hessenberg_reduce<T: std::num::Float>(h: &mut [T], n: uint) {
+	if n < 3 { return; }
+
+	let zero: T = std::num::zero();
+	let mut ort: ~[T] = std::vec::from_elem(n, zero.clone());
+	for m in range(1, n - 1) {
+		let mut scale = zero.clone();
+		for i in range(m, n) { scale = scale + h[i * n + m - 1].abs(); }
+		if scale == zero { continue; }
+
+		let mut hh = zero.clone();
+		for i in range(m, n) {
+			ort[i] = h[i * n + m - 1] / scale;
+			hh = hh + ort[i] * ort[i];
+		}
+		let mut g = hh.sqrt();
+		if ort[m] > zero { g = -g; }
+		hh = hh - ort[m] * g;
+		ort[m] = ort[m] - g;
+
+		// Apply the reflection from the left and from the right.
+		for j in range(m, n) {
+			let mut f = zero.clone();
+			for i in range(m, n) { f = f + ort[i] * h[i * n + j]; }
+			f = f / hh;
+			for i in range(m, n) { h[i * n + j] = h[i * n + j] - f * ort[i]; }
+		}
+		for i in range(0, n) {
+			let mut f = zero.clone();
+			for j in range(m, n) { f = f + ort[j] * h[i * n + j]; }
+			f = f / hh;
+			for j in range(m, n) { h[i * n + j] = h[i * n + j] - f * ort[j]; }
+		}
+
+		h[m * n + m - 1] = scale * g;
+		for i in range(m + 1, n) { h[i * n + m - 1] = zero.clone(); }
+	}
+}
+
+/// Computes the eigenvalues of an upper Hessenberg matrix
+/// with the Francis double shift QR method.
+/// This follows the `hqr` procedure from EISPACK.
+/// The matrix is stored row by row with `n` rows and is overwritten.
+fn hessenberg_eigenvalues<T: std::num::Float>(a: &mut [T], n: uint) -> ~[Complex<T>] {
+	/// Returns the index of an element in the matrix.
+	fn at(i: int, j: int, n: uint) -> uint {
+		i as uint * n + j as uint
+	}
+
+	let zero: T = std::num::zero();
+	let eps: T = std::num::Float::epsilon();
+	let half: T = std::num::cast(0.5).unwrap();
+	let exceptional_x: T = std::num::cast(0.75).unwrap();
+	let exceptional_w: T = std::num::cast(0.4375).unwrap();
+	let mut wr: ~[Complex<T>] = std::vec::from_fn(n, |_| Complex {
+		x0: zero.clone(),
+		x1: zero.clone()
+	});
+
+	let mut anorm = zero.clone();
+	for i in range(0, n as int) {
+		for j in range(if i > 0 { i - 1 } else { 0 }, n as int) {
+			anorm = anorm + a[at(i, j, n)].abs();
+		}
+	}
+
+	let mut nn = n as int - 1;
+	let mut t = zero.clone();
+	let (mut p, mut q, mut r) = (zero.clone(), zero.clone(), zero.clone());
+	let (mut s, mut w) = (zero.clone(), zero.clone());
+	let (mut x, mut y, mut z) = (zero.clone(), zero.clone(), zero.clone());
+	while nn >= 0 {
+		let mut its = 0;
+		loop {
+			// Look for a single small subdiagonal element.
+			let mut l = nn;
+			while l > 0 {
+				s = a[at(l - 1, l - 1, n)].abs() + a[at(l, l, n)].abs();
+				if s == zero { s = anorm.clone(); }
+				if a[at(l, l - 1, n)].abs() <= eps * s {
+					a[at(l, l - 1, n)] = zero.clone();
+					break;
+				}
+				l -= 1;
+			}
+
+			x = a[at(nn, nn, n)].clone();
+			if l == nn {
+				// One root found.
+				wr[nn as uint] = Complex { x0: x + t, x1: zero.clone() };
+				nn -= 1;
+			} else {
+				y = a[at(nn - 1, nn - 1, n)].clone();
+				w = a[at(nn, nn - 1, n)] * a[at(nn - 1, nn, n)];
+				if l == nn - 1 {
+					// Two roots found.
+					p = half * (y - x);
+					q = p * p + w;
+					z = q.abs().sqrt();
+					x = x + t;
+					if q >= zero {
+						z = if p < zero { p - z } else { p + z };
+						let first = x + z;
+						let second = if z != zero { x - w / z } else { first.clone() };
+						wr[(nn - 1) as uint] = Complex { x0: first, x1: zero.clone() };
+						wr[nn as uint] = Complex { x0: second, x1: zero.clone() };
+					} else {
+						wr[(nn - 1) as uint] = Complex { x0: x + p, x1: z.clone() };
+						wr[nn as uint] = Complex { x0: x + p, x1: -z };
+					}
+					nn -= 2;
+				} else {
+					if its == 30 { fail!("Eigenvalues did not converge"); }
+					if its == 10 || its == 20 {
+						// Exceptional shift.
+						t = t + x;
+						for i in range(0, nn + 1) {
+							a[at(i, i, n)] = a[at(i, i, n)] - x;
+						}
+						s = a[at(nn, nn - 1, n)].abs() + a[at(nn - 1, nn - 2, n)].abs();
+						x = exceptional_x * s;
+						y = x.clone();
+						w = -exceptional_w * s * s;
+					}
+					its += 1;
+
+					// Look for two consecutive small subdiagonal elements.
+					let mut m = nn - 2;
+					while m >= l {
+						z = a[at(m, m, n)].clone();
+						r = x - z;
+						s = y - z;
+						p = (r * s - w) / a[at(m + 1, m, n)] + a[at(m, m + 1, n)];
+						q = a[at(m + 1, m + 1, n)] - z - r - s;
+						r = a[at(m + 2, m + 1, n)].clone();
+						s = p.abs() + q.abs() + r.abs();
+						p = p / s;
+						q = q / s;
+						r = r / s;
+						if m == l { break; }
+						let u = a[at(m, m - 1, n)].abs() * (q.abs() + r.abs());
+						let v = p.abs() * (a[at(m - 1, m - 1, n)].abs() + z.abs()
+							+ a[at(m + 1, m + 1, n)].abs());
+						if u <= eps * v { break; }
+						m -= 1;
+					}
+					for i in range(m, nn - 1) {
+						a[at(i + 2, i, n)] = zero.clone();
+						if i != m { a[at(i + 2, i - 1, n)] = zero.clone(); }
+					}
+
+					// Double QR step on rows l to nn and columns m to nn.
+					for k in range(m, nn) {
+						if k != m {
+							p = a[at(k, k - 1, n)].clone();
+							q = a[at(k + 1, k - 1, n)].clone();
+							r = zero.clone();
+							if k + 1 != nn { r = a[at(k + 2, k - 1, n)].clone(); }
+							x = p.abs() + q.abs() + r.abs();
+							if x != zero {
+								p = p / x;
+								q = q / x;
+								r = r / x;
+							}
+						}
+						s = (p * p + q * q + r * r).sqrt();
+						if p < zero { s = -s; }
+						if s == zero { continue; }
+
+						if k == m {
+							if l != m { a[at(k, k - 1, n)] = -a[at(k, k - 1, n)]; }
+						} else {
+							a[at(k, k - 1, n)] = -s * x;
+						}
+						p = p + s;
+						x = p / s;
+						y = q / s;
+						z = r / s;
+						q = q / p;
+						r = r / p;
+						for j in range(k, nn + 1) {
+							p = a[at(k, j, n)] + q * a[at(k + 1, j, n)];
+							if k + 1 != nn {
+								p = p + r * a[at(k + 2, j, n)];
+								a[at(k + 2, j, n)] = a[at(k + 2, j, n)] - p * z;
+							}
+							a[at(k + 1, j, n)] = a[at(k + 1, j, n)] - p * y;
+							a[at(k, j, n)] = a[at(k, j, n)] - p * x;
+						}
+						let mmin = if nn < k + 3 { nn } else { k + 3 };
+						for i in range(l, mmin + 1) {
+							p = x * a[at(i, k, n)] + y * a[at(i, k + 1, n)];
+							if k + 1 != nn {
+								p = p + z * a[at(i, k + 2, n)];
+								a[at(i, k + 2, n)] = a[at(i, k + 2, n)] - p * r;
+							}
+							a[at(i, k + 1, n)] = a[at(i, k + 1, n)] - p * q;
+							a[at(i, k, n)] = a[at(i, k, n)] - p;
+						}
+					}
+				}
+			}
+			if !(l + 1 < nn) { break; }
+		}
+	}
+
+	wr
+}
+
+/// Sorts eigenvalues by descending absolute value.
+/// Of a conjugate pair the one with positive imaginary part comes first.
+fn sort_eigenvalues<T: std::num::Float>(ws: &mut [Complex<T>]) {
+	let n = ws.len();
+	for i in range(0, n) {
+		let mut k = i;
+		for j in range(i + 1, n) {
+			let (a, b) = (ws[j].norm_sq(), ws[k].norm_sq());
+			if a > b || (a == b && ws[j].x1 > ws[k].x1) { k = j; }
+		}
+		ws.swap(i, k);
+	}
+}
+
+/// Computes an eigenvector for an eigenvalue with inverse iteration.
+/// The shifted matrix is factorized once with complex LU
+/// and solved repeatedly starting from a vector of ones.
+/// The matrix is stored row by row with `n` rows.
+/// Returns a vector with unit norm.
+fn inverse_iteration<T: std::num::Float>(
+	a: &[T],
+	n: uint,
+	lambda: &Complex<T>
+) -> ~[Complex<T>] {
+	let zero: T = std::num::zero();
+	let one: T = std::num::one();
+	let eps: T = std::num::Float::epsilon();
+	let mut anorm = zero.clone();
+	for i in range(0, n) {
+		let mut row = zero.clone();
+		for j in range(0, n) { row = row + a[i * n + j].abs(); }
+		if row > anorm { anorm = row; }
+	}
+	if anorm == zero { anorm = one.clone(); }
+
+	// Perturb the shift slightly to keep the factorization nonsingular.
+	let tiny = eps * anorm;
+	let shift = Complex { x0: lambda.x0 + tiny, x1: lambda.x1.clone() };
+	let mut b: ~[Complex<T>] = std::vec::from_fn(n * n, |k| if k / n == k % n {
+		Complex { x0: a[k] - shift.x0, x1: -shift.x1 }
+	} else {
+		Complex { x0: a[k].clone(), x1: zero.clone() }
+	});
+	let mut perm: ~[uint] = std::vec::from_fn(n, |i| i);
+	for k in range(0, n) {
+		let mut p = k;
+		for i in range(k + 1, n) {
+			if b[i * n + k].norm_sq() > b[p * n + k].norm_sq() { p = i; }
+		}
+		if p != k {
+			for j in range(0, n) { b.swap(k * n + j, p * n + j); }
+			perm.swap(k, p);
+		}
+		if b[k * n + k].norm_sq() == zero {
+			b[k * n + k] = Complex { x0: tiny.clone(), x1: zero.clone() };
+		}
+		for i in range(k + 1, n) {
+			let f = b[i * n + k] / b[k * n + k];
+			for j in range(k + 1, n) {
+				b[i * n + j] = b[i * n + j] - f * b[k * n + j];
+			}
+			b[i * n + k] = f;
+		}
+	}
+
+	let mut x: ~[Complex<T>] = std::vec::from_fn(n, |_| Complex {
+		x0: one.clone(),
+		x1: zero.clone()
+	});
+	for _ in range(0, 3) {
+		let mut y: ~[Complex<T>] = perm.iter().map(|&i| x[i].clone()).collect();
+		for i in range(0, n) {
+			for j in range(0, i) { y[i] = y[i] - b[i * n + j] * y[j]; }
+		}
+		for ii in range(0, n) {
+			let i = n - 1 - ii;
+			for j in range(i + 1, n) { y[i] = y[i] - b[i * n + j] * y[j]; }
+			y[i] = y[i] / b[i * n + i];
+		}
+
+		let mut norm = zero.clone();
+		for v in y.iter() { norm = norm + v.norm_sq(); }
+		let norm = norm.sqrt();
+		x = y.iter().map(|v| Complex { x0: v.x0 / norm, x1: v.x1 / norm }).collect();
+	}
+
+	x
+}
+
+impl<
+	T: std::num::Float
+>
+Matrix<T> {
+	/// Computes the eigenvalues of a general real matrix.
+	/// Reduces to Hessenberg form and uses the shifted QR method.
+	/// Complex eigenvalues come in conjugate pairs.
+	/// Returns the eigenvalues sorted by descending absolute value.
+	/// Fails if the matrix is not square.
+	pub fn eigenvalues(&self) -> Vector<Complex<T>> {
+		self.check_square();
+		let n = self.rows;
+		let mut h = self.x.clone();
+		hessenberg_reduce(h.mut_slice(0, n * n), n);
+		let mut ws = hessenberg_eigenvalues(h.mut_slice(0, n * n), n);
+		sort_eigenvalues(ws.mut_slice(0, n));
+		Vector {
+			x: ws
+		}
+	}
+
+	/// Computes the eigenvalues and eigenvectors of a general real matrix.
+	/// The eigenvectors are computed with inverse iteration and have unit norm.
+	/// Returns one eigenvector per eigenvalue, in the same order.
+	/// Fails if the matrix is not square.
+	pub fn eigen(&self) -> (Vector<Complex<T>>, ~[Vector<Complex<T>>]) {
+		let ws = self.eigenvalues();
+		let vs = ws.x.iter().map(|w| Vector {
+			x: inverse_iteration(self.as_slice(), self.rows, w)
+		}).collect();
+		(ws, vs)
+	}
+
+	/// Computes the largest absolute value of the eigenvalues.
+	/// A discrete-time linear system is stable when it is less than one.
+	pub fn spectral_radius(&self) -> T {
+		match self.eigenvalues().x.head_opt() {
+			Some(w) => w.norm(),
+			None => std::num::zero()
+		}
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+Matrix4<T> {
+	/// Computes the eigenvalues of a general real matrix.
+	/// Returns the eigenvalues sorted by descending absolute value.
+	pub fn eigenvalues(&self) -> Vector<Complex<T>> {
+		Matrix::from_matrix4(self).eigenvalues()
+	}
+
+	/// Computes the eigenvalues and eigenvectors of a general real matrix.
+	/// Returns one eigenvector per eigenvalue, in the same order.
+	pub fn eigen(&self) -> (Vector<Complex<T>>, ~[Vector<Complex<T>>]) {
+		Matrix::from_matrix4(self).eigen()
+	}
+
+	/// Computes the largest absolute value of the eigenvalues.
+	/// A discrete-time linear system is stable when it is less than one.
+	pub fn spectral_radius(&self) -> T {
+		Matrix::from_matrix4(self).spectral_radius()
+	}
+}
+
+/// Multiplies a value with itself `n` times by repeated squaring.
+/// Returns `one` when `n` is zero.
+fn pow_by_squaring<T: Mul<T, T>>(mut base: T, mut n: uint, one: T) -> T {
+	let mut res = one;
+	while n > 0 {
+		if n % 2 == 1 { res = res * base; }
+		n /= 2;
+		if n > 0 { base = base * base; }
+	}
+
+	res
+}
+
+/// Raises a value to an integer power by repeated squaring.
+/// Negative powers use the inverse.
+#[inline(always)]
+pub fn powi<T: Mul<T, T> + Inv<T> + std::num::One>(a: T, n: int) -> T {
+	if n < 0 {
+		pow_by_squaring(a.inv(), (-n) as uint, std::num::one())
+	} else {
+		pow_by_squaring(a, n as uint, std::num::one())
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+Matrix<T> {
+	/// Multiplies all elements by a scalar.
+	fn mul_scalar(&self, s: &T) -> Matrix<T> {
+		self.map(|a| *a * *s)
+	}
+
+	/// Computes the maximum absolute row sum.
+	fn norm_inf(&self) -> T {
+		let mut res: T = std::num::zero();
+		for i in range(0, self.rows) {
+			let mut sum: T = std::num::zero();
+			for a in self.row_slice(i).iter() { sum = sum + a.abs(); }
+			if sum > res { res = sum; }
+		}
+
+		res
+	}
+
+	/// Computes the matrix exponential.
+	/// Uses a diagonal Padé approximation of degree 6 with scaling and squaring.
+	/// Fails if the matrix is not square or has elements that are not finite.
+	pub fn expm(&self) -> Matrix<T> {
+		self.check_square();
+		let n = self.rows;
+		let one: T = std::num::one();
+		let half: T = std::num::cast(0.5).unwrap();
+
+		// Scale until the norm is at most one half.
+		let mut norm = self.norm_inf();
+		if !norm.is_finite() {
+			fail!("Matrix exponential of a matrix with elements that are not finite");
+		}
+		let mut factor = one.clone();
+		let mut squarings = 0;
+		while norm > half {
+			norm = norm * half;
+			factor = factor * half;
+			squarings += 1;
+		}
+		let a = self.mul_scalar(&factor);
+
+		let q = 6;
+		let id: Matrix<T> = Matrix::identity(n);
+		let mut c = half.clone();
+		let mut x = a.clone();
+		let mut num = id + a.mul_scalar(&c);
+		let mut den = id - a.mul_scalar(&c);
+		for k in range(2, q + 1) {
+			c = c * std::num::cast(q - k + 1).unwrap()
+				/ std::num::cast(k * (2 * q - k + 1)).unwrap();
+			x = a * x;
+			let term = x.mul_scalar(&c);
+			num = num + term;
+			den = if k % 2 == 0 { den + term } else { den - term };
+		}
+
+		let mut res = num / den;
+		for _ in range(0, squarings) { res = res * res; }
+		res
+	}
+
+	/// Computes the principal square root with the Denman-Beavers iteration.
+	/// The matrix must not have eigenvalues on the closed negative real axis.
+	/// Fails if the iteration does not converge.
+	pub fn sqrtm(&self) -> Matrix<T> {
+		self.check_square();
+		let half: T = std::num::cast(0.5).unwrap();
+		let eps: T = std::num::Float::epsilon();
+		let tol = eps.sqrt();
+		let mut y = self.clone();
+		let mut z: Matrix<T> = Matrix::identity(self.rows);
+		for _ in range(0, 100) {
+			let y_next = (y + z.inv()).mul_scalar(&half);
+			let z_next = (z + y.inv()).mul_scalar(&half);
+			let diff = (y_next - y).norm_sq().sqrt();
+			y = y_next;
+			z = z_next;
+
+			// Convergence is quadratic, so one more step reaches full precision.
+			if diff <= tol * y.norm_sq().sqrt() {
+				return (y + z.inv()).mul_scalar(&half);
+			}
+		}
+
+		fail!("Matrix square root did not converge");
+	}
+
+	/// Computes the principal logarithm with inverse scaling and squaring.
+	/// Takes square roots until the matrix is close to identity
+	/// and sums the series of `2 atanh(Z)` with `Z = (A - I)(A + I)^-1`.
+	/// The matrix must not have eigenvalues on the closed negative real axis.
+	/// Fails if the square roots or the series do not converge.
+	pub fn logm(&self) -> Matrix<T> {
+		self.check_square();
+		let one: T = std::num::one();
+		let two = one + one;
+		let eps: T = std::num::Float::epsilon();
+		let quarter: T = std::num::cast(0.25).unwrap();
+		let id: Matrix<T> = Matrix::identity(self.rows);
+		let mut a = self.clone();
+		let mut factor = two.clone();
+		let mut roots = 0;
+		while (a - id).norm_sq().sqrt() > quarter {
+			if roots == 64 { fail!("Matrix logarithm square roots did not converge"); }
+			roots += 1;
+			a = a.sqrtm();
+			factor = factor * two;
+		}
+
+		let z = (a - id) / (a + id);
+		let z2 = z * z;
+		let mut t = z.clone();
+		let mut sum = z;
+		let mut j = one.clone();
+		for _ in range(0, 100) {
+			t = t * z2;
+			j = j + two;
+			let term = t.mul_scalar(&(one / j));
+			sum = sum + term;
+			if term.norm_sq().sqrt() <= eps * sum.norm_sq().sqrt() {
+				return sum.mul_scalar(&factor);
+			}
+		}
+
+		fail!("Matrix logarithm series did not converge");
+	}
+
+	/// Raises the matrix to an integer power by repeated squaring.
+	/// Negative powers use the inverse.
+	/// Fails if the matrix is not square.
+	pub fn powm(&self, n: int) -> Matrix<T> {
+		self.check_square();
+		let id: Matrix<T> = Matrix::identity(self.rows);
+		if n < 0 {
+			pow_by_squaring(self.inv(), (-n) as uint, id)
+		} else {
+			pow_by_squaring(self.clone(), n as uint, id)
+		}
+	}
+
+	/// Raises the matrix to a real power using `expm(p logm(A))`.
+	/// The matrix must not have eigenvalues on the closed negative real axis.
+	pub fn powm_real(&self, p: T) -> Matrix<T> {
+		self.logm().mul_scalar(&p).expm()
+	}
+}
+
+impl<
+	T: std::num::Float
+>
+Matrix4<T> {
+	/// Computes the matrix exponential.
+	pub fn expm(&self) -> Matrix4<T> {
+		Matrix::from_matrix4(self).expm().to_matrix4()
+	}
+
+	/// Computes the principal square root.
+	pub fn sqrtm(&self) -> Matrix4<T> {
+		Matrix::from_matrix4(self).sqrtm().to_matrix4()
+	}
+
+	/// Computes the principal logarithm.
+	pub fn logm(&self) -> Matrix4<T> {
+		Matrix::from_matrix4(self).logm().to_matrix4()
+	}
+
+	/// Raises the matrix to an integer power.
+	/// Negative powers use the inverse.
+	pub fn powm(&self, n: int) -> Matrix4<T> {
+		powi(self.clone(), n)
+	}
+
+	/// Raises the matrix to a real power.
+	pub fn powm_real(&self, p: T) -> Matrix4<T> {
+		Matrix::from_matrix4(self).powm_real(p).to_matrix4()
+	}
+}
+
+/// Compressed storage of a sparse matrix along one dimension.
+/// Entries of major index `i` are at `indptr[i]` up to `indptr[i + 1]`,
+/// with minor indices sorted in increasing order.
+/// Explicitly stored zeros are kept, but compare equal to missing entries.
+#[deriving(Eq, Clone)]
+struct Compressed<T> {
+	/// Contains the start of each major index, followed by the number of entries.
+	indptr: ~[uint],
+	/// Contains the minor index of each entry.
+	indices: ~[uint],
+	/// Contains the value of each entry.
+	data: ~[T],
+}
+
+impl<
+	T: Add<T, T> + Clone
+>
+Compressed<T> {
+	/// Compresses a list of `(major, minor, value)` entries.
+	/// Duplicate entries are summed.
+	fn from_entries(major: uint, entries: &[(uint, uint, T)]) -> Compressed<T> {
+		let mut keys: ~[(uint, uint, uint)] = entries.iter().enumerate()
+			.map(|(k, &(i, j, _))| (i, j, k)).collect();
+		keys.sort();
+
+		let mut indptr = std::vec::from_elem(major + 1, 0u);
+		let mut indices: ~[uint] = ~[];
+		let mut data: ~[T] = ~[];
+		let mut last = None;
+		for &(i, j, k) in keys.iter() {
+			let (_, _, ref val) = entries[k];
+			if last == Some((i, j)) {
+				let n = data.len() - 1;
+				data[n] = data[n] + *val;
+			} else {
+				indices.push(j);
+				data.push(val.clone());
+				indptr[i + 1] += 1;
+				last = Some((i, j));
+			}
+		}
+		for i in range(0, major) { indptr[i + 1] += indptr[i]; }
+
+		Compressed {
+			indptr: indptr,
+			indices: indices,
+			data: data
+		}
+	}
+}
+
+impl<T>
+Compressed<T> {
+	/// Returns the number of major indices.
+	fn major(&self) -> uint {
+		self.indptr.len() - 1
+	}
+
+	/// Returns the position of an entry, if it is stored.
+	fn find(&self, i: uint, j: uint) -> Option<uint> {
+		let (mut lo, mut hi) = (self.indptr[i], self.indptr[i + 1]);
+		while lo < hi {
+			let mid = (lo + hi) / 2;
+			if self.indices[mid] == j { return Some(mid); }
+			if self.indices[mid] < j { lo = mid + 1; } else { hi = mid; }
+		}
+
+		None
+	}
+
+	/// Fails if the storage is not consistent with the shape.
+	fn check(&self, minor: uint) {
+		let n = self.indptr.len();
+		if self.indptr[0] != 0 || self.indptr[n - 1] != self.indices.len()
+		|| self.indices.len() != self.data.len() {
+			fail!("Sparse matrix storage is inconsistent");
+		}
+		for i in range(0, n - 1) {
+			let (start, end) = (self.indptr[i], self.indptr[i + 1]);
+			if start > end { fail!("Sparse matrix storage is inconsistent"); }
+			for k in range(start, end) {
+				if self.indices[k] >= minor || (k > start && self.indices[k - 1] >= self.indices[k]) {
+					fail!("Sparse matrix storage is inconsistent");
+				}
+			}
+		}
+	}
+
+	/// Creates a new storage by applying a function to each value.
+	fn map<U>(&self, f: |&T| -> U) -> Compressed<U> {
+		Compressed {
+			indptr: self.indptr.clone(),
+			indices: self.indices.clone(),
+			data: self.data.iter().map(f).collect()
+		}
+	}
+}
+
+impl<
+	T: std::num::Zero
+>
+Compressed<T> {
+	/// Merges the entries of two storages with the same pattern of major indices.
+	/// A missing entry is passed as zero to the function.
+	fn zip_with<U>(&self, rhs: &Compressed<T>, f: |&T, &T| -> U) -> Compressed<U> {
+		let zero: T = std::num::zero();
+		let mut indptr = ~[0u];
+		let mut indices: ~[uint] = ~[];
+		let mut data: ~[U] = ~[];
+		for i in range(0, self.major()) {
+			let (mut a, a_end) = (self.indptr[i], self.indptr[i + 1]);
+			let (mut b, b_end) = (rhs.indptr[i], rhs.indptr[i + 1]);
+			while a < a_end || b < b_end {
+				let ja = if a < a_end { self.indices[a] } else { std::uint::max_value };
+				let jb = if b < b_end { rhs.indices[b] } else { std::uint::max_value };
+				if ja == jb {
+					indices.push(ja);
+					data.push(f(&self.data[a], &rhs.data[b]));
+					a += 1;
+					b += 1;
+				} else if ja < jb {
+					indices.push(ja);
+					data.push(f(&self.data[a], &zero));
+					a += 1;
+				} else {
+					indices.push(jb);
+					data.push(f(&zero, &rhs.data[b]));
+					b += 1;
+				}
+			}
+			indptr.push(indices.len());
+		}
+
+		Compressed {
+			indptr: indptr,
+			indices: indices,
+			data: data
+		}
+	}
+}
+
+impl<
+	T: std::num::Zero + Clone
+>
+Compressed<T> {
+	/// Compresses the same entries along the other dimension.
+	fn transpose(&self, minor: uint) -> Compressed<T> {
+		let nnz = self.indices.len();
+		let mut indptr = std::vec::from_elem(minor + 1, 0u);
+		for &j in self.indices.iter() { indptr[j + 1] += 1; }
+		for j in range(0, minor) { indptr[j + 1] += indptr[j]; }
+
+		let mut next = indptr.clone();
+		let mut indices = std::vec::from_elem(nnz, 0u);
+		let mut data: ~[T] = std::vec::from_elem(nnz, std::num::zero());
+		for i in range(0, self.major()) {
+			for k in range(self.indptr[i], self.indptr[i + 1]) {
+				let j = self.indices[k];
+				indices[next[j]] = i;
+				data[next[j]] = self.data[k].clone();
+				next[j] += 1;
+			}
+		}
+
+		Compressed {
+			indptr: indptr,
+			indices: indices,
+			data: data
+		}
+	}
+
+	/// Compresses the non-zero elements of a dense matrix row by row.
+	fn from_matrix(m: &Matrix<T>) -> Compressed<T> {
+		let mut indptr = ~[0u];
+		let mut indices: ~[uint] = ~[];
+		let mut data: ~[T] = ~[];
+		for i in range(0, m.rows) {
+			for (j, a) in m.row_slice(i).iter().enumerate() {
+				if a.is_zero() { continue; }
+				indices.push(j);
+				data.push(a.clone());
+			}
+			indptr.push(indices.len());
+		}
+
+		Compressed {
+			indptr: indptr,
+			indices: indices,
+			data: data
+		}
+	}
+
+	/// Returns the element at major and minor index, or zero if it is not stored.
+	fn get(&self, i: uint, j: uint) -> T {
+		match self.find(i, j) {
+			Some(k) => self.data[k].clone(),
+			None => std::num::zero()
+		}
+	}
+}
+
+/// A CooMatrix stores a sparse matrix as a list of coordinates and values.
+/// It is commonly used to build sparse matrices before converting to CSR or CSC.
+/// Duplicate entries are summed when converting.
+#[deriving(Eq, Clone)]
+pub struct CooMatrix<T> {
+	/// Number of rows.
+	rows: uint,
+	/// Number of columns.
+	cols: uint,
+	/// Contains the row, column and value of each entry.
+	entries: ~[(uint, uint, T)],
+}
+
+/// Creates a new empty sparse matrix in coordinate format.
+#[inline(always)]
+pub fn coo_matrix<T>(rows: uint, cols: uint) -> CooMatrix<T> {
+	CooMatrix::new(rows, cols)
+}
+
+impl<T>
+CooMatrix<T> {
+	/// Creates a new empty sparse matrix in coordinate format.
+	pub fn new(rows: uint, cols: uint) -> CooMatrix<T> {
+		CooMatrix {
+			rows: rows,
+			cols: cols,
+			entries: ~[]
+		}
+	}
+
+	/// Returns the number of rows.
+	pub fn rows(&self) -> uint {
+		self.rows
+	}
+
+	/// Returns the number of columns.
+	pub fn cols(&self) -> uint {
+		self.cols
+	}
+
+	/// Returns the number of rows and columns.
+	pub fn shape(&self) -> (uint, uint) {
+		(self.rows, self.cols)
+	}
+
+	/// Returns the number of stored entries, including duplicates.
+	pub fn nnz(&self) -> uint {
+		self.entries.len()
+	}
+
+	/// Adds an entry at zero-based row and column.
+	/// Fails if the index is out of bounds.
+	pub fn push(&mut self, row: uint, col: uint, val: T) {
+		if row >= self.rows || col >= self.cols { fail!("Matrix index out of bounds"); }
+		self.entries.push((row, col, val));
+	}
+}
+
+impl<
+	T: Add<T, T> + Clone
+>
+CooMatrix<T> {
+	/// Converts to compressed sparse row format.
+	pub fn to_csr(&self) -> CsrMatrix<T> {
+		CsrMatrix {
+			rows: self.rows,
+			cols: self.cols,
+			x: Compressed::from_entries(self.rows, self.entries.slice(0, self.entries.len()))
+		}
+	}
+
+	/// Converts to compressed sparse column format.
+	pub fn to_csc(&self) -> CscMatrix<T> {
+		let entries: ~[(uint, uint, T)] = self.entries.iter()
+			.map(|&(i, j, ref val)| (j, i, val.clone())).collect();
+		CscMatrix {
+			rows: self.rows,
+			cols: self.cols,
+			x: Compressed::from_entries(self.cols, entries.slice(0, entries.len()))
+		}
+	}
+}
+
+impl<
+	T: Add<T, T> + std::num::Zero + Clone
+>
+CooMatrix<T> {
+	/// Converts to a dense matrix.
+	pub fn to_matrix(&self) -> Matrix<T> {
+		let mut m: Matrix<T> = Matrix::zeros(self.rows, self.cols);
+		for &(i, j, ref val) in self.entries.iter() {
+			let sum = *m.elem(i, j) + *val;
+			m.set(i, j, sum);
+		}
+
+		m
+	}
+}
+
+/// A CsrMatrix stores a sparse matrix in compressed sparse row format.
+/// It is commonly used for fast matrix vector products.
+#[deriving(Clone)]
+pub struct CsrMatrix<T> {
+	/// Number of rows.
+	rows: uint,
+	/// Number of columns.
+	cols: uint,
+	/// Contains the entries compressed by row.
+	x: Compressed<T>,
+}
+
+/// Creates a new sparse matrix in compressed sparse row format.
+#[inline(always)]
+pub fn csr_matrix<T>(
+	rows: uint,
+	cols: uint,
+	indptr: ~[uint],
+	indices: ~[uint],
+	data: ~[T]
+) -> CsrMatrix<T> {
+	CsrMatrix::new(rows, cols, indptr, indices, data)
+}
+
+impl<T>
+CsrMatrix<T> {
+	/// Creates a new sparse matrix in compressed sparse row format.
+	/// Column indices must be sorted within each row.
+	/// Fails if the storage does not match the shape.
+	pub fn new(
+		rows: uint,
+		cols: uint,
+		indptr: ~[uint],
+		indices: ~[uint],
+		data: ~[T]
+	) -> CsrMatrix<T> {
+		if indptr.len() != rows + 1 { fail!("Sparse matrix storage is inconsistent"); }
+		let x = Compressed {
+			indptr: indptr,
+			indices: indices,
+			data: data
+		};
+		x.check(cols);
+		CsrMatrix {
+			rows: rows,
+			cols: cols,
+			x: x
+		}
+	}
+
+	/// Returns the number of rows.
+	pub fn rows(&self) -> uint {
+		self.rows
+	}
+
+	/// Returns the number of columns.
+	pub fn cols(&self) -> uint {
+		self.cols
+	}
+
+	/// Returns the number of rows and columns.
+	pub fn shape(&self) -> (uint, uint) {
+		(self.rows, self.cols)
+	}
+
+	/// Returns the number of stored entries.
+	pub fn nnz(&self) -> uint {
+		self.x.data.len()
+	}
+
+	/// Returns the start of each row, followed by the number of entries.
+	pub fn indptr<'a>(&'a self) -> &'a [uint] {
+		self.x.indptr.slice(0, self.x.indptr.len())
+	}
+
+	/// Returns the column index of each entry.
+	pub fn indices<'a>(&'a self) -> &'a [uint] {
+		self.x.indices.slice(0, self.x.indices.len())
+	}
+
+	/// Returns the value of each entry.
+	pub fn data<'a>(&'a self) -> &'a [T] {
+		self.x.data.slice(0, self.x.data.len())
+	}
+
+	/// Fails if the matrices do not have the same shape.
+	fn check_shape(&self, other: &CsrMatrix<T>) {
+		if self.rows != other.rows || self.cols != other.cols {
+			fail!("Matrix shapes do not match");
+		}
+	}
+}
+
+impl<
+	T: std::num::Zero + Clone
+>
+CsrMatrix<T> {
+	/// Creates a new sparse matrix from the non-zero elements of a dense matrix.
+	pub fn from_matrix(m: &Matrix<T>) -> CsrMatrix<T> {
+		CsrMatrix {
+			rows: m.rows,
+			cols: m.cols,
+			x: Compressed::from_matrix(m)
+		}
+	}
+
+	/// Returns the element at zero-based row and column.
+	/// Elements that are not stored are zero.
+	pub fn get(&self, row: uint, col: uint) -> T {
+		if row >= self.rows || col >= self.cols { fail!("Matrix index out of bounds"); }
+		self.x.get(row, col)
+	}
+
+	/// Converts to a dense matrix.
+	pub fn to_matrix(&self) -> Matrix<T> {
+		let mut m: Matrix<T> = Matrix::zeros(self.rows, self.cols);
+		for i in range(0, self.rows) {
+			for k in range(self.x.indptr[i], self.x.indptr[i + 1]) {
+				m.set(i, self.x.indices[k], self.x.data[k].clone());
+			}
+		}
+
+		m
+	}
+
+	/// Converts to coordinate format.
+	pub fn to_coo(&self) -> CooMatrix<T> {
+		let mut res = CooMatrix::new(self.rows, self.cols);
+		for i in range(0, self.rows) {
+			for k in range(self.x.indptr[i], self.x.indptr[i + 1]) {
+				res.push(i, self.x.indices[k], self.x.data[k].clone());
+			}
+		}
+
+		res
+	}
+
+	/// Converts to compressed sparse column format.
+	pub fn to_csc(&self) -> CscMatrix<T> {
+		CscMatrix {
+			rows: self.rows,
+			cols: self.cols,
+			x: self.x.transpose(self.cols)
+		}
+	}
+
+	/// Swaps rows and columns.
+	pub fn transpose(&self) -> CsrMatrix<T> {
+		CsrMatrix {
+			rows: self.cols,
+			cols: self.rows,
+			x: self.x.transpose(self.cols)
+		}
+	}
+}
+
+impl<
+	T: Add<T, T> + Mul<T, T> + std::num::Zero
+>
+CsrMatrix<T> {
+	/// Multiplies a vector by the matrix.
+	/// Fails if the length does not match the number of columns.
+	pub fn transform(&self, v: &Vector<T>) -> Vector<T> {
+		if v.len() != self.cols { fail!("Vector length does not match matrix"); }
+		Vector {
+			x: std::vec::from_fn(self.rows, |i| {
+				let mut res: T = std::num::zero();
+				for k in range(self.x.indptr[i], self.x.indptr[i + 1]) {
+					res = res + self.x.data[k] * v.x[self.x.indices[k]];
+				}
+				res
+			})
+		}
+	}
+}
+
+impl<
+	T: Add<T, T> + std::num::Zero
+>
+Add<CsrMatrix<T>, CsrMatrix<T>> 
+for CsrMatrix<T> {
+	fn add(&self, rhs: &CsrMatrix<T>) -> CsrMatrix<T> {
+		self.check_shape(rhs);
+		CsrMatrix {
+			rows: self.rows,
+			cols: self.cols,
+			x: self.x.zip_with(&rhs.x, |a, b| *a + *b)
+		}
+	}
+}
+
+impl<
+	T: Sub<T, T> + std::num::Zero
+>
+Sub<CsrMatrix<T>, CsrMatrix<T>> 
+for CsrMatrix<T> {
+	fn sub(&self, rhs: &CsrMatrix<T>) -> CsrMatrix<T> {
+		self.check_shape(rhs);
+		CsrMatrix {
+			rows: self.rows,
+			cols: self.cols,
+			x: self.x.zip_with(&rhs.x, |a, b| *a - *b)
+		}
+	}
+}
+
+impl<
+	T: Neg<T>
+>
+Neg<CsrMatrix<T>> 
+for CsrMatrix<T> {
+	fn neg(&self) -> CsrMatrix<T> {
+		CsrMatrix {
+			rows: self.rows,
+			cols: self.cols,
+			x: self.x.map(|a| -*a)
+		}
+	}
+}
+
+impl<
+	T: Add<T, T> + Mul<T, T> + std::num::Zero
+>
+NormSq<T> 
+for CsrMatrix<T> {
+	fn norm_sq(&self) -> T {
+		let mut res: T = std::num::zero();
+		for a in self.x.data.iter() { res = res + *a * *a; }
+		res
+	}
+}
+
+impl<
+	T: Eps + std::num::Zero
+>
+Eps 
+for CsrMatrix<T> {
+	fn close_eps(&self, other: &CsrMatrix<T>, eps: f64) -> bool {
+		self.rows == other.rows && self.cols == other.cols
+		&& self.x.zip_with(&other.x, |a, b| a.close_eps(b, eps)).data.iter().all(|&x| x)
 	}
 }
 
 impl<
-	T: Sub<T, T>
+	T: Eq + std::num::Zero
 >
-Sub<Matrix4<T>, Matrix4<T>> 
-for Matrix4<T> {
-	fn sub(&self, rhs: &Matrix4<T>) -> Matrix4<T> {
-		Matrix4 {
-			m11: self.m11 - rhs.m11,
-			m12: self.m12 - rhs.m12,
-			m13: self.m13 - rhs.m13,
-			m14: self.m14 - rhs.m14,
+Eq for CsrMatrix<T> {
+	fn eq(&self, other: &CsrMatrix<T>) -> bool {
+		self.rows == other.rows && self.cols == other.cols
+		&& self.x.zip_with(&other.x, |a, b| a == b).data.iter().all(|&x| x)
+	}
+}
 
-			m21: self.m21 - rhs.m21,
-			m22: self.m22 - rhs.m22,
-			m23: self.m23 - rhs.m23,
-			m24: self.m24 - rhs.m24,
+/// A CscMatrix stores a sparse matrix in compressed sparse column format.
+/// It is commonly used when accessing columns, for example in factorizations.
+#[deriving(Clone)]
+pub struct CscMatrix<T> {
+	/// Number of rows.
+	rows: uint,
+	/// Number of columns.
+	cols: uint,
+	/// Contains the entries compressed by column.
+	x: Compressed<T>,
+}
 
-			m31: self.m31 - rhs.m31,
-			m32: self.m32 - rhs.m32,
-			m33: self.m33 - rhs.m33,
-			m34: self.m34 - rhs.m34,
+/// Creates a new sparse matrix in compressed sparse column format.
+#[inline(always)]
+pub fn csc_matrix<T>(
+	rows: uint,
+	cols: uint,
+	indptr: ~[uint],
+	indices: ~[uint],
+	data: ~[T]
+) -> CscMatrix<T> {
+	CscMatrix::new(rows, cols, indptr, indices, data)
+}
 
-			m41: self.m41 - rhs.m41,
-			m42: self.m42 - rhs.m42,
-			m43: self.m43 - rhs.m43,
-			m44: self.m44 - rhs.m44
+impl<T>
+CscMatrix<T> {
+	/// Creates a new sparse matrix in compressed sparse column format.
+	/// Row indices must be sorted within each column.
+	/// Fails if the storage does not match the shape.
+	pub fn new(
+		rows: uint,
+		cols: uint,
+		indptr: ~[uint],
+		indices: ~[uint],
+		data: ~[T]
+	) -> CscMatrix<T> {
+		if indptr.len() != cols + 1 { fail!("Sparse matrix storage is inconsistent"); }
+		let x = Compressed {
+			indptr: indptr,
+			indices: indices,
+			data: data
+		};
+		x.check(rows);
+		CscMatrix {
+			rows: rows,
+			cols: cols,
+			x: x
 		}
 	}
-}
 
-impl<
-	T: Mul<T, T> + Add<T, T>
->
-Mul<Matrix4<T>, Matrix4<T>> 
-for Matrix4<T> {
-	fn mul(&self, rhs: &Matrix4<T>) -> Matrix4<T> {
-		Matrix4 {
-			m11: self.m11*rhs.m11
-				+self.m12*rhs.m21
-				+self.m13*rhs.m31
-				+self.m14*rhs.m41,
-			m12: self.m11*rhs.m12
-				+self.m12*rhs.m22
-				+self.m13*rhs.m32
-				+self.m14*rhs.m42,
-			m13: self.m11*rhs.m13
-				+self.m12*rhs.m23
-				+self.m13*rhs.m33
-				+self.m14*rhs.m43,
-			m14: self.m11*rhs.m14
-				+self.m12*rhs.m24
-				+self.m13*rhs.m34
-				+self.m14*rhs.m44,
+	/// Returns the number of rows.
+	pub fn rows(&self) -> uint {
+		self.rows
+	}
 
-			m21: self.m21*rhs.m11
-				+self.m22*rhs.m21
-				+self.m23*rhs.m31
-				+self.m24*rhs.m41,
-			m22: self.m21*rhs.m12
-				+self.m22*rhs.m22
-				+self.m23*rhs.m32
-				+self.m24*rhs.m42,
-			m23: self.m21*rhs.m13
-				+self.m22*rhs.m23
-				+self.m23*rhs.m33
-				+self.m24*rhs.m43,
-			m24: self.m21*rhs.m14
-				+self.m22*rhs.m24
-				+self.m23*rhs.m34
-				+self.m24*rhs.m44,
-		
-			m31: self.m31*rhs.m11
-				+self.m32*rhs.m21
-				+self.m33*rhs.m31
-				+self.m34*rhs.m41,
-			m32: self.m31*rhs.m12
-				+self.m32*rhs.m22
-				+self.m33*rhs.m32
-				+self.m34*rhs.m42,
-			m33: self.m31*rhs.m13
-				+self.m32*rhs.m23
-				+self.m33*rhs.m33
-				+self.m34*rhs.m43,
-			m34: self.m31*rhs.m14
-				+self.m32*rhs.m24
-				+self.m33*rhs.m34
-				+self.m34*rhs.m44,
+	/// Returns the number of columns.
+	pub fn cols(&self) -> uint {
+		self.cols
+	}
 
-			m41: self.m41*rhs.m11
-				+self.m42*rhs.m21
-				+self.m43*rhs.m31
-				+self.m44*rhs.m41,
-			m42: self.m41*rhs.m12
-				+self.m42*rhs.m22
-				+self.m43*rhs.m32
-				+self.m44*rhs.m42,
-			m43: self.m41*rhs.m13
-				+self.m42*rhs.m23
-				+self.m43*rhs.m33
-				+self.m44*rhs.m43,
-			m44: self.m41*rhs.m14
-				+self.m42*rhs.m24
-				+self.m43*rhs.m34
-				+self.m44*rhs.m44
-		}
+	/// Returns the number of rows and columns.
+	pub fn shape(&self) -> (uint, uint) {
+		(self.rows, self.cols)
 	}
-}
 
-impl<
-	T: Neg<T>
->
-Neg<Matrix4<T>> 
-for Matrix4<T> {
-	fn neg(&self) -> Matrix4<T> {
-		Matrix4 {
-			m11: -self.m11, 
-			m12: -self.m12, 
-			m13: -self.m13, 
-			m14: -self.m14,
+	/// Returns the number of stored entries.
+	pub fn nnz(&self) -> uint {
+		self.x.data.len()
+	}
 
-			m21: -self.m21, 
-			m22: -self.m22, 
-			m23: -self.m23, 
-			m24: -self.m24,
-			
-			m31: -self.m31, 
-			m32: -self.m32, 
-			m33: -self.m33, 
-			m34: -self.m34,
+	/// Returns the start of each column, followed by the number of entries.
+	pub fn indptr<'a>(&'a self) -> &'a [uint] {
+		self.x.indptr.slice(0, self.x.indptr.len())
+	}
 
-			m41: -self.m41, 
-			m42: -self.m42, 
-			m43: -self.m43, 
-			m44: -self.m44
+	/// Returns the row index of each entry.
+	pub fn indices<'a>(&'a self) -> &'a [uint] {
+		self.x.indices.slice(0, self.x.indices.len())
+	}
+
+	/// Returns the value of each entry.
+	pub fn data<'a>(&'a self) -> &'a [T] {
+		self.x.data.slice(0, self.x.data.len())
+	}
+
+	/// Fails if the matrices do not have the same shape.
+	fn check_shape(&self, other: &CscMatrix<T>) {
+		if self.rows != other.rows || self.cols != other.cols {
+			fail!("Matrix shapes do not match");
 		}
 	}
 }
 
 impl<
-	T: Add<T, T> + Mul<T, T> + Sub<T, T>
+	T: std::num::Zero + Clone
 >
-Det<T> 
-for Matrix4<T> {
-	fn det(&self) -> T {
-		self.m11*self.m22*self.m33*self.m44
-		+self.m11*self.m23*self.m34*self.m42
-		+self.m11*self.m24*self.m32*self.m43
+CscMatrix<T> {
+	/// Creates a new sparse matrix from the non-zero elements of a dense matrix.
+	pub fn from_matrix(m: &Matrix<T>) -> CscMatrix<T> {
+		CscMatrix {
+			rows: m.rows,
+			cols: m.cols,
+			x: Compressed::from_matrix(m).transpose(m.cols)
+		}
+	}
 
-		+self.m12*self.m21*self.m34*self.m43
-		+self.m12*self.m23*self.m31*self.m44
-		+self.m12*self.m24*self.m33*self.m41
+	/// Returns the element at zero-based row and column.
+	/// Elements that are not stored are zero.
+	pub fn get(&self, row: uint, col: uint) -> T {
+		if row >= self.rows || col >= self.cols { fail!("Matrix index out of bounds"); }
+		self.x.get(col, row)
+	}
 
-		+self.m13*self.m21*self.m32*self.m44
-		+self.m13*self.m22*self.m34*self.m41
-		+self.m13*self.m24*self.m31*self.m42
+	/// Converts to a dense matrix.
+	pub fn to_matrix(&self) -> Matrix<T> {
+		let mut m: Matrix<T> = Matrix::zeros(self.rows, self.cols);
+		for j in range(0, self.cols) {
+			for k in range(self.x.indptr[j], self.x.indptr[j + 1]) {
+				m.set(self.x.indices[k], j, self.x.data[k].clone());
+			}
+		}
 
-		+self.m14*self.m21*self.m33*self.m42
-		+self.m14*self.m22*self.m31*self.m43
-		+self.m14*self.m23*self.m32*self.m41
+		m
+	}
 
-		-self.m11*self.m22*self.m34*self.m43
-		-self.m11*self.m23*self.m32*self.m44
-		-self.m11*self.m24*self.m33*self.m42
+	/// Converts to coordinate format.
+	pub fn to_coo(&self) -> CooMatrix<T> {
+		let mut res = CooMatrix::new(self.rows, self.cols);
+		for j in range(0, self.cols) {
+			for k in range(self.x.indptr[j], self.x.indptr[j + 1]) {
+				res.push(self.x.indices[k], j, self.x.data[k].clone());
+			}
+		}
 
-		-self.m12*self.m21*self.m33*self.m44
-		-self.m12*self.m23*self.m34*self.m41
-		-self.m12*self.m24*self.m31*self.m43
+		res
+	}
 
-		-self.m13*self.m21*self.m34*self.m42
-		-self.m13*self.m22*self.m31*self.m44
-		-self.m13*self.m24*self.m32*self.m41
+	/// Converts to compressed sparse row format.
+	pub fn to_csr(&self) -> CsrMatrix<T> {
+		CsrMatrix {
+			rows: self.rows,
+			cols: self.cols,
+			x: self.x.transpose(self.rows)
+		}
+	}
 
-		-self.m14*self.m21*self.m32*self.m43
-		-self.m14*self.m22*self.m33*self.m41
-		-self.m14*self.m23*self.m31*self.m42
+	/// Swaps rows and columns.
+	pub fn transpose(&self) -> CscMatrix<T> {
+		CscMatrix {
+			rows: self.cols,
+			cols: self.rows,
+			x: self.x.transpose(self.rows)
+		}
 	}
 }
 
 impl<
-	T: Add<T, T> + Mul<T, T> + Sub<T, T> + Div<T, T>
+	T: Add<T, T> + Mul<T, T> + std::num::Zero
 >
-Inv<Matrix4<T>> 
-for Matrix4<T> {
-	fn inv(&self) -> Matrix4<T> {
-		let det = self.det();
-		Matrix4 {
-			m11: (self.m22*self.m33*self.m44
-				+self.m23*self.m34*self.m42
-				+self.m24*self.m32*self.m43
-				-self.m22*self.m34*self.m43
-				-self.m23*self.m32*self.m44
-				-self.m24*self.m33*self.m42) / det,
-			m12: (self.m12*self.m34*self.m43
-				+self.m13*self.m32*self.m44
-				+self.m14*self.m33*self.m42
-				-self.m12*self.m33*self.m44
-				-self.m13*self.m34*self.m42
-				-self.m14*self.m32*self.m43) / det,
-			m13: (self.m12*self.m23*self.m44
-				+self.m13*self.m24*self.m42
-				+self.m14*self.m22*self.m43
-				-self.m12*self.m24*self.m43
-				-self.m13*self.m22*self.m44
-				-self.m14*self.m23*self.m42) / det,
-			m14: (self.m12*self.m24*self.m33
-				+self.m13*self.m22*self.m34
-				+self.m14*self.m23*self.m32
-				-self.m12*self.m23*self.m34
-				-self.m13*self.m24*self.m32
-				-self.m14*self.m22*self.m33) / det,
-
-			m21: (self.m21*self.m34*self.m43
-				+self.m23*self.m31*self.m44
-				+self.m24*self.m33*self.m41
-				-self.m21*self.m33*self.m44
-				-self.m23*self.m34*self.m41
-				-self.m24*self.m31*self.m43) / det,
-			m22: (self.m11*self.m33*self.m44
-				+self.m13*self.m34*self.m41
-				+self.m14*self.m31*self.m43
-				-self.m11*self.m34*self.m43
-				-self.m13*self.m31*self.m44
-				-self.m14*self.m33*self.m41) / det,
-			m23: (self.m11*self.m24*self.m43
-				+self.m13*self.m21*self.m44
-				+self.m14*self.m23*self.m41
-				-self.m11*self.m23*self.m44
-				-self.m13*self.m24*self.m41
-				-self.m14*self.m21*self.m43) / det,
-			m24: (self.m11*self.m23*self.m34
-				+self.m13*self.m24*self.m31
-				+self.m14*self.m21*self.m33
-				-self.m11*self.m24*self.m33
-				-self.m13*self.m21*self.m34
-				-self.m14*self.m23*self.m31) / det,
-
-			m31: (self.m21*self.m32*self.m44
-				+self.m22*self.m34*self.m41
-				+self.m24*self.m31*self.m42
-				-self.m21*self.m34*self.m42
-				-self.m22*self.m31*self.m44
-				-self.m24*self.m32*self.m41) / det,
-			m32: (self.m11*self.m34*self.m42
-				+self.m12*self.m31*self.m44
-				+self.m14*self.m32*self.m41
-				-self.m11*self.m32*self.m44
-				-self.m12*self.m34*self.m41
-				-self.m14*self.m31*self.m42) / det,
-			m33: (self.m11*self.m22*self.m44
-				+self.m12*self.m24*self.m41
-				+self.m14*self.m21*self.m42
-				-self.m11*self.m24*self.m42
-				-self.m12*self.m21*self.m44
-				-self.m14*self.m22*self.m41) / det,
-			m34: (self.m11*self.m24*self.m32
-				+self.m12*self.m21*self.m34
-				+self.m14*self.m22*self.m31
-				-self.m11*self.m22*self.m34
-				-self.m12*self.m24*self.m31
-				-self.m14*self.m21*self.m32) / det,
-
-			m41: (self.m21*self.m33*self.m42
-				+self.m22*self.m31*self.m43
-				+self.m23*self.m32*self.m41
-				-self.m21*self.m32*self.m43
-				-self.m22*self.m33*self.m41
-				-self.m23*self.m31*self.m42) / det,
-			m42: (self.m11*self.m32*self.m43
-				+self.m12*self.m33*self.m41
-				+self.m13*self.m31*self.m42
-				-self.m11*self.m33*self.m42
-				-self.m12*self.m31*self.m43
-				-self.m13*self.m32*self.m41) / det,
-			m43: (self.m11*self.m23*self.m42
-				+self.m12*self.m21*self.m43
-				+self.m13*self.m22*self.m41
-				-self.m11*self.m22*self.m43
-				-self.m12*self.m23*self.m41
-				-self.m13*self.m21*self.m42) / det,
-			m44: (self.m11*self.m22*self.m33
-				+self.m12*self.m23*self.m31
-				+self.m13*self.m21*self.m32
-				-self.m11*self.m23*self.m32
-				-self.m12*self.m21*self.m33
-				-self.m13*self.m22*self.m31) / det,
+CscMatrix<T> {
+	/// Multiplies a vector by the matrix.
+	/// Fails if the length does not match the number of columns.
+	pub fn transform(&self, v: &Vector<T>) -> Vector<T> {
+		if v.len() != self.cols { fail!("Vector length does not match matrix"); }
+		let mut res: ~[T] = std::vec::from_fn(self.rows, |_| std::num::zero());
+		for j in range(0, self.cols) {
+			for k in range(self.x.indptr[j], self.x.indptr[j + 1]) {
+				let i = self.x.indices[k];
+				res[i] = res[i] + self.x.data[k] * v.x[j];
+			}
+		}
+
+		Vector {
+			x: res
 		}
 	}
 }
 
 impl<
-	T: Add<T, T> + Mul<T, T> + Sub<T, T> + Div<T, T>
+	T: Add<T, T> + std::num::Zero
 >
-Div<Matrix4<T>, Matrix4<T>> 
-for Matrix4<T> {
-	fn div(&self, rhs: &Matrix4<T>) -> Matrix4<T> {
-		let inv = rhs.inv();
-		self * inv
+Add<CscMatrix<T>, CscMatrix<T>> 
+for CscMatrix<T> {
+	fn add(&self, rhs: &CscMatrix<T>) -> CscMatrix<T> {
+		self.check_shape(rhs);
+		CscMatrix {
+			rows: self.rows,
+			cols: self.cols,
+			x: self.x.zip_with(&rhs.x, |a, b| *a + *b)
+		}
 	}
 }
 
 impl<
-	T: Eps
+	T: Sub<T, T> + std::num::Zero
 >
-Eps 
-for Matrix4<T> {
-	fn close_eps(&self, other: &Matrix4<T>, eps: f64) -> bool {
-		self.m11.close_eps(&other.m11, eps)
-		&& self.m12.close_eps(&other.m12, eps)
-		&& self.m13.close_eps(&other.m13, eps)
-		&& self.m14.close_eps(&other.m14, eps)
-
-		&& self.m21.close_eps(&other.m21, eps)
-		&& self.m22.close_eps(&other.m22, eps)
-		&& self.m23.close_eps(&other.m23, eps)
-		&& self.m24.close_eps(&other.m24, eps)
-
-		&& self.m31.close_eps(&other.m31, eps)
-		&& self.m32.close_eps(&other.m32, eps)
-		&& self.m33.close_eps(&other.m33, eps)
-		&& self.m34.close_eps(&other.m34, eps)
-
-		&& self.m41.close_eps(&other.m41, eps)
-		&& self.m42.close_eps(&other.m42, eps)
-		&& self.m43.close_eps(&other.m43, eps)
-		&& self.m44.close_eps(&other.m44, eps)
+Sub<CscMatrix<T>, CscMatrix<T>> 
+for CscMatrix<T> {
+	fn sub(&self, rhs: &CscMatrix<T>) -> CscMatrix<T> {
+		self.check_shape(rhs);
+		CscMatrix {
+			rows: self.rows,
+			cols: self.cols,
+			x: self.x.zip_with(&rhs.x, |a, b| *a - *b)
+		}
 	}
 }
 
 impl<
-	T: std::num::Zero + std::num::One
+	T: Neg<T>
 >
-std::num::One 
-for Matrix4<T> {
-	fn one() -> Matrix4<T> {
-		Matrix4 {
-			m11: std::num::one(),
-			m12: std::num::zero(),
-			m13: std::num::zero(),
-			m14: std::num::zero(),
-
-			m21: std::num::zero(),
-			m22: std::num::one(),
-			m23: std::num::zero(),
-			m24: std::num::zero(),
-
-			m31: std::num::zero(),
-			m32: std::num::zero(),
-			m33: std::num::one(),
-			m34: std::num::zero(),
-
-			m41: std::num::zero(),
-			m42: std::num::zero(),
-			m43: std::num::zero(),
-			m44: std::num::one()
+Neg<CscMatrix<T>> 
+for CscMatrix<T> {
+	fn neg(&self) -> CscMatrix<T> {
+		CscMatrix {
+			rows: self.rows,
+			cols: self.cols,
+			x: self.x.map(|a| -*a)
 		}
 	}
 }
 
-/// A Vector type contains a list of values.
-/// It is commonly used for list operations.
-#[deriving(Eq)]
-pub struct Vector<T> {
-	/// Contains the items in the vector.
-	x: ~[T],
+impl<
+	T: Add<T, T> + Mul<T, T> + std::num::Zero
+>
+NormSq<T> 
+for CscMatrix<T> {
+	fn norm_sq(&self) -> T {
+		let mut res: T = std::num::zero();
+		for a in self.x.data.iter() { res = res + *a * *a; }
+		res
+	}
+}
+
+impl<
+	T: Eps + std::num::Zero
+>
+Eps 
+for CscMatrix<T> {
+	fn close_eps(&self, other: &CscMatrix<T>, eps: f64) -> bool {
+		self.rows == other.rows && self.cols == other.cols
+		&& self.x.zip_with(&other.x, |a, b| a.close_eps(b, eps)).data.iter().all(|&x| x)
+	}
+}
+
+impl<
+	T: Eq + std::num::Zero
+>
+Eq for CscMatrix<T> {
+	fn eq(&self, other: &CscMatrix<T>) -> bool {
+		self.rows == other.rows && self.cols == other.cols
+		&& self.x.zip_with(&other.x, |a, b| a == b).data.iter().all(|&x| x)
+	}
 }
 
-/// Creates a new vector from a list of values.
-#[inline(always)]
-pub fn vector<T>(x: ~[T]) -> Vector<T> {
-	Vector::new(x)
+impl<
+	T: Add<T, T> + Mul<T, T> + std::num::Zero
+>
+LinearOperator<T> 
+for Matrix<T> {
+	fn apply(&self, v: &Vector<T>) -> Vector<T> {
+		self.transform(v)
+	}
 }
 
-impl<T> 
-Vector<T> {
-	/// Creates a new vector from a list of values.
-	pub fn new(x: ~[T]) -> Vector<T> {
-		Vector {
-			x: x 
-		}
+impl<
+	T: Add<T, T> + Mul<T, T> + std::num::Zero
+>
+LinearOperator<T> 
+for CsrMatrix<T> {
+	fn apply(&self, v: &Vector<T>) -> Vector<T> {
+		self.transform(v)
 	}
 }
 
 impl<
-	T: Add<T, T>
+	T: Add<T, T> + Mul<T, T> + std::num::Zero
 >
-Add<Vector<T>, Vector<T>> 
-for Vector<T> {
-	fn add(&self, rhs: &Vector<T>) -> Vector<T> {
-		if self.x.len() != rhs.x.len() {
-			Vector { 
-				x: ~[] 
-			}
-		} else {
-			let mut res: ~[T] = ~[];
-			let mut i = 0;
-			let n = self.x.len();
-			while i < n {
-				res.push(self.x[i] + rhs.x[i]);
-				i += 1;
-			}
+LinearOperator<T> 
+for CscMatrix<T> {
+	fn apply(&self, v: &Vector<T>) -> Vector<T> {
+		self.transform(v)
+	}
+}
 
-			Vector { 
-				x: res 
-			}
+/// A preconditioner that leaves vectors unchanged.
+pub struct IdentityPreconditioner;
+
+impl<
+	T: Clone
+>
+Preconditioner<T> 
+for IdentityPreconditioner {
+	fn precondition(&self, v: &Vector<T>) -> Vector<T> {
+		Vector {
+			x: v.x.clone()
 		}
 	}
 }
 
+/// A Jacobi preconditioner divides by the diagonal of the matrix.
+/// It is cheap and works well for diagonally dominant matrices.
+pub struct JacobiPreconditioner<T> {
+	/// Contains the inverse of each diagonal element.
+	inv_diag: ~[T],
+}
+
 impl<
-	T: Sub<T, T>
+	T: std::num::Float
 >
-Sub<Vector<T>, Vector<T>> 
-for Vector<T> {
-	fn sub(&self, rhs: &Vector<T>) -> Vector<T> {
-		if self.x.len() != rhs.x.len() {
-			Vector { 
-				x: ~[] 
-			}
-		} else {
-			let mut res: ~[T] = ~[];
-			let mut i = 0;
-			let n = self.x.len();
-			while i < n {
-				res.push(self.x[i] - rhs.x[i]);
-				i += 1;
-			}
-
-			Vector { 
-				x: res 
-			}
+JacobiPreconditioner<T> {
+	/// Creates a new preconditioner from the diagonal elements.
+	/// Fails if a diagonal element is zero.
+	pub fn from_diagonal(diag: &[T]) -> JacobiPreconditioner<T> {
+		let one: T = std::num::one();
+		JacobiPreconditioner {
+			inv_diag: diag.iter().map(|d| {
+				if d.is_zero() { fail!("Zero on diagonal"); }
+				one / *d
+			}).collect()
 		}
 	}
+
+	/// Creates a new preconditioner from a dense matrix.
+	/// Fails if the matrix is not square or has a zero on the diagonal.
+	pub fn from_matrix(m: &Matrix<T>) -> JacobiPreconditioner<T> {
+		m.check_square();
+		let diag: ~[T] = range(0, m.rows).map(|i| m.get(i, i)).collect();
+		JacobiPreconditioner::from_diagonal(diag.slice(0, diag.len()))
+	}
+
+	/// Creates a new preconditioner from a sparse matrix.
+	/// Fails if the matrix is not square or has a zero on the diagonal.
+	pub fn from_csr(m: &CsrMatrix<T>) -> JacobiPreconditioner<T> {
+		if m.rows != m.cols { fail!("Matrix is not square"); }
+		let diag: ~[T] = range(0, m.rows).map(|i| m.get(i, i)).collect();
+		JacobiPreconditioner::from_diagonal(diag.slice(0, diag.len()))
+	}
 }
 
 impl<
 	T: Mul<T, T>
 >
-Mul<Vector<T>, Vector<T>> 
-for Vector<T> {
-	fn mul(&self, rhs: &Vector<T>) -> Vector<T> {
-		if self.x.len() != rhs.x.len() {
-			Vector { 
-				x: ~[] 
-			}
-		} else {
-			let mut res: ~[T] = ~[];
-			let mut i = 0;
-			let n = self.x.len();
-			while i < n {
-				res.push(self.x[i] * rhs.x[i]);
-				i += 1;
-			}
-
-			Vector { 
-				x: res 
-			}
+Preconditioner<T> 
+for JacobiPreconditioner<T> {
+	fn precondition(&self, v: &Vector<T>) -> Vector<T> {
+		if v.x.len() != self.inv_diag.len() { fail!("Vector length does not match matrix"); }
+		Vector {
+			x: v.x.iter().zip(self.inv_diag.iter()).map(|(a, b)| *a * *b).collect()
 		}
 	}
 }
 
+/// An incomplete LU factorization without fill-in, also called ILU(0).
+/// The factors have the same sparsity pattern as the matrix.
+pub struct IluPreconditioner<T> {
+	/// Contains the unit lower factor below the diagonal and the upper factor.
+	lu: Compressed<T>,
+	/// Contains the position of each diagonal element.
+	diag: ~[uint],
+}
+
 impl<
-	T: Div<T, T>
+	T: std::num::Float
 >
-Div<Vector<T>, Vector<T>> 
-for Vector<T> {
-	fn div(&self, rhs: &Vector<T>) -> Vector<T> {
-		if self.x.len() != rhs.x.len() {
-			Vector { 
-				x: ~[] 
-			}
-		} else {
-			let mut res: ~[T] = ~[];
-			let mut i = 0;
-			let n = self.x.len();
-			while i < n {
-				res.push(self.x[i] / rhs.x[i]);
-				i += 1;
-			}
+IluPreconditioner<T> {
+	/// Creates a new preconditioner from a sparse matrix.
+	/// Fails if the matrix is not square or a diagonal element is missing or zero.
+	pub fn from_csr(m: &CsrMatrix<T>) -> IluPreconditioner<T> {
+		if m.rows != m.cols { fail!("Matrix is not square"); }
+		let n = m.rows;
+		let mut lu = m.x.clone();
+		let diag: ~[uint] = range(0, n).map(|i| match lu.find(i, i) {
+			Some(k) => k,
+			None => fail!("Zero on diagonal")
+		}).collect();
 
-			Vector { 
-				x: res 
+		for i in range(0, n) {
+			for p in range(lu.indptr[i], diag[i]) {
+				let k = lu.indices[p];
+				let pivot = lu.data[diag[k]].clone();
+				if pivot.is_zero() { fail!("Zero on diagonal"); }
+				let f = lu.data[p] / pivot;
+				lu.data[p] = f.clone();
+				for q in range(p + 1, lu.indptr[i + 1]) {
+					let j = lu.indices[q];
+					match lu.find(k, j) {
+						Some(r) => lu.data[q] = lu.data[q] - f * lu.data[r],
+						None => {}
+					}
+				}
 			}
+			if lu.data[diag[i]].is_zero() { fail!("Zero on diagonal"); }
 		}
+
+		IluPreconditioner {
+			lu: lu,
+			diag: diag
+		}
+	}
+
+	/// Creates a new preconditioner from the non-zero elements of a dense matrix.
+	pub fn from_matrix(m: &Matrix<T>) -> IluPreconditioner<T> {
+		IluPreconditioner::from_csr(&CsrMatrix::from_matrix(m))
 	}
 }
 
 impl<
-	T: Inv<T>
+	T: std::num::Float
 >
-Inv<Vector<T>> 
-for Vector<T> {
-	fn inv(&self) -> Vector<T> {
-		let mut res: ~[T] = ~[];
-		for i in range(0, self.x.len()) {
-			res.push(self.x[i].inv());
+Preconditioner<T> 
+for IluPreconditioner<T> {
+	fn precondition(&self, v: &Vector<T>) -> Vector<T> {
+		let n = self.diag.len();
+		if v.x.len() != n { fail!("Vector length does not match matrix"); }
+		let lu = &self.lu;
+		let mut y = v.x.clone();
+		for i in range(0, n) {
+			for p in range(lu.indptr[i], self.diag[i]) {
+				y[i] = y[i] - lu.data[p] * y[lu.indices[p]];
+			}
+		}
+		for ii in range(0, n) {
+			let i = n - 1 - ii;
+			for p in range(self.diag[i] + 1, lu.indptr[i + 1]) {
+				y[i] = y[i] - lu.data[p] * y[lu.indices[p]];
+			}
+			y[i] = y[i] / lu.data[self.diag[i]];
 		}
 
-		Vector { 
-			x: res 
+		Vector {
+			x: y
 		}
 	}
 }
 
+/// The result of an iterative solver.
+/// Contains the solution together with iteration statistics.
+pub struct IterativeSolution<T> {
+	/// The approximate solution.
+	x: Vector<T>,
+	/// Number of iterations performed.
+	iterations: uint,
+	/// Norm of the residual relative to the norm of the right-hand side.
+	residual: T,
+	/// Whether the residual reached the tolerance.
+	/// Decided from the residual of the solution, not from the recurrence.
+	converged: bool,
+}
+
+impl<T>
+IterativeSolution<T> {
+	/// Returns the number of iterations.
+	pub fn iterations(&self) -> uint {
+		self.iterations
+	}
+
+	/// Returns whether the residual reached the tolerance.
+	pub fn converged(&self) -> bool {
+		self.converged
+	}
+
+	/// Destructures into the solution.
+	pub fn into_vector(self) -> Vector<T> {
+		let IterativeSolution { x, .. } = self;
+		x
+	}
+}
+
 impl<
-	T: Neg<T>
+	T: Clone
 >
-Neg<Vector<T>> 
-for Vector<T> {
-	fn neg(&self) -> Vector<T> {
-		let mut res: ~[T] = ~[];
-		for x in self.x.iter() {
-			res.push(-x);
+IterativeSolution<T> {
+	/// Returns a copy of the solution.
+	pub fn x(&self) -> Vector<T> {
+		Vector {
+			x: self.x.x.clone()
 		}
+	}
 
-		Vector { 
-			x: res 
+	/// Returns the norm of the residual relative to the right-hand side.
+	pub fn residual(&self) -> T {
+		self.residual.clone()
+	}
+}
+
+/// Computes the dot product of two vectors.
+fn vector_dot<T: std::num::Float>(a: &Vector<T>, b: &Vector<T>) -> T {
+	let mut res: T = std::num::zero();
+	for (x, y) in a.x.iter().zip(b.x.iter()) { res = res + *x * *y; }
+	res
+}
+
+/// Multiplies all items by a scalar.
+fn vector_scale<T: std::num::Float>(v: &Vector<T>, s: &T) -> Vector<T> {
+	Vector {
+		x: v.x.iter().map(|a| *a * *s).collect()
+	}
+}
+
+/// Computes `alpha * x + y`.
+fn vector_axpy<T: std::num::Float>(alpha: &T, x: &Vector<T>, y: &Vector<T>) -> Vector<T> {
+	Vector {
+		x: x.x.iter().zip(y.x.iter()).map(|(a, b)| *alpha * *a + *b).collect()
+	}
+}
+
+/// Returns whether dividing by a value breaks down an iterative solver.
+fn is_breakdown<T: std::num::Float>(a: &T) -> bool {
+	a.is_zero() || !a.is_finite()
+}
+
+/// Collects the statistics of an iterative solver.
+/// The residual is computed from the solution, not from the recurrence,
+/// and decides whether the solver converged.
+fn iterative_solution<T: std::num::Float, A: LinearOperator<T>>(
+	a: &A,
+	b: &Vector<T>,
+	x: Vector<T>,
+	iterations: uint,
+	tol: T
+) -> IterativeSolution<T> {
+	let bb = b.norm_sq();
+	let rr = (*b - a.apply(&x)).norm_sq();
+	let residual = if bb.is_zero() { rr.sqrt() } else { (rr / bb).sqrt() };
+	IterativeSolution {
+		x: x,
+		iterations: iterations,
+		residual: residual.clone(),
+		converged: residual <= tol
+	}
+}
+
+/// Solves `A x = b` with the preconditioned conjugate gradient method.
+/// The operator and preconditioner must be symmetric positive definite.
+/// Stops when `|r|^2 <= tol^2 |b|^2`, after `max_iter` iterations
+/// or when the method breaks down.
+pub fn cg<
+	T: std::num::Float,
+	A: LinearOperator<T>,
+	P: Preconditioner<T>
+>(a: &A, b: &Vector<T>, p: &P, tol: T, max_iter: uint) -> IterativeSolution<T> {
+	let zero: T = std::num::zero();
+	let n = b.len();
+	let bb = b.norm_sq();
+	let limit = tol * tol * bb;
+	let mut x = Vector { x: std::vec::from_elem(n, zero.clone()) };
+	let mut r = Vector { x: b.x.clone() };
+	let mut z = p.precondition(&r);
+	let mut d = Vector { x: z.x.clone() };
+	let mut rz = vector_dot(&r, &z);
+	let mut iterations = 0;
+	let mut converged = bb.is_zero();
+	while !converged && iterations < max_iter {
+		iterations += 1;
+		let ad = a.apply(&d);
+		let dad = vector_dot(&d, &ad);
+		if is_breakdown(&dad) { break; }
+
+		let alpha = rz / dad;
+		x = vector_axpy(&alpha, &d, &x);
+		r = vector_axpy(&-alpha, &ad, &r);
+		if r.norm_sq() <= limit {
+			converged = true;
+			break;
 		}
+
+		z = p.precondition(&r);
+		let rz_next = vector_dot(&r, &z);
+		let beta = rz_next / rz;
+		d = vector_axpy(&beta, &d, &z);
+		rz = rz_next;
 	}
+
+	iterative_solution(a, b, x, iterations, tol)
 }
 
-impl<
-	T: Add<T, T> + Mul<T, T> + Clone
->
-NormSq<T> 
-for Vector<T> {
-	fn norm_sq(&self) -> T {
-		let mut res = self.x[0].clone();
-		for x in self.x.iter().skip(1) {
-			res = res + *x * *x;
+/// Solves `A x = b` with the right preconditioned BiCGSTAB method.
+/// Works for general square operators.
+/// Stops when `|r|^2 <= tol^2 |b|^2`, after `max_iter` iterations
+/// or when the method breaks down.
+pub fn bicgstab<
+	T: std::num::Float,
+	A: LinearOperator<T>,
+	P: Preconditioner<T>
+>(a: &A, b: &Vector<T>, p: &P, tol: T, max_iter: uint) -> IterativeSolution<T> {
+	let zero: T = std::num::zero();
+	let one: T = std::num::one();
+	let n = b.len();
+	let bb = b.norm_sq();
+	let limit = tol * tol * bb;
+	let mut x = Vector { x: std::vec::from_elem(n, zero.clone()) };
+	let mut r = Vector { x: b.x.clone() };
+	let r0 = Vector { x: b.x.clone() };
+	let mut v = Vector { x: std::vec::from_elem(n, zero.clone()) };
+	let mut d = Vector { x: std::vec::from_elem(n, zero.clone()) };
+	let (mut rho, mut alpha, mut omega) = (one.clone(), one.clone(), one.clone());
+	let mut iterations = 0;
+	let mut converged = bb.is_zero();
+	while !converged && iterations < max_iter {
+		iterations += 1;
+		let rho_next = vector_dot(&r0, &r);
+		if is_breakdown(&rho_next) { break; }
+
+		let beta = (rho_next / rho) * (alpha / omega);
+		d = vector_axpy(&beta, &vector_axpy(&-omega, &v, &d), &r);
+		let dh = p.precondition(&d);
+		v = a.apply(&dh);
+		let r0v = vector_dot(&r0, &v);
+		if is_breakdown(&r0v) { break; }
+
+		alpha = rho_next / r0v;
+		let s = vector_axpy(&-alpha, &v, &r);
+		x = vector_axpy(&alpha, &dh, &x);
+		if s.norm_sq() <= limit {
+			converged = true;
+			break;
 		}
 
-		res
+		let sh = p.precondition(&s);
+		let t = a.apply(&sh);
+		let tt = t.norm_sq();
+		if is_breakdown(&tt) { break; }
+
+		omega = vector_dot(&t, &s) / tt;
+		x = vector_axpy(&omega, &sh, &x);
+		r = vector_axpy(&-omega, &t, &s);
+		if r.norm_sq() <= limit {
+			converged = true;
+			break;
+		}
+		if is_breakdown(&omega) { break; }
+
+		rho = rho_next;
 	}
+
+	iterative_solution(a, b, x, iterations, tol)
 }
 
-impl<
-	T: Eps
-> 
-Eps 
-for Vector<T> {
-	fn close_eps(&self, other: &Vector<T>, eps: f64) -> bool {
-		let n = self.x.len();
-		let m = other.x.len();
-		if n != m { fail!("Vectors not of same length"); }
+/// Solves `A x = b` with the right preconditioned GMRES method.
+/// Restarts after `restart` iterations to limit memory use.
+/// Works for general square operators.
+/// Stops when `|r|^2 <= tol^2 |b|^2`, after `max_iter` iterations in total
+/// or when the method breaks down.
+pub fn gmres<
+	T: std::num::Float,
+	A: LinearOperator<T>,
+	P: Preconditioner<T>
+>(a: &A, b: &Vector<T>, p: &P, restart: uint, tol: T, max_iter: uint) -> IterativeSolution<T> {
+	if restart == 0 { fail!("Restart must be at least one"); }
 
-		if range(0, n).any(
-			|i| !self.x[i].close_eps(&other.x[i], eps)
-		) {
-			false
-		} else {
-			true
+	let zero: T = std::num::zero();
+	let one: T = std::num::one();
+	let m = restart;
+	let n = b.len();
+	let bb = b.norm_sq();
+	let limit = tol * tol * bb;
+	let mut x = Vector { x: std::vec::from_elem(n, zero.clone()) };
+	let mut iterations = 0;
+	loop {
+		let r = *b - a.apply(&x);
+		let beta = r.norm_sq().sqrt();
+		if beta * beta <= limit || iterations >= max_iter { break; }
+
+		// Arnoldi process with Givens rotations on the Hessenberg matrix.
+		let mut vs = ~[vector_scale(&r, &(one / beta))];
+		let mut zs: ~[Vector<T>] = ~[];
+		let mut h: ~[~[T]] = std::vec::from_fn(m + 1, |_| std::vec::from_elem(m, zero.clone()));
+		let mut g: ~[T] = std::vec::from_elem(m + 1, zero.clone());
+		let mut cs: ~[T] = std::vec::from_elem(m, zero.clone());
+		let mut sn: ~[T] = std::vec::from_elem(m, zero.clone());
+		g[0] = beta;
+		let mut k = 0;
+		let mut breakdown = false;
+		for j in range(0, m) {
+			iterations += 1;
+			zs.push(p.precondition(&vs[j]));
+			let mut w = a.apply(&zs[j]);
+			for i in range(0, j + 1) {
+				h[i][j] = vector_dot(&w, &vs[i]);
+				w = vector_axpy(&-h[i][j], &vs[i], &w);
+			}
+			let w_norm = w.norm_sq().sqrt();
+			h[j + 1][j] = w_norm.clone();
+
+			for i in range(0, j) {
+				let t = cs[i] * h[i][j] + sn[i] * h[i + 1][j];
+				h[i + 1][j] = -sn[i] * h[i][j] + cs[i] * h[i + 1][j];
+				h[i][j] = t;
+			}
+			let d = h[j][j].hypot(&h[j + 1][j]);
+			if is_breakdown(&d) {
+				breakdown = true;
+				break;
+			}
+
+			cs[j] = h[j][j] / d;
+			sn[j] = h[j + 1][j] / d;
+			h[j][j] = d;
+			h[j + 1][j] = zero.clone();
+			g[j + 1] = -sn[j] * g[j];
+			g[j] = cs[j] * g[j];
+			k = j + 1;
+
+			if g[j + 1] * g[j + 1] <= limit || iterations >= max_iter || w_norm.is_zero() { break; }
+			vs.push(vector_scale(&w, &(one / w_norm)));
 		}
+
+		// Solve the upper triangular least squares system and update.
+		let mut y: ~[T] = std::vec::from_elem(k, zero.clone());
+		for ii in range(0, k) {
+			let i = k - 1 - ii;
+			let mut sum = g[i].clone();
+			for l in range(i + 1, k) { sum = sum - h[i][l] * y[l]; }
+			y[i] = sum / h[i][i];
+		}
+		for i in range(0, k) { x = vector_axpy(&y[i], &zs[i], &x); }
+		if breakdown { break; }
 	}
+
+	iterative_solution(a, b, x, iterations, tol)
 }
 
 impl 
@@ -1364,3 +10146,76 @@ for f32 {
 	}
 }
 
+impl 
+TryInv<f64> 
+for f64 {
+	fn try_inv(&self, eps: f64) -> Option<f64> {
+		if is_singular(self, eps) {
+			None
+		} else {
+			Some(self.inv())
+		}
+	}
+}
+
+impl 
+TryDiv<f64, f64> 
+for f64 {
+	fn try_div(&self, rhs: &f64, eps: f64) -> Option<f64> {
+		if is_singular(rhs, eps) {
+			None
+		} else {
+			Some(self.div(rhs))
+		}
+	}
+}
+
+impl 
+TryInv<f32> 
+for f32 {
+	fn try_inv(&self, eps: f64) -> Option<f32> {
+		if is_singular(self, eps) {
+			None
+		} else {
+			Some(self.inv())
+		}
+	}
+}
+
+impl 
+TryDiv<f32, f32> 
+for f32 {
+	fn try_div(&self, rhs: &f32, eps: f64) -> Option<f32> {
+		if is_singular(rhs, eps) {
+			None
+		} else {
+			Some(self.div(rhs))
+		}
+	}
+}
+
+impl 
+PInv<f64> 
+for f64 {
+	fn pinv(&self, eps: f64) -> f64 {
+		let a = self.abs();
+		if a <= eps * a {
+			0f64
+		} else {
+			self.inv()
+		}
+	}
+}
+
+impl 
+PInv<f32> 
+for f32 {
+	fn pinv(&self, eps: f64) -> f32 {
+		let a = self.abs() as f64;
+		if a <= eps * a {
+			0f32
+		} else {
+			self.inv()
+		}
+	}
+}
@@ -489,6 +489,13 @@ fn test_vector_norm_sq() {
 	assert_eq!(b, c);
 }
 
+#[test]
+fn test_vector_norm_sq_first_element() {
+	assert_eq!(alg::norm_sq(alg::vector(~[3_f64, 4_f64])), 25_f64);
+	let empty: alg::Vector<f64> = alg::vector(~[]);
+	assert_eq!(alg::norm_sq(empty), 0_f64);
+}
+
 #[test]
 fn test_f64_eps() {
 	let a = 1_f64;
@@ -510,3 +517,1894 @@ fn test_f32() {
 	assert!(a.approx_eq_eps(&b, &0.01f32));
 }
 
+fn finite_difference(f: |f64| -> f64, x: f64) -> f64 {
+	let h = 0.000001_f64;
+	(f(x + h) - f(x - h)) / (2_f64 * h)
+}
+
+#[test]
+fn test_dual_sqrt() {
+	let a = alg::dual2(2_f64, 1_f64).sqrt();
+	let b = alg::dual2(2_f64.sqrt(), finite_difference(|x| x.sqrt(), 2_f64));
+	assert!(alg::close_eps(&a, &b, 0.00001_f64));
+}
+
+#[test]
+fn test_dual_exp() {
+	let a = alg::dual2(0.5_f64, 1_f64).exp();
+	let b = alg::dual2(0.5_f64.exp(), finite_difference(|x| x.exp(), 0.5_f64));
+	assert!(alg::close_eps(&a, &b, 0.00001_f64));
+}
+
+#[test]
+fn test_dual_ln() {
+	let a = alg::dual2(2_f64, 1_f64).ln();
+	let b = alg::dual2(2_f64.ln(), finite_difference(|x| x.ln(), 2_f64));
+	assert!(alg::close_eps(&a, &b, 0.00001_f64));
+}
+
+#[test]
+fn test_dual_sin_cos_tan() {
+	let a = alg::dual2(0.3_f64, 1_f64).sin();
+	let b = alg::dual2(0.3_f64.sin(), finite_difference(|x| x.sin(), 0.3_f64));
+	assert!(alg::close_eps(&a, &b, 0.00001_f64));
+	let a = alg::dual2(0.3_f64, 1_f64).cos();
+	let b = alg::dual2(0.3_f64.cos(), finite_difference(|x| x.cos(), 0.3_f64));
+	assert!(alg::close_eps(&a, &b, 0.00001_f64));
+	let a = alg::dual2(0.3_f64, 1_f64).tan();
+	let b = alg::dual2(0.3_f64.tan(), finite_difference(|x| x.tan(), 0.3_f64));
+	assert!(alg::close_eps(&a, &b, 0.00001_f64));
+}
+
+#[test]
+fn test_dual_atan2() {
+	let y = alg::dual2(1_f64, 1_f64);
+	let x = alg::dual2(2_f64, 0_f64);
+	let a = y.atan2(&x);
+	let b = alg::dual2(1_f64.atan2(&2_f64), finite_difference(|t| t.atan2(&2_f64), 1_f64));
+	assert!(alg::close_eps(&a, &b, 0.00001_f64));
+}
+
+#[test]
+fn test_dual_powf() {
+	let a = alg::dual2(1.5_f64, 1_f64).powf(&2.5_f64);
+	let b = alg::dual2(1.5_f64.powf(&2.5_f64), finite_difference(|x| x.powf(&2.5_f64), 1.5_f64));
+	assert!(alg::close_eps(&a, &b, 0.00001_f64));
+}
+
+#[test]
+fn test_dual_abs() {
+	let a = alg::dual2(-2_f64, 1_f64).abs();
+	let b = alg::dual2(2_f64, -1_f64);
+	assert_eq!(a, b);
+
+	let a = alg::dual2(2_f32, 1_f32).abs();
+	let b = alg::dual2(2_f32, 1_f32);
+	assert_eq!(a, b);
+}
+
+#[test]
+fn test_dual_n_eq() {
+	let one: alg::DualN<f64> = std::num::one();
+	assert_eq!(alg::dual_n(1_f64, alg::vector(~[0_f64, 0_f64])), one);
+	assert!(alg::dual_n(1_f64, alg::vector(~[0_f64, 1_f64])) != one);
+	assert!(alg::dual_n(1_f64, alg::vector(~[0_f64])) != alg::dual_n(1_f64, alg::vector(~[0_f64, 0_f64])));
+	let zero: alg::DualN<f64> = std::num::zero();
+	assert_eq!(zero, alg::dual_n(0_f64, alg::vector(~[0_f64])));
+}
+
+#[test]
+fn test_dual_n_add() {
+	let a = alg::dual_n(1_f64, alg::vector(~[1_f64, 0_f64]));
+	let b = alg::dual_n(2_f64, alg::vector(~[0_f64, 1_f64]));
+	let c = a + b;
+	let d = alg::dual_n(3_f64, alg::vector(~[1_f64, 1_f64]));
+	assert_eq!(c, d);
+}
+
+#[test]
+fn test_dual_n_sub() {
+	let a = alg::dual_n(1_f64, alg::vector(~[1_f64, 0_f64]));
+	let b = alg::dual_n(2_f64, alg::vector(~[0_f64, 1_f64]));
+	let c = a - b;
+	let d = alg::dual_n(-1_f64, alg::vector(~[1_f64, -1_f64]));
+	assert_eq!(c, d);
+}
+
+#[test]
+fn test_dual_n_gradient() {
+	// f(x, y) = x * y + x / y
+	let x = alg::DualN::variable(2_f64, 0, 2);
+	let y = alg::DualN::variable(3_f64, 1, 2);
+	let f = x * y + x / y;
+	let g = alg::dual_n(
+		6_f64 + 2_f64 / 3_f64,
+		alg::vector(~[3_f64 + 1_f64 / 3_f64, 2_f64 - 2_f64 / 9_f64])
+	);
+	assert!(alg::close_eps(&f, &g, 0.00001_f64));
+}
+
+#[test]
+fn test_dual_n_inv() {
+	let a = alg::dual_n(2_f64, alg::vector(~[1_f64, 2_f64]));
+	let b = alg::inv(a);
+	let c = alg::dual_n(0.5_f64, alg::vector(~[-0.25_f64, -0.5_f64]));
+	assert_eq!(b, c);
+}
+
+#[test]
+fn test_dual_n_neg() {
+	let a = alg::dual_n(2_f64, alg::vector(~[1_f64, 2_f64]));
+	let b = -a;
+	let c = alg::dual_n(-2_f64, alg::vector(~[-1_f64, -2_f64]));
+	assert_eq!(b, c);
+}
+
+#[test]
+fn test_dual_n_zero_one_scale() {
+	let x = alg::DualN::variable(2_f64, 0, 2);
+	let zero: alg::DualN<f64> = std::num::zero();
+	assert!(alg::close_eps(&(x + zero), &x, 0_f64));
+	let one: alg::DualN<f64> = std::num::one();
+	assert!(alg::close_eps(&(x * one), &x, 0_f64));
+	let two: alg::DualN<f64> = alg::scale(2_f64);
+	let c = alg::dual_n(4_f64, alg::vector(~[2_f64, 0_f64]));
+	assert!(alg::close_eps(&(x * two), &c, 0_f64));
+}
+
+#[test]
+fn test_hyper_dual_mul() {
+	// f(x) = x^3, f'(x) = 3x^2, f''(x) = 6x
+	let x = alg::hyper_dual(2_f64, 1_f64, 1_f64, 0_f64);
+	let f = x * x * x;
+	let g = alg::hyper_dual(8_f64, 12_f64, 12_f64, 12_f64);
+	assert_eq!(f, g);
+}
+
+#[test]
+fn test_hyper_dual_div() {
+	// f(x) = 1 / x, f'(x) = -1/x^2, f''(x) = 2/x^3
+	let one: alg::HyperDual<f64> = std::num::one();
+	let x = alg::hyper_dual(2_f64, 1_f64, 1_f64, 0_f64);
+	let f = one / x;
+	let g = alg::hyper_dual(0.5_f64, -0.25_f64, -0.25_f64, 0.25_f64);
+	assert!(alg::close_eps(&f, &g, 0.00001_f64));
+	assert!(alg::close_eps(&alg::inv(x), &g, 0.00001_f64));
+}
+
+#[test]
+fn test_hyper_dual_add_sub_neg() {
+	let a = alg::hyper_dual(1_f64, 2_f64, 3_f64, 4_f64);
+	let b = alg::hyper_dual(4_f64, 3_f64, 2_f64, 1_f64);
+	assert_eq!(a + b, alg::hyper_dual(5_f64, 5_f64, 5_f64, 5_f64));
+	assert_eq!(a - b, alg::hyper_dual(-3_f64, -1_f64, 1_f64, 3_f64));
+	assert_eq!(-a, alg::hyper_dual(-1_f64, -2_f64, -3_f64, -4_f64));
+}
+
+#[test]
+fn test_hyper_dual_scale() {
+	let a: alg::HyperDual<f64> = alg::scale(2_f64);
+	let b = alg::hyper_dual(2_f64, 0_f64, 0_f64, 0_f64);
+	assert_eq!(a, b);
+}
+
+fn poly<T: Add<T, T> + Mul<T, T> + Div<T, T>>(x: &T, y: &T) -> T {
+	*x * *y + *x / *y
+}
+
+#[test]
+fn test_var_grad() {
+	let t = alg::tape();
+	let x = t.var(2_f64);
+	let y = t.var(3_f64);
+	let f = poly(&x, &y);
+	assert_eq!(f.value(), 6_f64 + 2_f64 / 3_f64);
+	let g = f.grad();
+	let h = alg::vector(~[3_f64 + 1_f64 / 3_f64, 2_f64 - 2_f64 / 9_f64]);
+	assert!(alg::close_eps(&g, &h, 0.00001_f64));
+
+	// Forward mode gives the same partial derivative.
+	let a = poly(&alg::dual2(2_f64, 1_f64), &alg::dual2(3_f64, 0_f64));
+	let b = alg::dual2(6_f64 + 2_f64 / 3_f64, 3_f64 + 1_f64 / 3_f64);
+	assert!(alg::close_eps(&a, &b, 0.00001_f64));
+}
+
+#[test]
+fn test_var_sub_neg() {
+	let t = alg::tape();
+	let x = t.var(2_f64);
+	let y = t.var(3_f64);
+	let f = -(x - y);
+	assert_eq!(f.value(), 1_f64);
+	assert_eq!(f.grad(), alg::vector(~[-1_f64, 1_f64]));
+}
+
+#[test]
+fn test_var_inv() {
+	let t = alg::tape();
+	let x = t.var(2_f64);
+	let f = alg::inv(x);
+	assert_eq!(f.value(), 0.5_f64);
+	assert_eq!(f.grad(), alg::vector(~[-0.25_f64]));
+}
+
+#[test]
+fn test_var_norm_sq() {
+	let t = alg::tape();
+	let x = t.var(3_f64);
+	let y = t.var(4_f64);
+	let f = alg::norm_sq(x) + alg::norm_sq(y);
+	assert_eq!(f.value(), 25_f64);
+	assert_eq!(f.grad(), alg::vector(~[6_f64, 8_f64]));
+	assert_eq!(t.len(), 5);
+}
+
+#[test]
+fn test_derivative() {
+	let a = alg::derivative(|x| x * x * x, 2_f64);
+	assert_eq!(a, 12_f64);
+	let b = alg::derivative(|x| x.sin(), 0_f64);
+	assert_eq!(b, 1_f64);
+}
+
+#[test]
+fn test_gradient() {
+	let x = alg::vector(~[2_f64, 3_f64]);
+	let g = alg::gradient(|v| v[0] * v[1] + v[0] / v[1], &x);
+	let h = alg::vector(~[3_f64 + 1_f64 / 3_f64, 2_f64 - 2_f64 / 9_f64]);
+	assert!(alg::close_eps(&g, &h, 0.00001_f64));
+}
+
+#[test]
+fn test_jacobian() {
+	let x = alg::vector(~[2_f64, 3_f64]);
+	let j = alg::jacobian(|v| ~[v[0] * v[1], v[0] + v[1]], &x);
+	assert_eq!(j, ~[alg::vector(~[3_f64, 2_f64]), alg::vector(~[1_f64, 1_f64])]);
+}
+
+#[test]
+fn test_jacobian4() {
+	let x = alg::vector(~[1_f64, 2_f64, 3_f64, 4_f64]);
+	let j = alg::jacobian4(|v| ~[v[0] * v[0], v[1] * v[2], v[3] + v[0], v[3]], &x);
+	let k = alg::matrix4(
+		2_f64, 0_f64, 0_f64, 0_f64,
+		0_f64, 3_f64, 2_f64, 0_f64,
+		1_f64, 0_f64, 0_f64, 1_f64,
+		0_f64, 0_f64, 0_f64, 1_f64
+	);
+	assert_eq!(j, k);
+}
+
+#[test]
+fn test_hessian() {
+	// f(x, y) = x^2 y + y^3
+	let x = alg::vector(~[2_f64, 3_f64]);
+	let h = alg::hessian(|v| v[0] * v[0] * v[1] + v[1] * v[1] * v[1], &x);
+	assert_eq!(h, ~[alg::vector(~[6_f64, 4_f64]), alg::vector(~[4_f64, 18_f64])]);
+}
+
+#[test]
+fn test_dual_accessors() {
+	let mut a = alg::dual2(1_f64, 2_f64);
+	assert_eq!(a.real(), 1_f64);
+	assert_eq!(a.dual(), 2_f64);
+	a.set_real(3_f64);
+	a.set_dual(4_f64);
+	assert_eq!(a.to_tuple(), (3_f64, 4_f64));
+	assert_eq!(a.to_array(), [3_f64, 4_f64]);
+	assert_eq!(alg::Dual2::from_tuple((3_f64, 4_f64)), a);
+	assert_eq!(alg::Dual2::from_array([3_f64, 4_f64]), a);
+	let (x0, x1) = a.into_tuple();
+	assert_eq!((x0, x1), (3_f64, 4_f64));
+}
+
+#[test]
+fn test_dual_n_accessors() {
+	let mut a = alg::dual_n(1_f64, alg::vector(~[2_f64, 3_f64]));
+	assert_eq!(a.real(), 1_f64);
+	assert_eq!(a.dual(), alg::vector(~[2_f64, 3_f64]));
+	assert_eq!(a.to_tuple(), (1_f64, alg::vector(~[2_f64, 3_f64])));
+	a.set_real(4_f64);
+	a.set_dual(alg::vector(~[5_f64]));
+	let (x0, x1) = a.into_tuple();
+	assert_eq!(x0, 4_f64);
+	assert_eq!(x1.into_vec(), ~[5_f64]);
+}
+
+#[test]
+fn test_hyper_dual_accessors() {
+	let mut a = alg::hyper_dual(1_f64, 2_f64, 3_f64, 4_f64);
+	assert_eq!(a.to_tuple(), (a.real(), a.eps1(), a.eps2(), a.eps12()));
+	a.set_eps12(5_f64);
+	assert_eq!(a.to_array(), [1_f64, 2_f64, 3_f64, 5_f64]);
+	assert_eq!(alg::HyperDual::from_array([1_f64, 2_f64, 3_f64, 5_f64]), a);
+}
+
+#[test]
+fn test_complex_accessors() {
+	let mut a = alg::complex(1_f64, 2_f64);
+	assert_eq!(a.re(), 1_f64);
+	assert_eq!(a.im(), 2_f64);
+	a.set_re(3_f64);
+	a.set_im(4_f64);
+	assert_eq!(a.to_tuple(), (3_f64, 4_f64));
+	assert_eq!(alg::Complex::from_array(a.to_array()), a);
+	let (re, im) = a.into_tuple();
+	assert_eq!(alg::Complex::from_tuple((re, im)), alg::complex(3_f64, 4_f64));
+}
+
+#[test]
+fn test_quaternion_accessors() {
+	let mut a = alg::quaternion(1_f64, 2_f64, 3_f64, 4_f64);
+	assert_eq!((a.x(), a.y(), a.z(), a.w()), (1_f64, 2_f64, 3_f64, 4_f64));
+	a.set_x(5_f64);
+	a.set_w(6_f64);
+	assert_eq!(a.to_array(), [5_f64, 2_f64, 3_f64, 6_f64]);
+	assert_eq!(alg::Quaternion::from_tuple(a.to_tuple()), a);
+	let (x, y, z, w) = a.into_tuple();
+	assert_eq!(alg::Quaternion::from_array([x, y, z, w]), alg::quaternion(5_f64, 2_f64, 3_f64, 6_f64));
+}
+
+#[test]
+fn test_matrix4_accessors() {
+	let mut a: alg::Matrix4<f64> = std::num::one();
+	assert_eq!(a.get(1, 1), 1_f64);
+	assert_eq!(a.get(1, 2), 0_f64);
+	a.set(1, 2, 5_f64);
+	assert_eq!(*a.elem(1, 2), 5_f64);
+	assert_eq!(a.row(1), [0_f64, 1_f64, 5_f64, 0_f64]);
+	assert_eq!(a.col(2), [0_f64, 5_f64, 1_f64, 0_f64]);
+	a.set_row(3, [1_f64, 2_f64, 3_f64, 4_f64]);
+	a.set_col(0, [7_f64, 7_f64, 7_f64, 7_f64]);
+	assert_eq!(a.row(3), [7_f64, 2_f64, 3_f64, 4_f64]);
+	assert_eq!(alg::Matrix4::from_array(a.to_array()), a);
+	let b = a.into_array();
+	assert_eq!(b[1], [7_f64, 1_f64, 5_f64, 0_f64]);
+}
+
+#[test]
+fn test_vector_accessors() {
+	let mut a = alg::vector(~[1_f64, 2_f64, 3_f64]);
+	assert_eq!(a.len(), 3);
+	assert_eq!(a.get(1), 2_f64);
+	a.set(1, 5_f64);
+	assert_eq!(a.as_slice()[1], 5_f64);
+	assert_eq!(a.to_vec(), ~[1_f64, 5_f64, 3_f64]);
+	assert_eq!(a.into_vec(), ~[1_f64, 5_f64, 3_f64]);
+}
+
+#[test]
+fn test_complex_polar() {
+	let pi: f64 = std::num::Real::pi();
+	let a = alg::Complex::from_polar(2_f64, pi / 3_f64);
+	assert!(alg::close_eps(&a, &alg::complex(1_f64, 3_f64.sqrt()), 0.00001_f64));
+	assert!(alg::close_eps(&a.norm(), &2_f64, 0.00001_f64));
+	assert!(alg::close_eps(&a.arg(), &(pi / 3_f64), 0.00001_f64));
+	let b = alg::complex(-1_f64, 0_f64);
+	assert_eq!(b.arg(), pi);
+}
+
+#[test]
+fn test_complex_conj() {
+	let a = alg::complex(1_f64, 2_f64);
+	assert_eq!(a.conj(), alg::complex(1_f64, -2_f64));
+	assert_eq!(a * a.conj(), alg::complex(alg::norm_sq(a), 0_f64));
+}
+
+#[test]
+fn test_complex_exp_ln() {
+	let pi: f64 = std::num::Real::pi();
+	let a = alg::complex(0_f64, pi).exp();
+	assert!(alg::close_eps(&a, &alg::complex(-1_f64, 0_f64), 0.00001_f64));
+	let b = alg::complex(-1_f64, 0_f64).ln();
+	assert!(alg::close_eps(&b, &alg::complex(0_f64, pi), 0.00001_f64));
+	let c = alg::complex(1_f64, 2_f64);
+	assert!(alg::close_eps(&c.ln().exp(), &c, 0.00001_f64));
+}
+
+#[test]
+fn test_complex_sqrt() {
+	let a = alg::complex(-4_f64, 0_f64).sqrt();
+	assert_eq!(a, alg::complex(0_f64, 2_f64));
+	let b = alg::complex(3_f64, -4_f64).sqrt();
+	assert!(alg::close_eps(&b, &alg::complex(2_f64, -1_f64), 0.00001_f64));
+	let c = alg::complex(-3_f64, -4_f64).sqrt();
+	assert!(alg::close_eps(&c, &alg::complex(1_f64, -2_f64), 0.00001_f64));
+	assert!(alg::close_eps(&(c * c), &alg::complex(-3_f64, -4_f64), 0.00001_f64));
+}
+
+#[test]
+fn test_complex_pow() {
+	let pi: f64 = std::num::Real::pi();
+	let a = alg::complex(1_f64, 1_f64).powf(&2_f64);
+	assert!(alg::close_eps(&a, &alg::complex(0_f64, 2_f64), 0.00001_f64));
+	let i = alg::complex(0_f64, 1_f64);
+	let b = i.powc(&i);
+	assert!(alg::close_eps(&b, &alg::complex((-pi / 2_f64).exp(), 0_f64), 0.00001_f64));
+}
+
+#[test]
+fn test_complex_pow_zero() {
+	let inf: f64 = std::num::Float::infinity();
+	let zero = alg::complex(0_f64, 0_f64);
+	assert_eq!(zero.powf(&0_f64), alg::complex(1_f64, 0_f64));
+	assert_eq!(zero.powf(&2_f64), zero);
+	assert_eq!(zero.powf(&-1_f64), alg::complex(inf, 0_f64));
+	assert_eq!(zero.powc(&zero), alg::complex(1_f64, 0_f64));
+	assert_eq!(zero.powc(&alg::complex(2_f64, 1_f64)), zero);
+	assert_eq!(zero.powc(&alg::complex(-1_f64, 1_f64)), alg::complex(inf, 0_f64));
+}
+
+#[test]
+fn test_complex_trigonometric() {
+	let a = alg::complex(0.5_f64, 0.25_f64);
+	let s = a.sin();
+	let c = a.cos();
+	let one: alg::Complex<f64> = std::num::one();
+	assert!(alg::close_eps(&(s * s + c * c), &one, 0.00001_f64));
+	assert!(alg::close_eps(&a.tan(), &(s / c), 0.00001_f64));
+	let x = alg::complex(0_f64, 0.5_f64);
+	assert!(alg::close_eps(&x.sinh(), &alg::complex(0_f64, 0.5_f64.sin()), 0.00001_f64));
+	assert!(alg::close_eps(&x.cosh(), &alg::complex(0.5_f64.cos(), 0_f64), 0.00001_f64));
+	let sh = a.sinh();
+	let ch = a.cosh();
+	assert!(alg::close_eps(&(ch * ch - sh * sh), &one, 0.00001_f64));
+	assert!(alg::close_eps(&a.tanh(), &(sh / ch), 0.00001_f64));
+}
+
+#[test]
+fn test_complex_rotate_point() {
+	let pi: f64 = std::num::Real::pi();
+	let a = alg::Complex::rotation(pi / 2_f64);
+	let (x, y) = a.rotate_point((1_f64, 2_f64));
+	assert!(alg::close_eps(&alg::complex(x, y), &alg::complex(-2_f64, 1_f64), 0.00001_f64));
+}
+
+#[test]
+fn test_complex_slerp_nlerp() {
+	let pi: f64 = std::num::Real::pi();
+	let a = alg::Complex::rotation(0.25_f64 * pi);
+	let b = alg::Complex::rotation(-0.5_f64 * pi);
+	let c = a.slerp(&b, 1_f64 / 3_f64);
+	assert!(alg::close_eps(&c, &alg::Complex::rotation(0_f64), 0.00001_f64));
+	let d = alg::Complex::rotation(0.5_f64 * pi);
+	let e = a.nlerp(&d, 0.5_f64);
+	assert!(alg::close_eps(&e, &alg::Complex::rotation(0.375_f64 * pi), 0.00001_f64));
+	assert!(alg::close_eps(&e.norm(), &1_f64, 0.00001_f64));
+}
+
+#[test]
+fn test_transform2_compose() {
+	let pi: f64 = std::num::Real::pi();
+	let a = alg::Transform2::from_angle(pi / 2_f64, (1_f64, 0_f64));
+	let b = alg::transform2(alg::Complex::rotation(pi), (0_f64, 2_f64));
+	let (x, y) = (a * b).transform_point((1_f64, 1_f64));
+	let (u, v) = a.transform_point(b.transform_point((1_f64, 1_f64)));
+	assert!(alg::close_eps(&alg::complex(x, y), &alg::complex(u, v), 0.00001_f64));
+	assert!(alg::close_eps(&alg::complex(x, y), &alg::complex(0_f64, -1_f64), 0.00001_f64));
+}
+
+#[test]
+fn test_transform2_inv() {
+	let a = alg::Transform2::from_angle(0.3_f64, (1_f64, 2_f64));
+	let one: alg::Transform2<f64> = std::num::one();
+	assert!(alg::close_eps(&(a * alg::inv(a)), &one, 0.00001_f64));
+	let (x, y) = a.transform_vector((1_f64, 0_f64));
+	assert!(alg::close_eps(&alg::complex(x, y), &alg::Complex::rotation(0.3_f64), 0.00001_f64));
+}
+
+#[test]
+fn test_transform2_to_matrix4() {
+	let a = alg::Transform2::from_angle(0.3_f64, (1_f64, 2_f64));
+	let m = a.to_matrix4();
+	let (x, y) = a.transform_point((3_f64, 4_f64));
+	let p = alg::matrix4(
+		3_f64, 0_f64, 0_f64, 0_f64,
+		4_f64, 0_f64, 0_f64, 0_f64,
+		0_f64, 0_f64, 0_f64, 0_f64,
+		1_f64, 0_f64, 0_f64, 0_f64
+	);
+	let q = m * p;
+	assert!(alg::close_eps(&alg::complex(q.get(0, 0), q.get(1, 0)), &alg::complex(x, y), 0.00001_f64));
+	assert_eq!(q.get(3, 0), 1_f64);
+}
+
+fn close_eps3(a: (f64, f64, f64), b: (f64, f64, f64), eps: f64) -> bool {
+	let (ax, ay, az) = a;
+	let (bx, by, bz) = b;
+	alg::close_eps(&alg::vector(~[ax, ay, az]), &alg::vector(~[bx, by, bz]), eps)
+}
+
+#[test]
+fn test_quaternion_conjugate() {
+	let a = alg::quaternion(1_f64, 2_f64, 3_f64, 4_f64);
+	assert_eq!(a.conjugate(), alg::quaternion(-1_f64, -2_f64, -3_f64, 4_f64));
+}
+
+#[test]
+fn test_quaternion_normalize() {
+	let a = alg::quaternion(1_f64, 2_f64, 3_f64, 4_f64).normalize();
+	assert!(alg::close_eps(&alg::norm_sq(a), &1_f64, 0.00001_f64));
+	assert!(alg::close_eps(&alg::inv(a), &a.conjugate(), 0.00001_f64));
+}
+
+#[test]
+fn test_quaternion_axis_angle() {
+	let pi: f64 = std::num::Real::pi();
+	let a = alg::Quaternion::from_axis_angle((0_f64, 0_f64, 2_f64), pi / 2_f64);
+	let v = a.rotate_vector((1_f64, 0_f64, 0_f64));
+	assert!(close_eps3(v, (0_f64, 1_f64, 0_f64), 0.00001_f64));
+	let (axis, angle) = a.to_axis_angle();
+	assert!(close_eps3(axis, (0_f64, 0_f64, 1_f64), 0.00001_f64));
+	assert!(alg::close_eps(&angle, &(pi / 2_f64), 0.00001_f64));
+	let one: alg::Quaternion<f64> = std::num::one();
+	let (axis, angle) = one.to_axis_angle();
+	assert_eq!(axis, (1_f64, 0_f64, 0_f64));
+	assert_eq!(angle, 0_f64);
+	let z = alg::Quaternion::from_axis_angle((0_f64, 0_f64, 0_f64), 1_f64);
+	assert_eq!(z, one);
+}
+
+#[test]
+fn test_quaternion_rotate_vector() {
+	let a = alg::Quaternion::from_axis_angle((1_f64, 2_f64, 3_f64), 0.7_f64);
+	let v = a.rotate_vector((4_f64, 5_f64, 6_f64));
+	let p = alg::quaternion(4_f64, 5_f64, 6_f64, 0_f64);
+	let q = a * p * a.conjugate();
+	assert!(close_eps3(v, (q.x(), q.y(), q.z()), 0.00001_f64));
+}
+
+#[test]
+fn test_quaternion_euler() {
+	let orders = ~[alg::XYZ, alg::XZY, alg::YXZ, alg::YZX, alg::ZXY, alg::ZYX];
+	for order in orders.iter() {
+		let a = alg::Quaternion::from_euler(0.1_f64, 0.2_f64, 0.3_f64, *order);
+		let e = a.to_euler(*order);
+		assert!(close_eps3(e, (0.1_f64, 0.2_f64, 0.3_f64), 0.00001_f64));
+	}
+
+	// In gimbal lock the angles are not unique, but the rotation is recovered.
+	let pi: f64 = std::num::Real::pi();
+	for order in orders.iter() {
+		for &b in [pi / 2_f64, -pi / 2_f64].iter() {
+			let a = alg::Quaternion::from_euler(0.4_f64, b, pi / 6_f64, *order);
+			let (x, y, z) = a.to_euler(*order);
+			assert!(alg::close_eps(&y, &b, 0.00001_f64));
+			assert_eq!(z, 0_f64);
+			let c = alg::Quaternion::from_euler(x, y, z, *order);
+			let v = (1_f64, 2_f64, 3_f64);
+			assert!(close_eps3(c.rotate_vector(v), a.rotate_vector(v), 0.00001_f64));
+		}
+	}
+
+	// Rotating a quarter turn about x and then about z moves z to x.
+	let a = alg::Quaternion::from_euler(pi / 2_f64, 0_f64, pi / 2_f64, alg::XYZ);
+	let v = a.rotate_vector((0_f64, 0_f64, 1_f64));
+	assert!(close_eps3(v, (1_f64, 0_f64, 0_f64), 0.00001_f64));
+}
+
+#[test]
+fn test_quaternion_slerp() {
+	let a = alg::Quaternion::from_axis_angle((0_f64, 0_f64, 1_f64), 0.2_f64);
+	let b = alg::Quaternion::from_axis_angle((0_f64, 0_f64, 1_f64), 1.4_f64);
+	assert!(alg::close_eps(&a.slerp(&b, 0_f64), &a, 0.00001_f64));
+	assert!(alg::close_eps(&a.slerp(&b, 1_f64), &b, 0.00001_f64));
+	let c = a.slerp(&b, 0.25_f64);
+	let d = alg::Quaternion::from_axis_angle((0_f64, 0_f64, 1_f64), 0.5_f64);
+	assert!(alg::close_eps(&c, &d, 0.00001_f64));
+	for i in range(0, 11) {
+		let t = i as f64 / 10_f64;
+		assert!(alg::close_eps(&alg::norm_sq(a.slerp(&b, t)), &1_f64, 0.00001_f64));
+	}
+
+	// Shortest path with the sign of the target flipped.
+	let e = a.slerp(&-b, 0.25_f64);
+	assert!(alg::close_eps(&e, &d, 0.00001_f64));
+}
+
+#[test]
+fn test_quaternion_nlerp() {
+	let a = alg::Quaternion::from_axis_angle((1_f64, 0_f64, 0_f64), 0.2_f64);
+	let b = alg::Quaternion::from_axis_angle((0_f64, 1_f64, 0_f64), 1.4_f64);
+	for i in range(0, 11) {
+		let t = i as f64 / 10_f64;
+		assert!(alg::close_eps(&alg::norm_sq(a.nlerp(&b, t)), &1_f64, 0.00001_f64));
+	}
+	assert!(alg::close_eps(&a.nlerp(&-b, 1_f64), &b, 0.00001_f64));
+}
+
+#[test]
+fn test_quaternion_squad() {
+	let q0 = alg::Quaternion::from_axis_angle((1_f64, 0_f64, 0_f64), 0.1_f64);
+	let q1 = alg::Quaternion::from_axis_angle((0_f64, 1_f64, 0_f64), 0.5_f64);
+	let q2 = alg::Quaternion::from_axis_angle((0_f64, 0_f64, 1_f64), 0.9_f64);
+	let q3 = alg::Quaternion::from_axis_angle((1_f64, 1_f64, 0_f64), 1.3_f64);
+	let a = q1.squad_control(&q0, &q2);
+	let b = q2.squad_control(&q1, &q3);
+	assert!(alg::close_eps(&q1.squad(&q2, &a, &b, 0_f64), &q1, 0.00001_f64));
+	assert!(alg::close_eps(&q1.squad(&q2, &a, &b, 1_f64), &q2, 0.00001_f64));
+	for i in range(0, 11) {
+		let t = i as f64 / 10_f64;
+		let q = q1.squad(&q2, &a, &b, t);
+		assert!(alg::close_eps(&alg::norm_sq(q), &1_f64, 0.00001_f64));
+	}
+}
+
+#[test]
+fn test_quaternion_exp_ln() {
+	let a = alg::quaternion(1_f64, 2_f64, 3_f64, 4_f64);
+	assert!(alg::close_eps(&a.ln().exp(), &a, 0.00001_f64));
+	let b = alg::Quaternion::from_axis_angle((1_f64, 2_f64, 3_f64), 0.8_f64);
+	let c = b.powf(&0.5_f64);
+	assert!(alg::close_eps(&(c * c), &b, 0.00001_f64));
+	let d = alg::Quaternion::from_axis_angle((1_f64, 2_f64, 3_f64), 0.4_f64);
+	assert!(alg::close_eps(&c, &d, 0.00001_f64));
+	let e: alg::Quaternion<f64> = std::num::one();
+	assert!(alg::close_eps(&e.ln(), &std::num::zero(), 0.00001_f64));
+	let f = -e;
+	assert!(alg::close_eps(&f.ln().exp(), &f, 0.00001_f64));
+	let g = f.powf(&0.5_f64);
+	assert!(alg::close_eps(&(g * g), &f, 0.00001_f64));
+	let h = alg::quaternion(0_f64, 0_f64, 0_f64, -2_f64);
+	assert!(alg::close_eps(&h.ln().exp(), &h, 0.00001_f64));
+}
+
+#[test]
+fn test_quaternion_angular_velocity() {
+	let mut q: alg::Quaternion<f64> = std::num::one();
+	for _ in range(0, 100) {
+		q = q.integrate((0_f64, 0_f64, 2_f64), 0.01_f64);
+		assert!(alg::close_eps(&alg::norm_sq(q), &1_f64, 0.00001_f64));
+	}
+	let r = alg::Quaternion::from_axis_angle((0_f64, 0_f64, 1_f64), 2_f64);
+	assert!(alg::close_eps(&q, &r, 0.00001_f64));
+
+	let a = alg::Quaternion::from_axis_angle((1_f64, 2_f64, 3_f64), 0.3_f64);
+	let b = a.integrate((0.5_f64, -1_f64, 2_f64), 0.1_f64);
+	let w = a.angular_velocity(&b, 0.1_f64);
+	assert!(close_eps3(w, (0.5_f64, -1_f64, 2_f64), 0.00001_f64));
+}
+
+#[test]
+fn test_matrix4_from_quaternion() {
+	let q = alg::Quaternion::from_axis_angle((1_f64, 2_f64, 3_f64), 0.7_f64);
+	let m = alg::Matrix4::from_quaternion(&q);
+	let (x, y, z) = q.rotate_vector((4_f64, 5_f64, 6_f64));
+	let p = m.row(0);
+	assert!(alg::close_eps(&(p[0] * 4_f64 + p[1] * 5_f64 + p[2] * 6_f64), &x, 0.00001_f64));
+	let p = m.row(1);
+	assert!(alg::close_eps(&(p[0] * 4_f64 + p[1] * 5_f64 + p[2] * 6_f64), &y, 0.00001_f64));
+	let p = m.row(2);
+	assert!(alg::close_eps(&(p[0] * 4_f64 + p[1] * 5_f64 + p[2] * 6_f64), &z, 0.00001_f64));
+	assert!(alg::close_eps(&alg::det(m), &1_f64, 0.00001_f64));
+}
+
+#[test]
+fn test_quaternion_from_rotation_matrix() {
+	// Covers each branch of the extraction.
+	let rotations = ~[
+		alg::Quaternion::from_axis_angle((1_f64, 2_f64, 3_f64), 0.7_f64),
+		alg::Quaternion::from_axis_angle((1_f64, 0.1_f64, 0.1_f64), 3_f64),
+		alg::Quaternion::from_axis_angle((0.1_f64, 1_f64, 0.1_f64), 3_f64),
+		alg::Quaternion::from_axis_angle((0.1_f64, 0.1_f64, 1_f64), 3_f64)
+	];
+	for q in rotations.iter() {
+		let m = alg::Matrix4::from_quaternion(q);
+		let r = alg::Quaternion::from_rotation_matrix(&m);
+		let r = if r.dot(q) < 0_f64 { -r } else { r };
+		assert!(alg::close_eps(&r, q, 0.00001_f64));
+	}
+}
+
+#[test]
+fn test_matrix4_decompose() {
+	let q = alg::Quaternion::from_axis_angle((1_f64, 2_f64, 3_f64), 0.7_f64);
+	let m = alg::Matrix4::compose((1_f64, 2_f64, 3_f64), &q, (2_f64, 3_f64, 4_f64));
+	let (t, r, s) = m.decompose().unwrap();
+	assert!(close_eps3(t, (1_f64, 2_f64, 3_f64), 0.00001_f64));
+	assert!(close_eps3(s, (2_f64, 3_f64, 4_f64), 0.00001_f64));
+	let r = if r.dot(&q) < 0_f64 { -r } else { r };
+	assert!(alg::close_eps(&r, &q, 0.00001_f64));
+
+	let n = alg::Matrix4::compose((0_f64, 0_f64, 0_f64), &q, (-2_f64, 3_f64, 4_f64));
+	let (_, r, s) = n.decompose().unwrap();
+	assert!(close_eps3(s, (-2_f64, 3_f64, 4_f64), 0.00001_f64));
+	let r = if r.dot(&q) < 0_f64 { -r } else { r };
+	assert!(alg::close_eps(&r, &q, 0.00001_f64));
+
+	let flat = alg::Matrix4::compose((0_f64, 0_f64, 0_f64), &q, (2_f64, 0_f64, 4_f64));
+	assert!(flat.decompose().is_none());
+}
+
+fn project(m: &alg::Matrix4<f64>, (x, y, z): (f64, f64, f64)) -> (f64, f64, f64) {
+	let r = m.to_array();
+	let t = |i: uint| r[i][0] * x + r[i][1] * y + r[i][2] * z + r[i][3];
+	let w = t(3);
+	(t(0) / w, t(1) / w, t(2) / w)
+}
+
+#[test]
+fn test_matrix4_translation_scaling() {
+	let a = alg::Matrix4::translation((1_f64, 2_f64, 3_f64));
+	assert!(close_eps3(project(&a, (1_f64, 1_f64, 1_f64)), (2_f64, 3_f64, 4_f64), 0.00001_f64));
+	let b = alg::Matrix4::scaling((1_f64, 2_f64, 3_f64));
+	assert!(close_eps3(project(&b, (1_f64, 1_f64, 1_f64)), (1_f64, 2_f64, 3_f64), 0.00001_f64));
+	let c = a * b;
+	assert!(close_eps3(project(&c, (1_f64, 1_f64, 1_f64)), (2_f64, 4_f64, 6_f64), 0.00001_f64));
+}
+
+#[test]
+fn test_matrix4_rotation() {
+	let pi: f64 = std::num::Real::pi();
+	let a = alg::Matrix4::rotation_x(pi / 2_f64);
+	assert!(close_eps3(project(&a, (0_f64, 1_f64, 0_f64)), (0_f64, 0_f64, 1_f64), 0.00001_f64));
+	let b = alg::Matrix4::rotation_y(pi / 2_f64);
+	assert!(close_eps3(project(&b, (0_f64, 0_f64, 1_f64)), (1_f64, 0_f64, 0_f64), 0.00001_f64));
+	let c = alg::Matrix4::rotation_z(pi / 2_f64);
+	assert!(close_eps3(project(&c, (1_f64, 0_f64, 0_f64)), (0_f64, 1_f64, 0_f64), 0.00001_f64));
+	let q = alg::Quaternion::from_axis_angle((0_f64, 0_f64, 1_f64), 0.3_f64);
+	let d = alg::Matrix4::rotation_z(0.3_f64);
+	assert!(alg::close_eps(&d, &alg::Matrix4::from_quaternion(&q), 0.00001_f64));
+}
+
+#[test]
+fn test_matrix4_look_at() {
+	let eye = (1_f64, 2_f64, 3_f64);
+	let target = (1_f64, 2_f64, -2_f64);
+	let up = (0_f64, 1_f64, 0_f64);
+	let a = alg::Matrix4::look_at(eye, target, up, alg::RightHanded);
+	assert!(close_eps3(project(&a, eye), (0_f64, 0_f64, 0_f64), 0.00001_f64));
+	assert!(close_eps3(project(&a, target), (0_f64, 0_f64, -5_f64), 0.00001_f64));
+	assert!(close_eps3(project(&a, (2_f64, 3_f64, 3_f64)), (1_f64, 1_f64, 0_f64), 0.00001_f64));
+	let b = alg::Matrix4::look_at(eye, target, up, alg::LeftHanded);
+	assert!(close_eps3(project(&b, target), (0_f64, 0_f64, 5_f64), 0.00001_f64));
+	assert!(close_eps3(project(&b, (2_f64, 3_f64, 3_f64)), (-1_f64, 1_f64, 0_f64), 0.00001_f64));
+}
+
+#[test]
+fn test_matrix4_perspective() {
+	let pi: f64 = std::num::Real::pi();
+	let a = alg::Matrix4::perspective(pi / 2_f64, 2_f64, 1_f64, 10_f64,
+		alg::RightHanded, alg::NegativeOneToOne);
+	assert!(close_eps3(project(&a, (2_f64, 1_f64, -1_f64)), (1_f64, 1_f64, -1_f64), 0.00001_f64));
+	assert!(close_eps3(project(&a, (0_f64, 0_f64, -10_f64)), (0_f64, 0_f64, 1_f64), 0.00001_f64));
+	let b = alg::Matrix4::perspective(pi / 2_f64, 2_f64, 1_f64, 10_f64,
+		alg::LeftHanded, alg::ZeroToOne);
+	assert!(close_eps3(project(&b, (2_f64, 1_f64, 1_f64)), (1_f64, 1_f64, 0_f64), 0.00001_f64));
+	assert!(close_eps3(project(&b, (0_f64, 0_f64, 10_f64)), (0_f64, 0_f64, 1_f64), 0.00001_f64));
+}
+
+#[test]
+fn test_matrix4_frustum() {
+	let a = alg::Matrix4::frustum(-1_f64, 3_f64, -2_f64, 2_f64, 1_f64, 10_f64,
+		alg::RightHanded, alg::ZeroToOne);
+	assert!(close_eps3(project(&a, (3_f64, 2_f64, -1_f64)), (1_f64, 1_f64, 0_f64), 0.00001_f64));
+	assert!(close_eps3(project(&a, (-10_f64, -20_f64, -10_f64)), (-1_f64, -1_f64, 1_f64), 0.00001_f64));
+	let b = alg::Matrix4::frustum(-1_f64, 3_f64, -2_f64, 2_f64, 1_f64, 10_f64,
+		alg::LeftHanded, alg::NegativeOneToOne);
+	assert!(close_eps3(project(&b, (3_f64, 2_f64, 1_f64)), (1_f64, 1_f64, -1_f64), 0.00001_f64));
+	assert!(close_eps3(project(&b, (-10_f64, -20_f64, 10_f64)), (-1_f64, -1_f64, 1_f64), 0.00001_f64));
+}
+
+#[test]
+fn test_matrix4_orthographic() {
+	let a = alg::Matrix4::orthographic(-1_f64, 3_f64, -2_f64, 2_f64, 1_f64, 10_f64,
+		alg::RightHanded, alg::NegativeOneToOne);
+	assert!(close_eps3(project(&a, (3_f64, 2_f64, -1_f64)), (1_f64, 1_f64, -1_f64), 0.00001_f64));
+	assert!(close_eps3(project(&a, (-1_f64, -2_f64, -10_f64)), (-1_f64, -1_f64, 1_f64), 0.00001_f64));
+	let b = alg::Matrix4::orthographic(-1_f64, 3_f64, -2_f64, 2_f64, 1_f64, 10_f64,
+		alg::LeftHanded, alg::ZeroToOne);
+	assert!(close_eps3(project(&b, (3_f64, 2_f64, 1_f64)), (1_f64, 1_f64, 0_f64), 0.00001_f64));
+	assert!(close_eps3(project(&b, (-1_f64, -2_f64, 10_f64)), (-1_f64, -1_f64, 1_f64), 0.00001_f64));
+}
+
+#[test]
+fn test_vector3_ops() {
+	let a = alg::vector3(1_f64, 2_f64, 3_f64);
+	let b = alg::vector3(4_f64, 5_f64, 6_f64);
+	assert_eq!(a + b, alg::vector3(5_f64, 7_f64, 9_f64));
+	assert_eq!(a - b, alg::vector3(-3_f64, -3_f64, -3_f64));
+	assert_eq!(a * b, alg::vector3(4_f64, 10_f64, 18_f64));
+	assert_eq!(-a, alg::vector3(-1_f64, -2_f64, -3_f64));
+	assert_eq!(alg::norm_sq(a), 14_f64);
+	assert_eq!(a.dot(&b), 32_f64);
+	assert_eq!(a.cross(&b), alg::vector3(-3_f64, 6_f64, -3_f64));
+	assert_eq!(a.to_tuple(), (1_f64, 2_f64, 3_f64));
+	let zero: alg::Vector3<f64> = std::num::zero();
+	assert!(alg::close_eps(&(a + zero), &a, 0_f64));
+}
+
+#[test]
+fn test_vector4_ops() {
+	let a = alg::vector4(1_f64, 2_f64, 3_f64, 4_f64);
+	let b = alg::vector4(4_f64, 3_f64, 2_f64, 1_f64);
+	assert_eq!(a + b, alg::vector4(5_f64, 5_f64, 5_f64, 5_f64));
+	assert_eq!(a - b, alg::vector4(-3_f64, -1_f64, 1_f64, 3_f64));
+	assert_eq!(a * b, alg::vector4(4_f64, 6_f64, 6_f64, 4_f64));
+	assert_eq!(-a, alg::vector4(-1_f64, -2_f64, -3_f64, -4_f64));
+	assert_eq!(alg::norm_sq(a), 30_f64);
+	assert_eq!(a.dot(&b), 20_f64);
+	assert_eq!(a.xyz(), alg::vector3(1_f64, 2_f64, 3_f64));
+	assert_eq!(alg::Vector4::from_vector3(&a.xyz(), 4_f64), a);
+}
+
+#[test]
+fn test_matrix4_transform() {
+	let m = alg::Matrix4::translation((1_f64, 2_f64, 3_f64)) * alg::Matrix4::scaling((2_f64, 2_f64, 2_f64));
+	let p = alg::vector3(1_f64, 1_f64, 1_f64);
+	assert!(alg::close_eps(&m.transform_point(&p), &alg::vector3(3_f64, 4_f64, 5_f64), 0.00001_f64));
+	assert!(alg::close_eps(&m.transform_vector(&p), &alg::vector3(2_f64, 2_f64, 2_f64), 0.00001_f64));
+	let h = alg::Vector4::from_vector3(&p, 1_f64);
+	assert!(alg::close_eps(&m.transform(&h), &alg::vector4(3_f64, 4_f64, 5_f64, 1_f64), 0.00001_f64));
+}
+
+#[test]
+fn test_matrix4_transform_point_perspective() {
+	let pi: f64 = std::num::Real::pi();
+	let m = alg::Matrix4::perspective(pi / 2_f64, 2_f64, 1_f64, 10_f64,
+		alg::RightHanded, alg::NegativeOneToOne);
+	let p = m.transform_point(&alg::vector3(2_f64, 1_f64, -1_f64));
+	assert!(alg::close_eps(&p, &alg::vector3(1_f64, 1_f64, -1_f64), 0.00001_f64));
+}
+
+#[test]
+fn test_matrix4_transform_normal() {
+	// A plane x + y = 0 scaled along x keeps its normal perpendicular.
+	let m = alg::Matrix4::scaling((2_f64, 1_f64, 1_f64));
+	let t = m.transform_vector(&alg::vector3(1_f64, -1_f64, 0_f64));
+	let n = m.transform_normal(&alg::vector3(1_f64, 1_f64, 0_f64));
+	assert!(alg::close_eps(&t.dot(&n), &0_f64, 0.00001_f64));
+	assert!(alg::close_eps(&n, &alg::vector3(0.5_f64, 1_f64, 0_f64), 0.00001_f64));
+}
+
+#[test]
+fn test_matrix4_transform_batch() {
+	let m = alg::Matrix4::rotation_z(0.3_f64) * alg::Matrix4::translation((1_f64, 2_f64, 3_f64));
+	let ps = ~[alg::vector3(1_f64, 0_f64, 0_f64), alg::vector3(0_f64, 1_f64, 2_f64)];
+	let a = m.transform_points(ps);
+	let b = m.transform_vectors(ps);
+	let c = m.transform_normals(ps);
+	let hs = ~[alg::vector4(1_f64, 0_f64, 0_f64, 1_f64), alg::vector4(0_f64, 1_f64, 2_f64, 1_f64)];
+	let d = m.transform_all(hs);
+	for i in range(0, 2) {
+		assert!(alg::close_eps(&a[i], &m.transform_point(&ps[i]), 0_f64));
+		assert!(alg::close_eps(&b[i], &m.transform_vector(&ps[i]), 0_f64));
+		assert!(alg::close_eps(&c[i], &m.transform_normal(&ps[i]), 0.00001_f64));
+		assert!(alg::close_eps(&d[i], &m.transform(&hs[i]), 0_f64));
+	}
+}
+
+/// Generates a number in the range `[-1, 1)` with a linear congruential generator.
+fn random_number(seed: &mut u64) -> f64 {
+	*seed = *seed * 6364136223846793005 + 1442695040888963407;
+	(*seed >> 11) as f64 / 9007199254740992_f64 * 2_f64 - 1_f64
+}
+
+/// Generates a diagonally dominant matrix, which is well-conditioned.
+fn random_matrix4(seed: &mut u64) -> alg::Matrix4<f64> {
+	let mut a: alg::Matrix4<f64> = std::num::one();
+	for i in range(0u, 4) {
+		for j in range(0u, 4) {
+			let d = if i == j { 5_f64 } else { 0_f64 };
+			a.set(i, j, random_number(seed) + d);
+		}
+	}
+	a
+}
+
+#[test]
+fn test_matrix4_transpose_trace() {
+	let a = alg::matrix4(
+		1_f64, 2_f64, 3_f64, 4_f64,
+		5_f64, 6_f64, 7_f64, 8_f64,
+		9_f64, 10_f64, 11_f64, 12_f64,
+		13_f64, 14_f64, 15_f64, 16_f64
+	);
+	let b = alg::matrix4(
+		1_f64, 5_f64, 9_f64, 13_f64,
+		2_f64, 6_f64, 10_f64, 14_f64,
+		3_f64, 7_f64, 11_f64, 15_f64,
+		4_f64, 8_f64, 12_f64, 16_f64
+	);
+	assert_eq!(a.transpose(), b);
+	assert_eq!(a.trace(), 34_f64);
+}
+
+#[test]
+fn test_matrix4_minor_cofactor() {
+	let a = alg::matrix4(
+		2_f64, 0_f64, 0_f64, 0_f64,
+		0_f64, 3_f64, 1_f64, 0_f64,
+		0_f64, 4_f64, 5_f64, 0_f64,
+		1_f64, 0_f64, 0_f64, 6_f64
+	);
+	assert_eq!(a.minor(0, 0), 66_f64);
+	assert_eq!(a.minor(3, 0), 0_f64);
+	assert_eq!(a.minor(0, 3), 11_f64);
+	assert_eq!(a.cofactor(0, 3), -11_f64);
+	assert_eq!(a.cofactor(1, 1), 60_f64);
+}
+
+#[test]
+fn test_matrix4_adjugate() {
+	let mut seed = 1_u64;
+	let a = random_matrix4(&mut seed);
+	let b = a * a.adjugate();
+	let c: alg::Matrix4<f64> = alg::scale(alg::det(a));
+	assert!(alg::close_eps(&b, &c, 0.00001_f64));
+}
+
+#[test]
+fn test_matrix4_inv_random() {
+	let mut seed = 42_u64;
+	let one: alg::Matrix4<f64> = std::num::one();
+	for _ in range(0, 20) {
+		let a = random_matrix4(&mut seed);
+		assert!(alg::close_eps(&(a * alg::inv(a)), &one, 0.00001_f64));
+		assert!(alg::close_eps(&(alg::inv(a) * a), &one, 0.00001_f64));
+	}
+}
+
+#[test]
+fn test_try_inv_scalar() {
+	let eps = 0.00001_f64;
+	assert_eq!(alg::try_inv(2_f64, eps), Some(0.5_f64));
+	assert_eq!(alg::try_inv(0.000001_f64, eps), None);
+	assert_eq!(alg::try_inv(4_f32, eps), Some(0.25_f32));
+	assert_eq!(alg::try_div(1_f64, 4_f64, eps), Some(0.25_f64));
+	assert_eq!(alg::try_div(1_f64, 0_f64, eps), None);
+}
+
+#[test]
+fn test_try_inv_complex_quaternion() {
+	let eps = 0.00001_f64;
+	let a = alg::complex(3_f64, 4_f64);
+	assert_eq!(alg::try_inv(a, eps), Some(alg::inv(a)));
+	assert_eq!(alg::try_inv(alg::complex(0_f64, 0_f64), eps), None);
+	let b = alg::complex(1_f64, 2_f64);
+	assert_eq!(alg::try_div(b, a, eps), Some(b / a));
+	let q = alg::quaternion(1_f64, 2_f64, 3_f64, 4_f64);
+	assert_eq!(alg::try_inv(q, eps), Some(alg::inv(q)));
+	let z: alg::Quaternion<f64> = std::num::zero();
+	assert_eq!(alg::try_inv(z, eps), None);
+	assert_eq!(alg::try_div(q, z, eps), None);
+}
+
+#[test]
+fn test_try_inv_matrix4() {
+	let eps = 0.00001_f64;
+	let mut seed = 7_u64;
+	let a = random_matrix4(&mut seed);
+	assert_eq!(alg::try_inv(a, eps), Some(alg::inv(a)));
+	// The last row is the sum of the first two rows.
+	let b = alg::matrix4(
+		1_f64, 2_f64, 3_f64, 4_f64,
+		0_f64, 1_f64, 5_f64, 2_f64,
+		2_f64, 7_f64, 1_f64, 3_f64,
+		1_f64, 3_f64, 8_f64, 6_f64
+	);
+	assert_eq!(alg::try_inv(b, eps), None);
+	assert_eq!(alg::try_div(a, b, eps), None);
+	// Singularity does not depend on the scale.
+	let c = alg::matrix4(
+		0.01_f64, 0_f64, 0_f64, 0_f64,
+		0_f64, 0.01_f64, 0_f64, 0_f64,
+		0_f64, 0_f64, 0.01_f64, 0_f64,
+		0_f64, 0_f64, 0_f64, 0.01_f64
+	);
+	assert_eq!(alg::try_inv(c, eps), Some(alg::inv(c)));
+}
+
+#[test]
+fn test_try_inv_dual_vector() {
+	let eps = 0.00001_f64;
+	let a = alg::dual2(2_f64, 1_f64);
+	assert_eq!(alg::try_inv(a, eps), Some(alg::inv(a)));
+	assert_eq!(alg::try_inv(alg::dual2(0_f64, 1_f64), eps), None);
+	let v = alg::vector(~[1_f64, 2_f64, 4_f64]);
+	assert_eq!(alg::try_inv(v, eps), Some(alg::vector(~[1_f64, 0.5_f64, 0.25_f64])));
+	let w = alg::vector(~[1_f64, 0_f64, 4_f64]);
+	assert_eq!(alg::try_inv(w, eps), None);
+	let v = alg::vector(~[1_f64, 2_f64, 4_f64]);
+	let w = alg::vector(~[1_f64, 0_f64, 4_f64]);
+	assert_eq!(alg::try_div(v, w, eps), None);
+}
+
+#[test]
+fn test_matrix2_inv() {
+	let a = alg::matrix2(4_f64, 7_f64, 2_f64, 6_f64);
+	assert_eq!(alg::det(a), 10_f64);
+	let b = alg::matrix2(0.6_f64, -0.7_f64, -0.2_f64, 0.4_f64);
+	assert!(alg::close_eps(&alg::inv(a), &b, 0.00001_f64));
+	let one: alg::Matrix2<f64> = std::num::one();
+	assert!(alg::close_eps(&(a * alg::inv(a)), &one, 0.00001_f64));
+	assert_eq!(a.transform((1_f64, 1_f64)), (11_f64, 8_f64));
+}
+
+#[test]
+fn test_matrix3_inv() {
+	let a = alg::matrix3(
+		2_f64, 0_f64, 1_f64,
+		1_f64, 3_f64, 2_f64,
+		1_f64, 1_f64, 2_f64
+	);
+	assert_eq!(alg::det(a), 6_f64);
+	let one: alg::Matrix3<f64> = std::num::one();
+	assert!(alg::close_eps(&(a * alg::inv(a)), &one, 0.00001_f64));
+	let b = alg::matrix3(
+		1_f64, 2_f64, 3_f64,
+		2_f64, 4_f64, 6_f64,
+		0_f64, 1_f64, 1_f64
+	);
+	assert_eq!(alg::try_inv(b, 0.00001_f64), None);
+	let small = alg::matrix3(
+		0.01_f64, 0_f64, 0_f64,
+		0_f64, 0.01_f64, 0_f64,
+		0_f64, 0_f64, 0.01_f64
+	);
+	assert_eq!(alg::try_inv(small, 0.00001_f64), Some(alg::inv(small)));
+
+	let mut seed = 3_u64;
+	let m = random_matrix4(&mut seed);
+	let c = alg::Matrix3::from_matrix4(&m);
+	assert_eq!(c.minor(0, 0), m.get(1, 1) * m.get(2, 2) - m.get(1, 2) * m.get(2, 1));
+	assert!(alg::close_eps(&(alg::inv(c) * c), &one, 0.00001_f64));
+}
+
+#[test]
+fn test_matrix3x4_mul_div() {
+	let mut seed = 11_u64;
+	let m = random_matrix4(&mut seed);
+	let n = random_matrix4(&mut seed);
+	let a = alg::Matrix3x4::from_matrix4(&m);
+	let b = alg::Matrix3x4::from_matrix4(&(m * n));
+	assert!(alg::close_eps(&a.mul_matrix4(&n), &b, 0.00001_f64));
+	assert!(alg::close_eps(&(a / n).mul_matrix4(&n), &a, 0.00001_f64));
+
+	let c = a * a.transpose();
+	assert!(alg::close_eps(&c, &c.transpose(), 0.00001_f64));
+	let d = a.transpose() * a;
+	assert!(alg::close_eps(&d, &d.transpose(), 0.00001_f64));
+	assert!(alg::close_eps(&alg::Matrix4x3::from_matrix4(&m.transpose()), &a.transpose(), 0.00001_f64));
+}
+
+#[test]
+fn test_matrix2_ops() {
+	let a = alg::matrix2(1_f64, 2_f64, 3_f64, 4_f64);
+	let b = alg::matrix2(5_f64, 6_f64, 7_f64, 8_f64);
+	assert_eq!(a + b, alg::matrix2(6_f64, 8_f64, 10_f64, 12_f64));
+	assert_eq!(b - a, alg::matrix2(4_f64, 4_f64, 4_f64, 4_f64));
+	assert_eq!(-a, alg::matrix2(-1_f64, -2_f64, -3_f64, -4_f64));
+	assert_eq!(a * b, alg::matrix2(19_f64, 22_f64, 43_f64, 50_f64));
+	assert!(alg::close_eps(&((a * b) / b), &a, 0.00001_f64));
+	assert!(alg::close_eps(&a, &alg::matrix2(1.000001_f64, 2_f64, 3_f64, 4_f64), 0.00001_f64));
+	assert!(!alg::close_eps(&a, &b, 0.00001_f64));
+	assert_eq!(alg::try_div(a, alg::matrix2(1_f64, 2_f64, 2_f64, 4_f64), 0.00001_f64), None);
+}
+
+#[test]
+fn test_matrix3_ops() {
+	let a = alg::matrix3(
+		1_f64, 2_f64, 3_f64,
+		4_f64, 5_f64, 6_f64,
+		7_f64, 8_f64, 10_f64
+	);
+	let one: alg::Matrix3<f64> = std::num::one();
+	assert_eq!(a + one, alg::matrix3(
+		2_f64, 2_f64, 3_f64,
+		4_f64, 6_f64, 6_f64,
+		7_f64, 8_f64, 11_f64
+	));
+	assert_eq!(a - a, std::num::zero());
+	assert_eq!(-(-a), a);
+	assert_eq!(a * one, a);
+	assert!(alg::close_eps(&(a / a), &one, 0.00001_f64));
+	assert!(!alg::close_eps(&a, &one, 0.00001_f64));
+	assert_eq!(a.transform(&alg::vector3(1_f64, 0_f64, 0_f64)), alg::vector3(1_f64, 4_f64, 7_f64));
+}
+
+#[test]
+fn test_matrix3x4_matrix4x3_ops() {
+	let mut seed = 13_u64;
+	let m = random_matrix4(&mut seed);
+	let n = random_matrix4(&mut seed);
+	let a = alg::Matrix3x4::from_matrix4(&m);
+	let b = alg::Matrix3x4::from_matrix4(&n);
+	assert!(alg::close_eps(&(a + b), &alg::Matrix3x4::from_matrix4(&(m + n)), 0.00001_f64));
+	assert!(alg::close_eps(&(a - b), &alg::Matrix3x4::from_matrix4(&(m - n)), 0.00001_f64));
+	assert!(alg::close_eps(&-a, &alg::Matrix3x4::from_matrix4(&-m), 0.00001_f64));
+	assert!(!alg::close_eps(&a, &b, 0.00001_f64));
+
+	let c = alg::Matrix4x3::from_matrix4(&m);
+	let d = alg::Matrix4x3::from_matrix4(&n);
+	assert!(alg::close_eps(&(c + d), &alg::Matrix4x3::from_matrix4(&(m + n)), 0.00001_f64));
+	assert!(alg::close_eps(&(c - d), &alg::Matrix4x3::from_matrix4(&(m - n)), 0.00001_f64));
+	assert!(alg::close_eps(&-c, &alg::Matrix4x3::from_matrix4(&-m), 0.00001_f64));
+	assert!(!alg::close_eps(&c, &d, 0.00001_f64));
+
+	let e = alg::Matrix3::from_matrix4(&n);
+	let f = c.mul_matrix3(&e);
+	for i in range(0, 4) {
+		for j in range(0, 3) {
+			let mut sum = 0_f64;
+			for k in range(0, 3) { sum = sum + c.get(i, k) * e.get(k, j); }
+			assert!(alg::close_eps(&f.get(i, j), &sum, 0.00001_f64));
+		}
+	}
+	assert!(alg::close_eps(&(f / e), &c, 0.00001_f64));
+	assert!(alg::close_eps(&alg::try_div(f, e, 0.00001_f64).unwrap(), &c, 0.00001_f64));
+	let one: alg::Matrix3<f64> = std::num::one();
+	assert!(alg::close_eps(&c.mul_matrix3(&one), &c, 0.00001_f64));
+}
+
+#[test]
+fn test_matrix_conversions() {
+	let a = alg::matrix2(1_f64, 2_f64, 3_f64, 4_f64);
+	let b = alg::Matrix3::from_matrix2(&a);
+	assert_eq!(b.row(2), [0_f64, 0_f64, 1_f64]);
+	assert_eq!(alg::Matrix2::from_matrix3(&b), a);
+	let c = alg::Matrix4::from_matrix3(&b);
+	assert_eq!(alg::Matrix3::from_matrix4(&c), b);
+	assert_eq!(c.get(3, 3), 1_f64);
+
+	let t: alg::Matrix4<f64> = alg::Matrix4::translation((1_f64, 2_f64, 3_f64));
+	let d = alg::Matrix3x4::from_matrix4(&t);
+	assert_eq!(alg::Matrix4::from_matrix3x4(&d), t);
+	let p = d.transform_point(&alg::vector3(1_f64, 1_f64, 1_f64));
+	assert_eq!(p, alg::vector3(2_f64, 3_f64, 4_f64));
+	let e = d.transpose();
+	assert_eq!(alg::Matrix4::from_matrix4x3(&e), t.transpose());
+}
+
+fn random_matrix(n: uint, seed: &mut u64) -> alg::Matrix<f64> {
+	alg::Matrix::from_fn(n, n, |i, j| {
+		let r = random_number(seed);
+		if i == j { r + n as f64 } else { r }
+	})
+}
+
+#[test]
+fn test_matrix_shape() {
+	let mut a = alg::matrix(2, 3, ~[1_f64, 2_f64, 3_f64, 4_f64, 5_f64, 6_f64]);
+	assert_eq!(a.shape(), (2, 3));
+	assert_eq!(a.get(1, 0), 4_f64);
+	assert_eq!(a.row_slice(1)[2], 6_f64);
+	assert_eq!(a.col(1), alg::vector(~[2_f64, 5_f64]));
+	a.set_row(0, &alg::vector(~[7_f64, 8_f64, 9_f64]));
+	assert_eq!(a.row(0), alg::vector(~[7_f64, 8_f64, 9_f64]));
+	let b = a.transpose();
+	assert_eq!(b.shape(), (3, 2));
+	assert_eq!(b.get(2, 1), 6_f64);
+}
+
+#[test]
+#[should_fail]
+fn test_matrix_add_shape_mismatch() {
+	let a: alg::Matrix<f64> = alg::Matrix::zeros(2, 3);
+	let b: alg::Matrix<f64> = alg::Matrix::zeros(3, 2);
+	let _ = a + b;
+}
+
+#[test]
+fn test_matrix_product() {
+	let a = alg::matrix(2, 3, ~[1_f64, 2_f64, 3_f64, 4_f64, 5_f64, 6_f64]);
+	let b = alg::matrix(3, 2, ~[7_f64, 8_f64, 9_f64, 10_f64, 11_f64, 12_f64]);
+	assert_eq!(a * b, alg::matrix(2, 2, ~[58_f64, 64_f64, 139_f64, 154_f64]));
+	assert_eq!(a.transform(&alg::vector(~[1_f64, 0_f64, 1_f64])), alg::vector(~[4_f64, 10_f64]));
+	assert_eq!(a.mul_elem(&a), alg::matrix(2, 3, ~[1_f64, 4_f64, 9_f64, 16_f64, 25_f64, 36_f64]));
+	assert_eq!(alg::norm_sq(a), 91_f64);
+
+	let mut seed = 5_u64;
+	let m = random_matrix4(&mut seed);
+	let n = random_matrix4(&mut seed);
+	let c = alg::Matrix::from_matrix4(&m) * alg::Matrix::from_matrix4(&n);
+	assert!(alg::close_eps(&c.to_matrix4(), &(m * n), 0.00001_f64));
+}
+
+#[test]
+fn test_matrix_det_inv() {
+	let mut seed = 9_u64;
+	let m = random_matrix4(&mut seed);
+	let a = alg::Matrix::from_matrix4(&m);
+	assert!(alg::close_eps(&alg::det(a.clone()), &alg::det(m), 0.00001_f64));
+
+	let b = random_matrix(7, &mut seed);
+	let one: alg::Matrix<f64> = alg::Matrix::identity(7);
+	assert!(alg::close_eps(&(b * alg::inv(b.clone())), &one, 0.00001_f64));
+
+	let c = alg::matrix(3, 3, ~[
+		1_f64, 2_f64, 3_f64,
+		2_f64, 4_f64, 6_f64,
+		0_f64, 1_f64, 1_f64
+	]);
+	assert_eq!(alg::det(c.clone()), 0_f64);
+	assert_eq!(alg::try_inv(c, 0.00001_f64), None);
+	let small: alg::Matrix<f64> = alg::Matrix::from_fn(6, 6, |i, j| if i == j { 0.01_f64 } else { 0_f64 });
+	assert!(alg::try_inv(small, 0.00001_f64).is_some());
+}
+
+#[test]
+fn test_lu_factors() {
+	let mut seed = 13_u64;
+	let a = random_matrix(5, &mut seed);
+	let lu = a.lu();
+	assert!(alg::close_eps(&(lu.p() * a), &(lu.l() * lu.u()), 0.00001_f64));
+	assert!(alg::close_eps(&lu.det(), &alg::det(a.clone()), 0.00001_f64));
+
+	let m = random_matrix4(&mut seed);
+	assert!(alg::close_eps(&m.lu().det(), &alg::det(m), 0.00001_f64));
+}
+
+#[test]
+fn test_lu_solve() {
+	let mut seed = 17_u64;
+	let a = random_matrix(6, &mut seed);
+	let b = alg::vector(~[1_f64, 2_f64, 3_f64, 4_f64, 5_f64, 6_f64]);
+	let x = a.lu().solve(&b);
+	assert!(alg::close_eps(&a.transform(&x), &b, 0.00001_f64));
+
+	let m = random_matrix4(&mut seed);
+	let c = alg::vector4(1_f64, -2_f64, 3_f64, -4_f64);
+	let y = m.solve(&c);
+	assert!(alg::close_eps(&m.transform(&y), &c, 0.00001_f64));
+}
+
+#[test]
+fn test_lu_rank_deficient() {
+	let a = alg::matrix(3, 3, ~[
+		1_f64, 2_f64, 3_f64,
+		2_f64, 4_f64, 6_f64,
+		0_f64, 1_f64, 1_f64
+	]);
+	let lu = a.lu();
+	assert_eq!(lu.rank(0.00001_f64), 2);
+	assert!(lu.is_singular(0.00001_f64));
+	assert_eq!(lu.try_solve(&alg::vector(~[1_f64, 2_f64, 3_f64]), 0.00001_f64), None);
+	assert_eq!(lu.det(), 0_f64);
+}
+
+#[test]
+fn test_lu_refinement() {
+	// Partial pivoting doubles the last column at each step of this matrix,
+	// so the unrefined solution loses all precision.
+	let n = 60;
+	let a: alg::Matrix<f64> = alg::Matrix::from_fn(n, n, |i, j| {
+		if i == j || j == n - 1 { 1_f64 } else if i > j { -1_f64 } else { 0_f64 }
+	});
+	let x = alg::vector(std::vec::from_fn(n, |i| ((i * 7) % 11) as f64 - 5_f64));
+	let b = a.transform(&x);
+	let lu = a.lu();
+	assert!(!alg::close_eps(&lu.solve(&b), &x, 0.00001_f64));
+	assert!(alg::close_eps(&lu.solve_refined(&a, &b, 2), &x, 0.00001_f64));
+	assert!(alg::close_eps(&lu.solve_refined(&a, &b, 0), &lu.solve(&b), 0.00001_f64));
+}
+
+fn random_rect_matrix(m: uint, n: uint, seed: &mut u64) -> alg::Matrix<f64> {
+	alg::Matrix::from_fn(m, n, |_, _| random_number(seed))
+}
+
+#[test]
+fn test_qr_factors() {
+	let mut seed = 19_u64;
+	let a = random_rect_matrix(6, 4, &mut seed);
+	for qr in [a.qr(), a.qr_pivoted()].iter() {
+		let q = qr.q();
+		let one: alg::Matrix<f64> = alg::Matrix::identity(6);
+		assert!(alg::close_eps(&(q.transpose() * q), &one, 0.00001_f64));
+		assert!(alg::close_eps(&(a * qr.p()), &(q * qr.r()), 0.00001_f64));
+		let r = qr.r();
+		for i in range(1u, 6) {
+			for j in range(0u, if i < 4 { i } else { 4 }) {
+				assert_eq!(r.get(i, j), 0_f64);
+			}
+		}
+	}
+}
+
+#[test]
+fn test_qr_rank() {
+	let mut seed = 23_u64;
+	let mut a = random_rect_matrix(5, 3, &mut seed);
+	// The third column is the sum of the first two columns.
+	let c = a.col(0) + a.col(1);
+	a.set_col(2, &c);
+	assert_eq!(a.qr_pivoted().rank(0.00001_f64), 2);
+	assert_eq!(random_rect_matrix(5, 3, &mut seed).qr_pivoted().rank(0.00001_f64), 3);
+}
+
+#[test]
+fn test_least_squares() {
+	let mut seed = 29_u64;
+	let a = random_rect_matrix(10, 3, &mut seed);
+	let b = alg::vector(std::vec::from_fn(10, |i| i as f64));
+	let x = alg::least_squares(&a, &b);
+
+	// Compare with the solution of the normal equations.
+	let at = a.transpose();
+	let y = alg::inv(at * a).transform(&at.transform(&b));
+	assert!(alg::close_eps(&x, &y, 0.00001_f64));
+	let rx = alg::norm_sq(b - a.transform(&x));
+	let ry = alg::norm_sq(b - a.transform(&y));
+	assert!(alg::close_eps(&rx, &ry, 0.00001_f64));
+
+	// The residual is orthogonal to the columns.
+	let r = b - a.transform(&x);
+	let zero = alg::vector(~[0_f64, 0_f64, 0_f64]);
+	assert!(alg::close_eps(&at.transform(&r), &zero, 0.00001_f64));
+}
+
+fn random_spd_matrix(n: uint, seed: &mut u64) -> alg::Matrix<f64> {
+	let a = random_rect_matrix(n, n, seed);
+	a * a.transpose() + alg::Matrix::identity(n)
+}
+
+#[test]
+fn test_cholesky() {
+	let mut seed = 31_u64;
+	let a = random_spd_matrix(5, &mut seed);
+	assert!(a.is_positive_definite(0.00001_f64));
+	let c = a.cholesky().unwrap();
+	let l = c.l();
+	assert!(alg::close_eps(&(l * l.transpose()), &a, 0.00001_f64));
+	let b = alg::vector(~[1_f64, -1_f64, 2_f64, -2_f64, 3_f64]);
+	assert!(alg::close_eps(&a.transform(&c.solve(&b)), &b, 0.00001_f64));
+	assert!(alg::close_eps(&c.det(), &alg::det(a.clone()), 0.00001_f64));
+	assert!(alg::close_eps(&c.log_det(), &alg::det(a.clone()).ln(), 0.00001_f64));
+
+	// Symmetric but indefinite.
+	let d = alg::matrix(2, 2, ~[1_f64, 2_f64, 2_f64, 1_f64]);
+	assert!(d.cholesky().is_none());
+	assert!(!d.is_positive_definite(0.00001_f64));
+	let m: alg::Matrix4<f64> = alg::scale(2_f64);
+	assert!(m.cholesky().is_some());
+}
+
+#[test]
+fn test_cholesky_update_downdate() {
+	let mut seed = 37_u64;
+	let a = random_spd_matrix(4, &mut seed);
+	let x = alg::vector(~[0.5_f64, -0.25_f64, 0.125_f64, 1_f64]);
+	let xx = alg::matrix(4, 1, x.to_vec()) * alg::matrix(1, 4, x.to_vec());
+	let c = a.cholesky().unwrap();
+
+	let up = c.update(&x);
+	let expected = (a + xx).cholesky().unwrap();
+	assert!(alg::close_eps(&up.l(), &expected.l(), 0.00001_f64));
+
+	let down = up.downdate(&x).unwrap();
+	assert!(alg::close_eps(&down.l(), &c.l(), 0.00001_f64));
+
+	let big = alg::vector(~[10_f64, 10_f64, 10_f64, 10_f64]);
+	assert!(c.downdate(&big).is_none());
+}
+
+#[test]
+fn test_ldl() {
+	let mut seed = 41_u64;
+	let a = random_spd_matrix(5, &mut seed);
+	let f = a.ldl().unwrap();
+	assert!(f.is_positive_definite());
+	let d: alg::Matrix<f64> = alg::Matrix::from_fn(5, 5, |i, j| if i == j { f.d().get(i) } else { 0_f64 });
+	assert!(alg::close_eps(&(f.l() * d * f.l().transpose()), &a, 0.00001_f64));
+	let b = alg::vector(~[1_f64, 2_f64, 3_f64, 4_f64, 5_f64]);
+	assert!(alg::close_eps(&a.transform(&f.solve(&b)), &b, 0.00001_f64));
+	assert!(alg::close_eps(&f.log_det(), &a.cholesky().unwrap().log_det(), 0.00001_f64));
+
+	// Indefinite matrices have an LDL^T decomposition too.
+	let e = alg::matrix(2, 2, ~[1_f64, 2_f64, 2_f64, 1_f64]);
+	let g = e.ldl().unwrap();
+	assert!(!g.is_positive_definite());
+	assert!(alg::close_eps(&g.det(), &-3_f64, 0.00001_f64));
+
+	let x = alg::vector(~[1_f64, -1_f64, 0.5_f64, 2_f64, 0_f64]);
+	let xx = alg::matrix(5, 1, x.to_vec()) * alg::matrix(1, 5, x.to_vec());
+	let h = f.update(&x, -0.5_f64).unwrap();
+	let xx_half: alg::Matrix<f64> = alg::Matrix::from_fn(5, 5, |i, j| 0.5_f64 * xx.get(i, j));
+	let expected = a - xx_half;
+	assert!(alg::close_eps(&h.solve(&b), &expected.lu().solve(&b), 0.00001_f64));
+}
+
+#[test]
+fn test_symmetric_eigen_matrix3() {
+	let a = alg::matrix3(
+		2_f64, 1_f64, 0_f64,
+		1_f64, 2_f64, 0_f64,
+		0_f64, 0_f64, 5_f64
+	);
+	let (d, v) = a.symmetric_eigen();
+	assert!(alg::close_eps(&d, &alg::vector3(1_f64, 3_f64, 5_f64), 0.00001_f64));
+	let one: alg::Matrix3<f64> = std::num::one();
+	assert!(alg::close_eps(&(v.transpose() * v), &one, 0.00001_f64));
+	let (x, y, z) = d.to_tuple();
+	let e = alg::matrix3(x, 0_f64, 0_f64, 0_f64, y, 0_f64, 0_f64, 0_f64, z);
+	assert!(alg::close_eps(&(v * e * v.transpose()), &a, 0.00001_f64));
+}
+
+#[test]
+fn test_symmetric_eigen_matrix4() {
+	let mut seed = 43_u64;
+	let m = random_matrix4(&mut seed);
+	let a = m + m.transpose();
+	let (d, v) = a.symmetric_eigen();
+	let one: alg::Matrix4<f64> = std::num::one();
+	assert!(alg::close_eps(&(v.transpose() * v), &one, 0.00001_f64));
+	let (x, y, z, w) = d.to_tuple();
+	assert!(x <= y && y <= z && z <= w);
+	let e = alg::matrix4(
+		x, 0_f64, 0_f64, 0_f64,
+		0_f64, y, 0_f64, 0_f64,
+		0_f64, 0_f64, z, 0_f64,
+		0_f64, 0_f64, 0_f64, w
+	);
+	assert!(alg::close_eps(&(v * e * v.transpose()), &a, 0.00001_f64));
+}
+
+#[test]
+fn test_symmetric_eigen_matrix() {
+	let mut seed = 47_u64;
+	let n = 7;
+	let m = random_rect_matrix(n, n, &mut seed);
+	let a = m + m.transpose();
+	let (d, v) = a.symmetric_eigen();
+	let one: alg::Matrix<f64> = alg::Matrix::identity(n);
+	assert!(alg::close_eps(&(v.transpose() * v), &one, 0.00001_f64));
+	for i in range(1, n) {
+		assert!(d.get(i - 1) <= d.get(i));
+	}
+	let e: alg::Matrix<f64> = alg::Matrix::from_fn(n, n, |i, j| if i == j { d.get(i) } else { 0_f64 });
+	assert!(alg::close_eps(&(v * e * v.transpose()), &a, 0.00001_f64));
+
+	// The trace equals the sum of the eigenvalues.
+	let sum = d.as_slice().iter().fold(0_f64, |s, x| s + *x);
+	assert!(alg::close_eps(&sum, &a.trace(), 0.00001_f64));
+}
+
+fn svd_reconstruct(svd: &alg::SVD<f64>) -> alg::Matrix<f64> {
+	let s = svd.s();
+	let d: alg::Matrix<f64> = alg::Matrix::from_fn(s.len(), s.len(), |i, j| if i == j { s.get(i) } else { 0_f64 });
+	svd.u() * d * svd.v().transpose()
+}
+
+#[test]
+fn test_svd() {
+	let mut seed = 53_u64;
+	for &(m, n) in [(6u, 4u), (3u, 5u), (4u, 4u)].iter() {
+		let a = random_rect_matrix(m, n, &mut seed);
+		let svd = a.svd();
+		assert!(alg::close_eps(&svd_reconstruct(&svd), &a, 0.00001_f64));
+		let k = if m < n { m } else { n };
+		let one: alg::Matrix<f64> = alg::Matrix::identity(k);
+		assert!(alg::close_eps(&(svd.u().transpose() * svd.u()), &one, 0.00001_f64));
+		assert!(alg::close_eps(&(svd.v().transpose() * svd.v()), &one, 0.00001_f64));
+		for i in range(1, k) {
+			assert!(svd.s().get(i - 1) >= svd.s().get(i));
+		}
+	}
+}
+
+#[test]
+fn test_svd_norms() {
+	let a = alg::matrix(3, 3, ~[
+		0_f64, 2_f64, 0_f64,
+		-4_f64, 0_f64, 0_f64,
+		0_f64, 0_f64, 1_f64
+	]);
+	assert!(alg::close_eps(&a.spectral_norm(), &4_f64, 0.00001_f64));
+	assert!(alg::close_eps(&a.cond(), &4_f64, 0.00001_f64));
+	assert_eq!(a.rank(0.00001_f64), 3);
+
+	let mut b = a.clone();
+	b.set(2, 2, 0_f64);
+	assert_eq!(b.rank(0.00001_f64), 2);
+	assert!(b.cond().is_infinite());
+}
+
+#[test]
+fn test_pinv() {
+	let mut seed = 59_u64;
+	let m = random_matrix4(&mut seed);
+	assert!(alg::close_eps(&alg::pinv(m, 0.00001_f64), &alg::inv(m), 0.00001_f64));
+
+	// Rank deficient, so the inverse does not exist.
+	let mut a = random_rect_matrix(5, 3, &mut seed);
+	let c = a.col(0) - a.col(1);
+	a.set_col(2, &c);
+	let p = alg::pinv(a.clone(), 0.00001_f64);
+	assert_eq!(p.shape(), (3, 5));
+	assert!(alg::close_eps(&(a * p * a), &a, 0.00001_f64));
+	assert!(alg::close_eps(&(p * a * p), &p, 0.00001_f64));
+	let ap = a * p;
+	assert!(alg::close_eps(&ap, &ap.transpose(), 0.00001_f64));
+
+	assert_eq!(alg::pinv(4_f64, 0.00001_f64), 0.25_f64);
+	assert_eq!(alg::pinv(0_f64, 0.00001_f64), 0_f64);
+	assert_eq!(alg::pinv(0.0625_f64, 0.1_f64), 16_f64);
+}
+
+#[test]
+fn test_svd_rank_deficient() {
+	let mut seed = 59_u64;
+	let mut a = random_rect_matrix(5, 3, &mut seed);
+	let c = a.col(0) - a.col(1);
+	a.set_col(2, &c);
+	let one: alg::Matrix<f64> = alg::Matrix::identity(3);
+	let svd = a.svd();
+	assert!(alg::close_eps(&svd_reconstruct(&svd), &a, 0.00001_f64));
+	assert!(alg::close_eps(&(svd.u().transpose() * svd.u()), &one, 0.00001_f64));
+	let svd = a.transpose().svd();
+	assert!(alg::close_eps(&(svd.v().transpose() * svd.v()), &one, 0.00001_f64));
+	assert_eq!(a.rank(0.00001_f64), 2);
+
+	// The tolerance is relative to the largest singular value.
+	let small: alg::Matrix<f64> = alg::Matrix::from_fn(5, 3, |i, j| a.get(i, j) * 0.000001_f64);
+	assert_eq!(small.rank(0.00001_f64), 2);
+
+	let mut z = random_rect_matrix(4, 3, &mut seed);
+	for i in range(0, 4) { z.set(i, 1, 0_f64); }
+	let svd = z.svd();
+	assert!(alg::close_eps(&svd_reconstruct(&svd), &z, 0.00001_f64));
+	assert!(alg::close_eps(&(svd.u().transpose() * svd.u()), &one, 0.00001_f64));
+	assert_eq!(svd.s().get(2), 0_f64);
+}
+
+fn eigen_residual(
+	a: &alg::Matrix<f64>,
+	w: &alg::Complex<f64>,
+	v: &alg::Vector<alg::Complex<f64>>
+) -> f64 {
+	let mut sum = 0_f64;
+	for i in range(0, a.rows()) {
+		let mut r = *w * v.get(i);
+		for j in range(0, a.cols()) {
+			r = r - alg::complex(a.get(i, j), 0_f64) * v.get(j);
+		}
+		sum = sum + alg::norm_sq(r);
+	}
+	sum.sqrt()
+}
+
+#[test]
+fn test_eigenvalues_matrix4() {
+	let a = alg::matrix4(
+		0_f64, -1_f64, 0_f64, 0_f64,
+		1_f64, 0_f64, 0_f64, 0_f64,
+		0_f64, 0_f64, 2_f64, 0_f64,
+		0_f64, 0_f64, 0_f64, 3_f64
+	);
+	let ws = a.eigenvalues();
+	assert!(alg::close_eps(&ws.get(0), &alg::complex(3_f64, 0_f64), 0.00001_f64));
+	assert!(alg::close_eps(&ws.get(1), &alg::complex(2_f64, 0_f64), 0.00001_f64));
+	assert!(alg::close_eps(&ws.get(2), &alg::complex(0_f64, 1_f64), 0.00001_f64));
+	assert!(alg::close_eps(&ws.get(3), &alg::complex(0_f64, -1_f64), 0.00001_f64));
+	assert!(alg::close_eps(&a.spectral_radius(), &3_f64, 0.00001_f64));
+
+	let (ws, vs) = a.eigen();
+	let m = alg::Matrix::from_matrix4(&a);
+	for i in range(0, 4) {
+		assert!(eigen_residual(&m, &ws.get(i), &vs[i]) < 0.00001_f64);
+	}
+}
+
+#[test]
+fn test_eigen_random() {
+	let mut seed = 61_u64;
+	for &n in [1u, 2u, 5u, 8u].iter() {
+		let a = random_matrix(n, &mut seed);
+		let (ws, vs) = a.eigen();
+		assert_eq!(ws.len(), n);
+		let mut sum = alg::complex(0_f64, 0_f64);
+		let mut product = alg::complex(1_f64, 0_f64);
+		for i in range(0, n) {
+			sum = sum + ws.get(i);
+			product = product * ws.get(i);
+			assert!(eigen_residual(&a, &ws.get(i), &vs[i]) < 0.00001_f64);
+		}
+		assert!(alg::close_eps(&sum, &alg::complex(a.trace(), 0_f64), 0.00001_f64));
+		assert!(alg::close_eps(&product, &alg::complex(alg::det(a.clone()), 0_f64), 0.00001_f64));
+	}
+}
+
+#[test]
+fn test_expm() {
+	let t = 1.3_f64;
+	let a = alg::matrix4(
+		0_f64, -t, 0_f64, 0_f64,
+		t, 0_f64, 0_f64, 0_f64,
+		0_f64, 0_f64, 1_f64, 0_f64,
+		0_f64, 0_f64, 0_f64, -2_f64
+	);
+	let b = alg::matrix4(
+		t.cos(), -t.sin(), 0_f64, 0_f64,
+		t.sin(), t.cos(), 0_f64, 0_f64,
+		0_f64, 0_f64, 1_f64.exp(), 0_f64,
+		0_f64, 0_f64, 0_f64, (-2_f64).exp()
+	);
+	assert!(alg::close_eps(&a.expm(), &b, 0.00001_f64));
+
+	let zero: alg::Matrix<f64> = alg::Matrix::zeros(3, 3);
+	let one: alg::Matrix<f64> = alg::Matrix::identity(3);
+	assert!(alg::close_eps(&zero.expm(), &one, 0.00001_f64));
+
+	// The exponential of a matrix and its negative are inverses.
+	let mut seed = 67_u64;
+	let c = random_matrix(5, &mut seed);
+	let d = c.expm() * (-c).expm();
+	let one: alg::Matrix<f64> = alg::Matrix::identity(5);
+	assert!(alg::close_eps(&d, &one, 0.00001_f64));
+}
+
+#[test]
+#[should_fail]
+fn test_expm_infinite() {
+	let inf: f64 = std::num::Float::infinity();
+	let mut a: alg::Matrix<f64> = alg::Matrix::zeros(2, 2);
+	a.set(0, 1, inf);
+	let _ = a.expm();
+}
+
+#[test]
+fn test_logm_sqrtm() {
+	let mut seed = 71_u64;
+	for &n in [2u, 4u, 6u].iter() {
+		let a = random_spd_matrix(n, &mut seed);
+		assert!(alg::close_eps(&a.logm().expm(), &a, 0.00001_f64));
+		let s = a.sqrtm();
+		assert!(alg::close_eps(&(s * s), &a, 0.00001_f64));
+		assert!(alg::close_eps(&a.powm_real(0.5_f64), &s, 0.00001_f64));
+		assert!(alg::close_eps(&a.powm_real(2_f64), &(a * a), 0.00001_f64));
+	}
+
+	let m = alg::matrix4(
+		2_f64, 1_f64, 0_f64, 0_f64,
+		0_f64, 2_f64, 0_f64, 0_f64,
+		0_f64, 0_f64, 3_f64, -1_f64,
+		0_f64, 0_f64, 1_f64, 3_f64
+	);
+	assert!(alg::close_eps(&m.logm().expm(), &m, 0.00001_f64));
+	assert!(alg::close_eps(&(m.sqrtm() * m.sqrtm()), &m, 0.00001_f64));
+}
+
+#[test]
+fn test_powm() {
+	let mut seed = 73_u64;
+	let m = random_matrix4(&mut seed);
+	let one: alg::Matrix4<f64> = std::num::one();
+	assert_eq!(m.powm(0), one);
+	assert!(alg::close_eps(&m.powm(3), &(m * m * m), 0.00001_f64));
+	assert!(alg::close_eps(&m.powm(-2), &(alg::inv(m) * alg::inv(m)), 0.00001_f64));
+	assert!(alg::close_eps(&alg::powi(m, 5), &(m.powm(2) * m.powm(3)), 0.00001_f64));
+
+	let a = random_matrix(5, &mut seed);
+	assert!(alg::close_eps(&a.powm(3), &(a * a * a), 0.00001_f64));
+	assert!(alg::close_eps(&(a.powm(-1) * a), &alg::Matrix::identity(5), 0.00001_f64));
+
+	assert_eq!(alg::powi(2_f64, -2), 0.25_f64);
+	assert_eq!(alg::powi(3_f64, 3), 27_f64);
+}
+
+fn sparse_example() -> (alg::CooMatrix<f64>, alg::Matrix<f64>) {
+	let mut a = alg::coo_matrix(3, 4);
+	a.push(0, 3, 2_f64);
+	a.push(2, 1, 3_f64);
+	a.push(0, 0, 1_f64);
+	a.push(1, 2, 4_f64);
+	a.push(0, 3, 5_f64);
+	a.push(2, 0, -1_f64);
+	let b = alg::matrix(3, 4, ~[
+		1_f64, 0_f64, 0_f64, 7_f64,
+		0_f64, 0_f64, 4_f64, 0_f64,
+		-1_f64, 3_f64, 0_f64, 0_f64
+	]);
+	(a, b)
+}
+
+#[test]
+fn test_sparse_conversions() {
+	let (coo, dense) = sparse_example();
+	assert_eq!(coo.nnz(), 6);
+	assert_eq!(coo.to_matrix(), dense);
+
+	let csr = coo.to_csr();
+	assert_eq!(csr.nnz(), 5);
+	assert_eq!(csr.indptr(), &[0u, 2u, 3u, 5u]);
+	assert_eq!(csr.indices(), &[0u, 3u, 2u, 0u, 1u]);
+	assert_eq!(csr.to_matrix(), dense);
+	assert_eq!(csr.get(0, 3), 7_f64);
+	assert_eq!(csr.get(1, 1), 0_f64);
+	assert_eq!(alg::CsrMatrix::from_matrix(&dense), csr);
+	assert_eq!(csr.to_coo().to_csr(), csr);
+
+	let csc = coo.to_csc();
+	assert_eq!(csc.shape(), (3, 4));
+	assert_eq!(csc.indptr(), &[0u, 2u, 3u, 4u, 5u]);
+	assert_eq!(csc.to_matrix(), dense);
+	assert_eq!(csc.get(2, 0), -1_f64);
+	assert_eq!(alg::CscMatrix::from_matrix(&dense), csc);
+	assert_eq!(csr.to_csc(), csc);
+	assert_eq!(csc.to_csr(), csr);
+	assert_eq!(csc.to_coo().to_csc(), csc);
+
+	let raw = alg::csr_matrix(3, 4, ~[0, 2, 3, 5], ~[0, 3, 2, 0, 1],
+		~[1_f64, 7_f64, 4_f64, -1_f64, 3_f64]);
+	assert_eq!(raw, csr);
+}
+
+#[test]
+fn test_sparse_transform_transpose() {
+	let (coo, dense) = sparse_example();
+	let v = alg::vector(~[1_f64, 2_f64, 3_f64, 4_f64]);
+	let u = dense.transform(&v);
+	assert_eq!(coo.to_csr().transform(&v), u);
+	assert_eq!(coo.to_csc().transform(&v), u);
+
+	let t = dense.transpose();
+	assert_eq!(coo.to_csr().transpose().to_matrix(), t);
+	assert_eq!(coo.to_csc().transpose().to_matrix(), t);
+	assert_eq!(coo.to_csr().transpose().transpose(), coo.to_csr());
+}
+
+#[test]
+fn test_sparse_add_eps() {
+	let (coo, dense) = sparse_example();
+	let mut other = alg::coo_matrix(3, 4);
+	other.push(0, 0, 2_f64);
+	other.push(1, 1, -1_f64);
+	other.push(2, 3, 0.5_f64);
+	let a = coo.to_csr();
+	let b = other.to_csr();
+	assert_eq!((a + b).to_matrix(), dense + other.to_matrix());
+	assert_eq!((a - b).to_matrix(), dense - other.to_matrix());
+	assert_eq!((coo.to_csc() + other.to_csc()).to_matrix(), dense + other.to_matrix());
+	assert_eq!((-a).to_matrix(), -dense);
+	assert_eq!(alg::norm_sq(a.clone()), alg::norm_sq(dense.clone()));
+
+	// Missing entries compare as zero.
+	let zero: alg::CsrMatrix<f64> = alg::coo_matrix(3, 4).to_csr();
+	assert!(alg::close_eps(&(a - a), &zero, 0.00001_f64));
+	assert!(!alg::close_eps(&a, &zero, 0.00001_f64));
+	assert_eq!(a - a, zero);
+	assert!(a != zero);
+	let zero_csc: alg::CscMatrix<f64> = alg::coo_matrix(3, 4).to_csc();
+	assert_eq!(coo.to_csc() - coo.to_csc(), zero_csc);
+	let wide: alg::CsrMatrix<f64> = alg::coo_matrix(3, 5).to_csr();
+	assert!(zero != wide);
+	assert!(!alg::close_eps(&zero, &wide, 0.00001_f64));
+	let mut near = coo.clone();
+	near.push(1, 0, 0.000001_f64);
+	assert!(alg::close_eps(&near.to_csr(), &a, 0.00001_f64));
+	assert!(alg::close_eps(&near.to_csc(), &coo.to_csc(), 0.00001_f64));
+}
+
+#[test]
+#[should_fail]
+fn test_sparse_push_out_of_bounds() {
+	let mut a = alg::coo_matrix(3, 4);
+	a.push(3, 0, 1_f64);
+}
+
+fn banded_matrix(n: uint, skew: f64) -> alg::CsrMatrix<f64> {
+	let mut a = alg::coo_matrix(n, n);
+	for i in range(0, n) {
+		a.push(i, i, 4_f64);
+		if i > 0 { a.push(i, i - 1, -1_f64); }
+		if i + 1 < n { a.push(i, i + 1, -1_f64 + skew); }
+		if i + 5 < n {
+			a.push(i, i + 5, -0.5_f64);
+			a.push(i + 5, i, -0.5_f64);
+		}
+	}
+	a.to_csr()
+}
+
+fn random_vector(n: uint, seed: &mut u64) -> alg::Vector<f64> {
+	random_rect_matrix(n, 1, seed).col(0)
+}
+
+#[test]
+fn test_cg() {
+	let mut seed = 79_u64;
+	let a = banded_matrix(30, 0_f64);
+	let b = random_vector(30, &mut seed);
+	let expected = a.to_matrix().lu().solve(&b);
+
+	let res = alg::cg(&a, &b, &alg::IdentityPreconditioner, 0.0000000001_f64, 100);
+	assert!(res.converged());
+	assert!(res.iterations() <= 30);
+	assert!(res.residual() < 0.0000000001_f64);
+	assert!(alg::close_eps(&res.x(), &expected, 0.00001_f64));
+
+	let jacobi = alg::JacobiPreconditioner::from_csr(&a);
+	let res = alg::cg(&a, &b, &jacobi, 0.0000000001_f64, 100);
+	assert!(res.converged());
+	assert!(alg::close_eps(&res.into_vector(), &expected, 0.00001_f64));
+
+	// Dense matrices are operators too.
+	let dense = a.to_matrix();
+	let res = alg::cg(&dense, &b, &alg::IdentityPreconditioner, 0.0000000001_f64, 100);
+	assert!(alg::close_eps(&res.x(), &expected, 0.00001_f64));
+}
+
+#[test]
+fn test_bicgstab_gmres() {
+	let mut seed = 83_u64;
+	let a = banded_matrix(30, 0.7_f64);
+	let b = random_vector(30, &mut seed);
+	let expected = a.to_matrix().lu().solve(&b);
+	let jacobi = alg::JacobiPreconditioner::from_csr(&a);
+
+	let res = alg::bicgstab(&a, &b, &alg::IdentityPreconditioner, 0.0000000001_f64, 100);
+	assert!(res.converged());
+	assert!(alg::close_eps(&res.x(), &expected, 0.00001_f64));
+	let res = alg::bicgstab(&a, &b, &jacobi, 0.0000000001_f64, 100);
+	assert!(res.converged());
+	assert!(alg::close_eps(&res.x(), &expected, 0.00001_f64));
+
+	let res = alg::gmres(&a, &b, &alg::IdentityPreconditioner, 10, 0.0000000001_f64, 200);
+	assert!(res.converged());
+	assert!(res.residual() < 0.0000000001_f64);
+	assert!(alg::close_eps(&res.x(), &expected, 0.00001_f64));
+	let res = alg::gmres(&a.to_csc(), &b, &jacobi, 10, 0.0000000001_f64, 200);
+	assert!(res.converged());
+	assert!(alg::close_eps(&res.x(), &expected, 0.00001_f64));
+
+	// Stops early and reports it.
+	let res = alg::gmres(&a, &b, &alg::IdentityPreconditioner, 10, 0.0000000001_f64, 3);
+	assert!(!res.converged());
+	assert_eq!(res.iterations(), 3);
+	assert!(res.residual() > 0.0000000001_f64);
+}
+
+#[test]
+fn test_iterative_breakdown() {
+	let zero: alg::CsrMatrix<f64> = alg::coo_matrix(3, 3).to_csr();
+	let b = alg::vector(~[1_f64, 2_f64, 3_f64]);
+	let res = alg::cg(&zero, &b, &alg::IdentityPreconditioner, 0.0000000001_f64, 100);
+	assert!(!res.converged());
+	assert_eq!(res.iterations(), 1);
+	assert_eq!(res.residual(), 1_f64);
+	let res = alg::bicgstab(&zero, &b, &alg::IdentityPreconditioner, 0.0000000001_f64, 100);
+	assert!(!res.converged());
+	assert_eq!(res.residual(), 1_f64);
+	let res = alg::gmres(&zero, &b, &alg::IdentityPreconditioner, 10, 0.0000000001_f64, 100);
+	assert!(!res.converged());
+	assert_eq!(res.iterations(), 1);
+	assert_eq!(res.residual(), 1_f64);
+
+	// The second half step is annihilated by the operator.
+	let a = alg::matrix(2, 2, ~[1_f64, 1_f64, 0_f64, 0_f64]);
+	let b = alg::vector(~[1_f64, 1_f64]);
+	let res = alg::bicgstab(&a, &b, &alg::IdentityPreconditioner, 0.0000000001_f64, 100);
+	assert!(!res.converged());
+	assert_eq!(res.iterations(), 1);
+	assert_eq!(res.x(), alg::vector(~[1_f64, 1_f64]));
+	assert!(!res.residual().is_nan());
+}
+
+#[test]
+fn test_ilu_preconditioner() {
+	let mut seed = 89_u64;
+
+	// Without fill-in outside the pattern the factorization is exact.
+	let mut t = alg::coo_matrix(10, 10);
+	for i in range(0, 10) {
+		t.push(i, i, 3_f64);
+		if i > 0 { t.push(i, i - 1, -1_f64); }
+		if i + 1 < 10 { t.push(i, i + 1, -1.5_f64); }
+	}
+	let t = t.to_csr();
+	let x = random_vector(10, &mut seed);
+	let ilu = alg::IluPreconditioner::from_csr(&t);
+	assert!(alg::close_eps(&alg::precondition(&ilu, &alg::apply(&t, &x)), &x, 0.00001_f64));
+	let res = alg::bicgstab(&t, &alg::apply(&t, &x), &ilu, 0.0000000001_f64, 100);
+	assert!(res.converged());
+	assert_eq!(res.iterations(), 1);
+
+	let a = banded_matrix(30, 0.7_f64);
+	let b = random_vector(30, &mut seed);
+	let ilu = alg::IluPreconditioner::from_matrix(&a.to_matrix());
+	let plain = alg::bicgstab(&a, &b, &alg::IdentityPreconditioner, 0.0000000001_f64, 100);
+	let res = alg::bicgstab(&a, &b, &ilu, 0.0000000001_f64, 100);
+	assert!(res.converged());
+	assert!(res.iterations() < plain.iterations());
+	assert!(alg::close_eps(&res.x(), &a.to_matrix().lu().solve(&b), 0.00001_f64));
+}